@@ -1,38 +1,293 @@
-use std::time::{Duration, Instant};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::time::Duration;
 
-pub const METHODS: &[&str] = &["GET", "POST", "PUT", "DELETE", "PATCH", "HEAD", "OPTIONS"];
+use encoding_rs::Decoder;
+
+use crate::body::{self, BodyType, EncodedBody, JsonError};
+use crate::codegen;
+use crate::diff::{self, DiffMode, DiffRow};
+use crate::download;
+use crate::editor::Editor;
+use crate::external;
+use crate::filter;
+use crate::format::{self, Format};
+use crate::graphql::{self, Schema};
+use crate::grpc::{self, GrpcResponse, Transport};
+use crate::headers;
+use crate::highlight::{HighlightCache, Theme};
+use crate::http::{
+    self, BodySink, ClientSettings, HttpClient, RequestTimings, ResponseBody, SettingsField,
+    StreamUpdate, TimedResponse, TransferProgress,
+};
+use crate::params::{self, ParamTable, QueryParam};
+use crate::proto::Descriptors;
+use crate::search::{self, SearchMatch};
+use crate::sse::{self, EventParser};
+use crate::text;
+use crate::tls::CertificateInfo;
+use crate::tree::JsonTree;
+use crate::ws::{self, FrameType, LogEntry, WsConnection};
+
+/// Rows kept visible above a search match when jumping to it
+const SEARCH_CONTEXT_ROWS: usize = 2;
+
+pub const METHODS: &[&str] = &[
+    "GET", "POST", "PUT", "DELETE", "PATCH", "HEAD", "OPTIONS", "WS", "GRPC", "GRPC-WEB",
+];
+
+/// Custom methods kept in the selector after the built-in ones
+const MAX_RECENT_METHODS: usize = 5;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AppFocus {
     MethodSelector,
     UrlInput,
+    ParamsInput,
     HeadersInput,
     BodyInput,
     Response,
 }
 
-#[derive(Clone)]
+/// Editor pane opened in the external editor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EditTarget {
+    Headers,
+    Body,
+}
+
+impl EditTarget {
+    pub fn label(&self) -> &'static str {
+        match self {
+            EditTarget::Headers => "Headers",
+            EditTarget::Body => "Body",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResponseTab {
+    Body,
+    Tree,
+    Timing,
+    Certificate,
+    Diff,
+}
+
+impl ResponseTab {
+    pub const ALL: &'static [ResponseTab] = &[
+        ResponseTab::Body,
+        ResponseTab::Tree,
+        ResponseTab::Timing,
+        ResponseTab::Certificate,
+        ResponseTab::Diff,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            ResponseTab::Body => "Body",
+            ResponseTab::Tree => "Tree",
+            ResponseTab::Timing => "Timing",
+            ResponseTab::Certificate => "Certificate",
+            ResponseTab::Diff => "Diff",
+        }
+    }
+}
+
+/// Response kept aside to diff later responses against
+pub struct PinnedResponse {
+    /// Method, URL and status of the pinned request
+    pub label: String,
+    pub text: String,
+    pub json: Option<serde_json::Value>,
+}
+
+/// Response being streamed, shown as its chunks arrive
+struct ResponseStream {
+    updates: Receiver<StreamUpdate>,
+    /// Set when the stream is dropped, so the request thread stops reading
+    /// and closes the connection
+    stop: Arc<AtomicBool>,
+    /// Decoder for the body text, `None` for binary bodies
+    decoder: Option<Decoder>,
+    /// Parser of the events so far, for `text/event-stream` bodies
+    events: Option<EventParser>,
+    /// Wrapped rows of the complete lines of the response text
+    rows: usize,
+    /// Start of the last, unfinished line of the response text
+    last_line: usize,
+    /// Pane width `rows` was counted at
+    width: u16,
+}
+
+impl ResponseStream {
+    fn new(updates: Receiver<StreamUpdate>, stop: Arc<AtomicBool>) -> Self {
+        Self {
+            updates,
+            stop,
+            decoder: None,
+            events: None,
+            rows: 0,
+            last_line: 0,
+            width: 0,
+        }
+    }
+
+    /// Start showing a body of `content_type` again from its first byte
+    fn reset(&mut self, content_type: Option<&str>, raw: bool) {
+        self.decoder = download::text_decoder(content_type);
+        self.events = (!raw && Format::detect(content_type) == Format::EventStream)
+            .then(EventParser::default);
+        self.rows = 0;
+        self.last_line = 0;
+    }
+}
+
+impl Drop for ResponseStream {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+}
+
 pub struct App {
     pub url_input: String,
     pub cursor_position: usize,
+    /// First byte of the URL shown, so the cursor stays in view
+    pub url_scroll: usize,
+    pub params: ParamTable,
     pub response: String,
     pub response_scroll: u16,
     pub loading: bool,
     pub focus: AppFocus,
     pub should_quit: bool,
     pub http_method: String,
+    /// Position of the method in `METHODS` followed by `recent_methods`
     pub method_index: usize,
-    pub headers_input: Vec<String>,
-    pub headers_cursor_line: usize,
-    pub headers_cursor_col: usize,
-    pub headers_scroll: u16,
-    pub body_input: Vec<String>,
-    pub body_cursor_line: usize,
-    pub body_cursor_col: usize,
-    pub body_scroll: u16,
+    /// Custom method being typed in the method selector
+    pub method_edit: Option<String>,
+    /// Custom methods used recently, most recent first
+    pub recent_methods: Vec<String>,
+    pub headers: Editor,
+    pub body: Editor,
+    /// Text last copied or cut in the headers and body editors
+    pub clipboard: String,
+    pub body_type: BodyType,
+    /// Rows of a form-urlencoded body, edited in the body pane
+    pub form: ParamTable,
+    /// The body editor holds the GraphQL variables instead of the query
+    pub graphql_variables_active: bool,
+    /// GraphQL pane not shown in the body editor
+    pub graphql_parked: Editor,
+    /// Schema from the last introspection, for field completion
+    pub graphql_schema: Option<Schema>,
+    /// The response being shown answers a GraphQL request
+    pub graphql_response: bool,
     pub response_time: Option<Duration>,
     pub status_code: Option<u16>,
     pub response_size: Option<usize>,
+    pub connection_reused: Option<bool>,
+    pub timings: Option<RequestTimings>,
+    /// Server certificate chain of the last https response, leaf first.
+    /// Holds only the leaf until the rest is fetched for the Certificate tab.
+    pub certificates: Vec<CertificateInfo>,
+    /// Why the rest of the chain is missing, or that it is being fetched
+    pub chain_note: Option<String>,
+    /// Final URL of the last response, after redirects
+    certificate_url: String,
+    /// Chain fetch in flight
+    chain_pending: Option<Receiver<Result<Vec<CertificateInfo>, String>>>,
+    chain_requested: bool,
+    pub response_tab: ResponseTab,
+    /// Syntax highlighting palette for the body editor and response viewer
+    pub theme: Theme,
+    /// Highlighted response body, reused while it does not change
+    pub response_lines: HighlightCache,
+    /// Bumped whenever the text shown in the Body tab changes
+    pub response_generation: u64,
+    pub http: HttpClient,
+    pub settings_open: bool,
+    pub settings_index: usize,
+    pub settings_edit: Option<String>,
+    pub settings_error: Option<String>,
+    /// Upload progress of the request in flight, for file bodies
+    pub upload: Arc<TransferProgress>,
+    /// File body path and its size, so the body pane does not stat the file
+    /// on every frame
+    file_size: Option<(String, Option<u64>)>,
+    /// Body revision and the JSON syntax error found in it, so the body is
+    /// parsed once per edit rather than on every frame
+    body_error: Option<(u64, Option<JsonError>)>,
+    /// Download progress of the response in flight
+    pub download: Arc<TransferProgress>,
+    /// Stream response bodies straight to `download_dir` instead of showing them
+    pub download_mode: bool,
+    pub download_dir: PathBuf,
+    /// Show response bodies chunk by chunk as they arrive
+    pub stream_mode: bool,
+    /// Keep the end of a streaming response in view
+    pub stream_follow: bool,
+    /// Raw bytes of the last response, kept for saving
+    pub response_bytes: Vec<u8>,
+    pub response_content_type: Option<String>,
+    pub response_filename: String,
+    /// Show the response body as received instead of pretty-printed
+    pub response_raw: bool,
+    /// Result of the last save or download, shown in the status line
+    pub status_message: Option<String>,
+    /// Collapsible view of the last response, when it is JSON
+    pub json_tree: Option<JsonTree>,
+    /// jq or JSONPath expression applied to the response body
+    pub filter_input: String,
+    pub filter_cursor: usize,
+    pub filter_editing: bool,
+    /// Filtered response, or the error of an invalid expression
+    pub filter_output: Option<Result<String, String>>,
+    /// Last filter used for each request, keyed by method and URL without query
+    pub filters: HashMap<String, String>,
+    /// Regex searched for in the displayed response body
+    pub search_input: String,
+    pub search_cursor: usize,
+    pub search_editing: bool,
+    pub search_case_sensitive: bool,
+    pub search_matches: Vec<SearchMatch>,
+    /// Index into `search_matches` of the current match
+    pub search_index: usize,
+    pub search_error: Option<String>,
+    /// Inner width of the response pane, kept by the UI for wrap-aware scrolling
+    pub response_width: u16,
+    /// Inner height of the response pane, kept by the UI for auto-scrolling
+    pub response_height: u16,
+    /// Method, URL and status of the last response
+    pub response_label: String,
+    pub pinned: Option<PinnedResponse>,
+    pub diff_mode: DiffMode,
+    /// Pinned response compared with the current one
+    pub diff_rows: Vec<DiffRow>,
+    /// Text waiting to be copied to the system clipboard by the main loop
+    pub clipboard_request: Option<String>,
+    /// Pane waiting to be opened in `$VISUAL`/`$EDITOR` by the main loop
+    pub external_edit: Option<EditTarget>,
+    pending: Option<Receiver<reqwest::Result<TimedResponse>>>,
+    /// Introspection request in flight
+    schema_pending: Option<Receiver<Result<Schema, String>>>,
+    /// Response in flight, in stream mode
+    stream: Option<ResponseStream>,
+    /// WebSocket connection of the `WS` method, while it is open
+    pub ws: Option<WsConnection>,
+    /// Frames and connection events of the WebSocket connection
+    pub ws_log: Vec<LogEntry>,
+    /// Kind of frame the body editor sends over the WebSocket
+    pub ws_frame_type: FrameType,
+    /// Services and messages of the `.proto` files in the settings
+    pub proto: Option<Descriptors>,
+    /// gRPC call in flight
+    grpc_pending: Option<Receiver<Result<GrpcResponse, String>>>,
+    /// Response message type of the last gRPC call
+    grpc_output: String,
 }
 
 impl App {
@@ -41,8 +296,10 @@ impl App {
         let cursor_pos = default_url.len();
 
         Self {
-            url_input: default_url,
+            url_input: default_url.clone(),
+            url_scroll: 0,
             cursor_position: cursor_pos,
+            params: ParamTable::new(params::parse_query(&default_url)),
             response: "{}".to_string(),
             response_scroll: 0,
             loading: false,
@@ -50,106 +307,857 @@ impl App {
             should_quit: false,
             http_method: "GET".to_string(),
             method_index: 0,
-            headers_input: vec![String::new()],
-            headers_cursor_line: 0,
-            headers_cursor_col: 0,
-            headers_scroll: 0,
-            body_input: vec![String::new()],
-            body_cursor_line: 0,
-            body_cursor_col: 0,
-            body_scroll: 0,
+            method_edit: None,
+            recent_methods: Vec::new(),
+            headers: Editor::default(),
+            body: Editor::default(),
+            clipboard: String::new(),
+            body_type: BodyType::Json,
+            form: ParamTable::default(),
+            graphql_variables_active: false,
+            graphql_parked: Editor::default(),
+            graphql_schema: None,
+            graphql_response: false,
             response_time: None,
             status_code: None,
             response_size: None,
+            connection_reused: None,
+            timings: None,
+            certificates: Vec::new(),
+            chain_note: None,
+            certificate_url: String::new(),
+            chain_pending: None,
+            chain_requested: false,
+            response_tab: ResponseTab::Body,
+            theme: Theme::Dark,
+            response_lines: HighlightCache::default(),
+            response_generation: 0,
+            http: HttpClient::new(ClientSettings::default())
+                .expect("failed to initialize HTTP client"),
+            settings_open: false,
+            settings_index: 0,
+            settings_edit: None,
+            settings_error: None,
+            upload: Arc::new(TransferProgress::default()),
+            file_size: None,
+            body_error: None,
+            download: Arc::new(TransferProgress::default()),
+            download_mode: false,
+            download_dir: PathBuf::from("."),
+            stream_mode: false,
+            stream_follow: false,
+            response_bytes: Vec::new(),
+            response_content_type: None,
+            response_filename: String::new(),
+            response_raw: false,
+            status_message: None,
+            json_tree: None,
+            filter_input: String::new(),
+            filter_cursor: 0,
+            filter_editing: false,
+            filter_output: None,
+            filters: HashMap::new(),
+            search_input: String::new(),
+            search_cursor: 0,
+            search_editing: false,
+            search_case_sensitive: false,
+            search_matches: Vec::new(),
+            search_index: 0,
+            search_error: None,
+            response_width: 0,
+            response_height: 0,
+            response_label: String::new(),
+            pinned: None,
+            diff_mode: DiffMode::Json,
+            diff_rows: Vec::new(),
+            clipboard_request: None,
+            external_edit: None,
+            pending: None,
+            schema_pending: None,
+            stream: None,
+            ws: None,
+            ws_log: Vec::new(),
+            ws_frame_type: FrameType::Text,
+            proto: None,
+            grpc_pending: None,
+            grpc_output: String::new(),
+        }
+    }
+
+    /// Number of entries in the method selector
+    pub fn method_count(&self) -> usize {
+        METHODS.len() + self.recent_methods.len()
+    }
+
+    /// Select the method at `index` in the method selector
+    pub fn select_method(&mut self, index: usize) {
+        let Some(method) = METHODS.get(index).copied().or_else(|| {
+            self.recent_methods
+                .get(index - METHODS.len())
+                .map(String::as_str)
+        }) else {
+            return;
+        };
+        self.http_method = method.to_string();
+        self.method_index = index;
+    }
+
+    pub fn previous_method(&mut self) {
+        let count = self.method_count();
+        self.select_method((self.method_index + count - 1) % count);
+    }
+
+    pub fn next_method(&mut self) {
+        self.select_method((self.method_index + 1) % self.method_count());
+    }
+
+    /// Start typing a custom method in the method selector
+    pub fn start_method_edit(&mut self, c: char) {
+        self.method_edit = Some(String::new());
+        self.method_edit_char(c);
+    }
+
+    /// Method names are case-sensitive, so the typed case is kept
+    pub fn method_edit_char(&mut self, c: char) {
+        if let Some(edit) = &mut self.method_edit {
+            edit.push(c);
+        }
+    }
+
+    pub fn method_edit_backspace(&mut self) {
+        if let Some(edit) = &mut self.method_edit {
+            edit.pop();
+        }
+    }
+
+    pub fn cancel_method_edit(&mut self) {
+        self.method_edit = None;
+        self.status_message = None;
+    }
+
+    /// Use the typed method. Custom methods are added to the front of
+    /// `recent_methods` so they stay in the selector.
+    pub fn finish_method_edit(&mut self) {
+        let Some(edit) = self.method_edit.take() else {
+            return;
+        };
+        let method = edit.trim().to_string();
+        if method.is_empty() {
+            self.status_message = None;
+            return;
+        }
+        if reqwest::Method::from_bytes(method.as_bytes()).is_err() {
+            self.status_message = Some(format!("Invalid HTTP method: {}", method));
+            self.method_edit = Some(edit);
+            return;
+        }
+
+        self.status_message = None;
+        if let Some(index) = METHODS.iter().position(|m| *m == method) {
+            self.select_method(index);
+            return;
         }
+        self.recent_methods.retain(|m| *m != method);
+        self.recent_methods.insert(0, method);
+        self.recent_methods.truncate(MAX_RECENT_METHODS);
+        self.select_method(METHODS.len());
     }
 
-    pub fn send_request(&mut self) {
+    /// Send the request on a background thread; `poll_request` picks up the result
+    pub fn start_request(&mut self) {
         if self.url_input.is_empty() {
-            self.response = "Error: URL cannot be empty".to_string();
+            self.set_response("Error: URL cannot be empty".to_string());
             return;
         }
+        // A connection only carries requests for the URL it was opened for
+        if let Some(ws) = &self.ws {
+            if self.http_method != "WS" || ws.url() != self.url_input {
+                ws.close();
+                self.ws = None;
+            }
+        }
+        // Sending without a malformed line would quietly drop a header the
+        // user meant to send
+        if let Some((index, error)) = headers::errors(&self.headers.lines).into_iter().next() {
+            self.status_message = Some(format!("Invalid header on line {}: {}", index + 1, error));
+            return;
+        }
+        if self.http_method == "WS" {
+            self.ws_connect_or_send();
+            return;
+        }
+        // Keep the last response on screen; the body pane shows where
+        if let Some(error) = self.body_json_error() {
+            self.status_message = Some(format!(
+                "Invalid JSON in body at line {}: {} (Ctrl+L: go to error)",
+                error.line + 1,
+                error.message
+            ));
+            return;
+        }
+        if let Some(transport) = Transport::for_method(&self.http_method) {
+            self.grpc_call(transport);
+            return;
+        }
+
+        self.file_size = None;
+        // Checks every GraphQL pane, not only the one being edited, and
+        // also keeps the last response on screen when it fails
+        let encoded_body = match self.encode_body() {
+            Ok(encoded) => encoded,
+            Err(e) => {
+                self.status_message = Some(format!("Error: {}", e));
+                return;
+            }
+        };
 
         let url = self.url_input.clone();
         self.loading = true;
-        self.response = "Loading...".to_string();
+        self.set_response("Loading...".to_string());
+        self.clear_response();
+        // Fresh counters, so a stopped stream still finishing its last read
+        // cannot move the progress of this request
+        self.upload = Arc::default();
+        self.download = Arc::default();
+        let headers = self.request_headers();
+
+        self.graphql_response = self.body_type == BodyType::GraphQl;
+
+        // Build request with method on the shared client
+        let method = match reqwest::Method::from_bytes(self.http_method.as_bytes()) {
+            Ok(method) => method,
+            Err(_) => {
+                self.set_response(format!("Error: Invalid HTTP method: {}", self.http_method));
+                self.loading = false;
+                return;
+            }
+        };
+        let mut request = self.http.client().request(method, &url);
+
+        // Add headers
+        let has_content_type = headers
+            .iter()
+            .any(|(key, _)| key.eq_ignore_ascii_case("content-type"));
+        // Multipart sets its own Content-Type carrying the boundary
+        let multipart = matches!(encoded_body, EncodedBody::Multipart(_));
+        for (key, value) in headers {
+            if multipart && key.eq_ignore_ascii_case("content-type") {
+                continue;
+            }
+            request = request.header(key, value);
+        }
+
+        // Add body if present
+        let request = match body::apply(request, encoded_body, has_content_type, &self.upload) {
+            Ok(request) => request,
+            Err(e) => {
+                self.set_response(format!("Error: {}", e));
+                self.loading = false;
+                return;
+            }
+        };
+
+        // Send request without blocking the UI
+        let (sender, receiver) = mpsc::channel();
+        let http = self.http.clone();
+        let progress = Arc::clone(&self.download);
+        if self.download_mode {
+            let dir = self.download_dir.clone();
+            std::thread::spawn(move || {
+                let _ = sender.send(http.execute(request, &progress, BodySink::Download(&dir)));
+            });
+        } else if self.stream_mode {
+            let (updates, stream) = mpsc::channel();
+            let stop = Arc::new(AtomicBool::new(false));
+            self.stream = Some(ResponseStream::new(stream, Arc::clone(&stop)));
+            self.stream_follow = true;
+            std::thread::spawn(move || {
+                let sink = BodySink::Stream(&updates, &stop);
+                let _ = sender.send(http.execute(request, &progress, sink));
+            });
+        } else {
+            std::thread::spawn(move || {
+                let _ = sender.send(http.execute(request, &progress, BodySink::Memory));
+            });
+        }
+        self.pending = Some(receiver);
+    }
+
+    /// Encode the body editor text according to the body type
+    fn encode_body(&self) -> Result<EncodedBody, String> {
+        match self.body_type {
+            BodyType::GraphQl => {
+                body::encode_graphql(&self.graphql_query(), &self.graphql_variables())
+            }
+            BodyType::FormUrlEncoded => Ok(body::encode_form(&self.form.rows)),
+            _ => body::encode(self.body_type, &self.body.text()),
+        }
+    }
+
+    /// Size of the file a file body points at, looked up again when the
+    /// path changes or a request is sent
+    pub fn body_file_size(&mut self) -> Option<u64> {
+        let text = self.body.text();
+        let path = body::file_path(&text);
+        match &self.file_size {
+            Some((cached, size)) if cached == path => *size,
+            _ => {
+                let size = body::file_size(path).ok();
+                self.file_size = Some((path.to_string(), size));
+                size
+            }
+        }
+    }
+
+    /// Whether the body pane shows the form table instead of the editor
+    pub fn form_body_active(&self) -> bool {
+        self.body_type == BodyType::FormUrlEncoded
+            && self.http_method != "WS"
+            && !self.http_method.starts_with("GRPC")
+    }
+
+    /// Copy the request as a curl command
+    pub fn copy_as_curl(&mut self) {
+        if self.http_method == "WS" || self.http_method.starts_with("GRPC") {
+            self.status_message = Some("Copy as curl works for HTTP methods only".to_string());
+            return;
+        }
+        match self.encode_body() {
+            Ok(body) => {
+                let headers = self.request_headers();
+                self.clipboard_request = Some(codegen::curl(
+                    &self.http_method,
+                    &self.url_input,
+                    &headers,
+                    &body,
+                ));
+                self.status_message = Some("Copied as curl".to_string());
+            }
+            Err(e) => self.status_message = Some(format!("Error: {}", e)),
+        }
+    }
+
+    /// Copy the response text shown in the Body tab
+    pub fn copy_response(&mut self) {
+        self.clipboard_request = Some(self.displayed_response().to_string());
+        self.status_message = Some("Copied response body".to_string());
+    }
+
+    pub fn copy_url(&mut self) {
+        self.clipboard_request = Some(self.url_input.clone());
+        self.status_message = Some("Copied URL".to_string());
+    }
+
+    /// Insert pasted text into whatever is being edited. Single-line fields
+    /// take it without line breaks.
+    pub fn paste(&mut self, pasted: &str) {
+        let line: String = pasted
+            .chars()
+            .filter(|c| *c != '\r' && *c != '\n')
+            .collect();
+        if self.settings_open {
+            if let Some(edit) = &mut self.settings_edit {
+                edit.push_str(&line);
+            }
+            return;
+        }
+        if self.filter_editing {
+            line.chars().for_each(|c| self.filter_insert_char(c));
+            return;
+        }
+        if self.search_editing {
+            line.chars().for_each(|c| self.search_insert_char(c));
+            return;
+        }
+        if self.method_edit.is_some() {
+            line.chars().for_each(|c| self.method_edit_char(c));
+            return;
+        }
+        if self.loading {
+            return;
+        }
+        match self.focus {
+            AppFocus::UrlInput => {
+                text::insert(&mut self.url_input, &mut self.cursor_position, &line);
+                self.sync_params_from_url();
+            }
+            AppFocus::ParamsInput => {
+                self.params.insert_str(&line);
+                self.sync_url_from_params();
+            }
+            AppFocus::HeadersInput => self.headers.insert_str(pasted),
+            AppFocus::BodyInput if self.form_body_active() => self.form.insert_str(&line),
+            AppFocus::BodyInput => self.body.insert_str(pasted),
+            AppFocus::MethodSelector | AppFocus::Response => {}
+        }
+    }
+
+    pub fn editor_mut(&mut self, target: EditTarget) -> &mut Editor {
+        match target {
+            EditTarget::Headers => &mut self.headers,
+            EditTarget::Body => &mut self.body,
+        }
+    }
+
+    /// Copy the editor selection, or its cursor line, to the clipboard
+    pub fn editor_copy(&mut self, target: EditTarget) {
+        let copied = self.editor_mut(target).copy();
+        self.clipboard = copied.clone();
+        self.clipboard_request = Some(copied);
+    }
+
+    pub fn editor_cut(&mut self, target: EditTarget) {
+        let cut = self.editor_mut(target).cut();
+        self.clipboard = cut.clone();
+        self.clipboard_request = Some(cut);
+    }
+
+    /// Paste the text last copied in Jorna; text copied elsewhere arrives
+    /// through the terminal's own paste
+    pub fn editor_paste(&mut self, target: EditTarget) {
+        let clipboard = self.clipboard.clone();
+        self.editor_mut(target).insert_str(&clipboard);
+    }
+
+    /// Replace the response text, highlighting it again on the next frame
+    fn set_response(&mut self, text: String) {
+        self.response = text;
+        self.response_generation += 1;
+    }
+
+    /// Forget everything about the previous response
+    fn clear_response(&mut self) {
         self.response_scroll = 0;
         self.response_time = None;
         self.status_code = None;
         self.response_size = None;
+        self.connection_reused = None;
+        self.timings = None;
+        self.certificates.clear();
+        self.chain_note = None;
+        self.certificate_url.clear();
+        self.chain_pending = None;
+        self.chain_requested = false;
+        self.response_bytes.clear();
+        self.response_content_type = None;
+        self.response_filename.clear();
+        self.status_message = None;
+        self.json_tree = None;
+        self.filter_output = None;
+        self.response_generation += 1;
+        self.search_matches.clear();
+        self.response_label.clear();
+        self.diff_rows.clear();
+        self.stream = None;
+        self.upload.reset();
+        self.download.reset();
+    }
 
-        // Parse headers from headers_input
-        let headers: Vec<(String, String)> = self
-            .headers_input
-            .iter()
-            .filter_map(|line| {
-                let parts: Vec<&str> = line.splitn(2, ": ").collect();
-                if parts.len() == 2 {
-                    Some((parts[0].trim().to_string(), parts[1].trim().to_string()))
-                } else {
-                    None
-                }
-            })
-            .collect();
+    /// `Name: value` lines of the headers editor
+    fn request_headers(&self) -> Vec<(String, String)> {
+        headers::parse(&self.headers.lines)
+    }
 
-        // Get body text from body_input
-        let body_text = self.body_input.join("\n").trim().to_string();
+    /// Open a WebSocket connection to the URL, or send the composer message
+    /// over the one already open
+    fn ws_connect_or_send(&mut self) {
+        if self.ws.as_ref().is_some_and(WsConnection::is_open) {
+            self.ws_send();
+            return;
+        }
+        self.clear_response();
+        self.ws_log.clear();
+        self.stream_follow = true;
+        self.set_response(format!("Connecting to {}...", self.url_input));
+        self.ws = Some(WsConnection::connect(
+            &self.url_input,
+            self.request_headers(),
+            &self.http.settings().tls,
+            &self.http.settings().proxy,
+        ));
+    }
 
-        // Validate JSON if body is not empty
-        if !body_text.is_empty() {
-            if let Err(e) = serde_json::from_str::<serde_json::Value>(&body_text) {
-                self.response = format!("Error: Invalid JSON in body: {}", e);
-                self.loading = false;
+    /// Send the body editor contents as a frame of `ws_frame_type`
+    pub fn ws_send(&mut self) {
+        let Some(ws) = &self.ws else {
+            return;
+        };
+        match self.ws_frame_type.encode(&self.body.text()) {
+            Ok(message) => ws.send(message),
+            Err(e) => self.status_message = Some(e),
+        }
+    }
+
+    /// Close the WebSocket connection with a normal close code
+    pub fn ws_close(&mut self) {
+        if let Some(ws) = &self.ws {
+            ws.close();
+        }
+    }
+
+    pub fn cycle_ws_frame_type(&mut self) {
+        self.ws_frame_type = self.ws_frame_type.next();
+    }
+
+    /// Add new WebSocket log entries to the response pane
+    fn poll_ws(&mut self) {
+        let Some(ws) = self.ws.as_mut() else {
+            return;
+        };
+        let entries = ws.poll();
+        if !ws.is_open() {
+            self.ws = None;
+        }
+        if entries.is_empty() {
+            return;
+        }
+        self.ws_log.extend(entries);
+        self.set_response(ws::format_log(&self.ws_log));
+        if self.stream_follow {
+            self.scroll_to_end();
+        }
+        self.update_search();
+    }
+
+    /// Load the `.proto` files listed in the settings
+    fn load_protos(&mut self) -> Result<&Descriptors, String> {
+        let paths = self.http.settings().proto_paths();
+        if paths.is_empty() {
+            return Err("Set the gRPC .proto files in the settings (F2)".to_string());
+        }
+        Ok(self.proto.insert(Descriptors::load(&paths)?))
+    }
+
+    /// Call the gRPC method named by the URL path with the body editor
+    /// message on a background thread
+    fn grpc_call(&mut self, transport: Transport) {
+        self.clear_response();
+        let url = self.url_input.clone();
+        let text = self.body.text();
+        let prepared = grpc::method_path(&url).and_then(|path| {
+            let descriptors = self.load_protos()?;
+            let method = descriptors
+                .method(&path)
+                .ok_or_else(|| format!("No method {} in the loaded .proto files", path))?;
+            let body = grpc::encode_request(descriptors, method, &text)?;
+            Ok((method.output.clone(), body))
+        });
+        let (output, body) = match prepared {
+            Ok(prepared) => prepared,
+            Err(e) => {
+                self.set_response(format!("Error: {}", e));
+                return;
+            }
+        };
+
+        self.grpc_output = output;
+        self.loading = true;
+        self.set_response("Loading...".to_string());
+        let headers = self.request_headers();
+        let client = self.http.client().clone();
+        let settings = self.http.settings().clone();
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(grpc::call(
+                transport, &client, &settings, &url, &headers, body,
+            ));
+        });
+        self.grpc_pending = Some(receiver);
+    }
+
+    /// Show the gRPC response once the call has finished
+    fn poll_grpc(&mut self) {
+        let Some(pending) = &self.grpc_pending else {
+            return;
+        };
+        let result = match pending.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => Err("request thread stopped".to_string()),
+        };
+        self.grpc_pending = None;
+        self.loading = false;
+        let response = match result {
+            Ok(response) => response,
+            Err(e) => {
+                self.set_response(format!("Request failed: {}", e));
                 return;
             }
+        };
+        let Some(descriptors) = &self.proto else {
+            return;
+        };
+
+        let text = grpc::render(&response, descriptors, &self.grpc_output);
+        if let [message] = &response.messages[..] {
+            self.json_tree = descriptors
+                .decode(&self.grpc_output, message)
+                .ok()
+                .map(JsonTree::new);
         }
+        self.response_label = format!(
+            "{} {} ({})",
+            self.http_method,
+            self.url_input,
+            response.status_label()
+        );
+        self.status_code = Some(response.http_status);
+        self.response_time = Some(response.elapsed);
+        self.response_size = Some(response.messages.iter().map(Vec::len).sum());
+        self.status_message = Some(format!("gRPC status: {}", response.status_label()));
+        self.response_bytes = text.clone().into_bytes();
+        self.set_response(text);
+        self.restore_filter();
+    }
 
-        // Build request with method
-        let client = reqwest::blocking::Client::new();
-        let mut request = match self.http_method.as_str() {
-            "GET" => client.get(&url),
-            "POST" => client.post(&url),
-            "PUT" => client.put(&url),
-            "DELETE" => client.delete(&url),
-            "PATCH" => client.patch(&url),
-            "HEAD" => client.head(&url),
-            "OPTIONS" => client.request(reqwest::Method::OPTIONS, &url),
-            _ => {
-                self.response = "Error: Invalid HTTP method".to_string();
-                self.loading = false;
+    /// Point the URL at the next method of the loaded services. An empty
+    /// body, or one still holding the previous method's template, is
+    /// replaced by a template of the new request message.
+    pub fn cycle_grpc_method(&mut self) {
+        let current = grpc::method_path(&self.url_input).ok();
+        let descriptors = match self.load_protos() {
+            Ok(descriptors) => descriptors,
+            Err(e) => {
+                self.status_message = Some(e);
                 return;
             }
         };
+        let methods = descriptors.methods();
+        if methods.is_empty() {
+            self.status_message = Some("No services in the loaded .proto files".to_string());
+            return;
+        }
+        let index = current.and_then(|path| methods.iter().position(|m| m.path() == path));
+        let template = |input: &str| {
+            serde_json::to_string_pretty(&descriptors.template(input)).unwrap_or_default()
+        };
+        let previous = index.map(|i| template(&methods[i].input));
+        let method = methods[index.map_or(0, |i| (i + 1) % methods.len())].clone();
+        let mut next = template(&method.input);
+        if method.client_streaming {
+            next = serde_json::to_string_pretty(&[descriptors.template(&method.input)])
+                .unwrap_or_default();
+        }
 
-        // Add headers
-        for (key, value) in headers {
-            request = request.header(key, value);
+        match grpc::with_method_path(&self.url_input, &method.path()) {
+            Ok(url) => {
+                self.url_input = url;
+                self.cursor_position = self.url_input.len();
+                self.sync_params_from_url();
+            }
+            Err(e) => {
+                self.status_message = Some(e);
+                return;
+            }
         }
+        let body = self.body.text();
+        if body.trim().is_empty() || Some(body) == previous {
+            self.body.set_text(&next);
+        }
+        self.status_message = Some(format!("{} ({})", method.path(), method.kind()));
+    }
 
-        // Add body if present
-        if !body_text.is_empty() {
-            request = request.body(body_text);
+    /// Handle the response of a background request once it has arrived
+    pub fn poll_request(&mut self) {
+        self.poll_ws();
+        self.poll_grpc();
+        self.poll_schema();
+        self.poll_chain();
+        self.poll_stream();
+        let Some(pending) = &self.pending else {
+            return;
+        };
+        match pending.try_recv() {
+            Ok(result) => {
+                self.pending = None;
+                self.finish_request(result);
+            }
+            Err(TryRecvError::Empty) => {}
+            Err(TryRecvError::Disconnected) => {
+                self.pending = None;
+                self.request_thread_stopped();
+            }
         }
+    }
 
-        // Send request
-        let start = Instant::now();
-        let response_result = request.send();
-        let elapsed = start.elapsed();
+    /// Show the chunks of a streamed response that arrived since the last poll
+    fn poll_stream(&mut self) {
+        let Some(stream) = &self.stream else {
+            return;
+        };
+        let updates: Vec<StreamUpdate> = stream.updates.try_iter().collect();
+        if updates.is_empty() {
+            return;
+        }
+        for update in updates {
+            match update {
+                StreamUpdate::Started {
+                    status,
+                    content_type,
+                } => {
+                    self.status_code = Some(status);
+                    self.response.clear();
+                    if let Some(stream) = &mut self.stream {
+                        stream.reset(content_type.as_deref(), self.response_raw);
+                    }
+                    self.response_content_type = content_type;
+                }
+                StreamUpdate::Chunk(bytes) => {
+                    self.response_bytes.extend_from_slice(&bytes);
+                    self.append_stream(&bytes);
+                }
+            }
+        }
+        self.response_generation += 1;
+        self.response_size = Some(self.response_bytes.len());
+        if self.stream_follow {
+            self.follow_stream();
+        }
+    }
+
+    /// Decode newly arrived bytes of a streamed response and add them to the
+    /// text shown, without formatting what is already there again
+    fn append_stream(&mut self, bytes: &[u8]) {
+        let Some(stream) = &mut self.stream else {
+            return;
+        };
+        let Some(decoder) = &mut stream.decoder else {
+            self.response = download::describe_binary(
+                &self.response_bytes,
+                self.response_content_type.as_deref(),
+            );
+            stream.rows = 0;
+            stream.last_line = 0;
+            return;
+        };
+        let capacity = decoder
+            .max_utf8_buffer_length(bytes.len())
+            .unwrap_or(bytes.len() * 3);
+        let mut text = String::with_capacity(capacity);
+        let _ = decoder.decode_to_string(bytes, &mut text, false);
+        match &mut stream.events {
+            Some(parser) => {
+                let events = parser.push(&text);
+                if !events.is_empty() {
+                    if !self.response.is_empty() {
+                        self.response.push_str("\n\n");
+                    }
+                    self.response.push_str(&sse::format_events(&events));
+                }
+            }
+            None => self.response.push_str(&text),
+        }
+    }
+
+    /// Keep the end of a streaming response in view, counting only the rows
+    /// of the lines added since the last poll
+    fn follow_stream(&mut self) {
+        let Some(stream) = &mut self.stream else {
+            return;
+        };
+        if self.filter_output.is_some() {
+            self.scroll_to_end();
+            return;
+        }
+        let width = self.response_width;
+        if stream.width != width || stream.last_line > self.response.len() {
+            stream.width = width;
+            stream.rows = 0;
+            stream.last_line = 0;
+        }
+        let mut start = stream.last_line;
+        while let Some(end) = self.response[start..].find('\n') {
+            stream.rows += search::wrapped_rows(&self.response[start..start + end], width);
+            start += end + 1;
+        }
+        stream.last_line = start;
+        let last = match &self.response[start..] {
+            "" => 0,
+            line => search::wrapped_rows(line, width),
+        };
+        let rows = stream.rows + last;
+        self.response_scroll = rows.saturating_sub(self.response_height as usize) as u16;
+    }
+
+    /// Stop reading a streamed response, keeping what has arrived so far.
+    /// Dropping the stream tells the request thread to stop and close the
+    /// connection.
+    pub fn stop_stream(&mut self) {
+        if self.stream.take().is_none() || self.pending.take().is_none() {
+            return;
+        }
+        self.upload.reset();
+        self.download.reset();
+        self.loading = false;
+        self.stream_follow = false;
+        self.response_size = Some(self.response_bytes.len());
+        self.json_tree = serde_json::from_slice(&self.response_bytes)
+            .ok()
+            .map(JsonTree::new);
+        self.set_response(self.format_response());
+        self.status_message = Some("Stream stopped".to_string());
+        self.update_search();
+    }
 
-        let response_text = match response_result {
+    pub fn is_streaming(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    pub fn toggle_stream_mode(&mut self) {
+        self.stream_mode = !self.stream_mode;
+    }
+
+    /// Scroll so the last line of the response is at the bottom of the pane
+    pub fn scroll_to_end(&mut self) {
+        let rows = search::row_of_line(self.displayed_response(), usize::MAX, self.response_width);
+        self.response_scroll = rows.saturating_sub(self.response_height as usize) as u16;
+    }
+
+    fn request_thread_stopped(&mut self) {
+        self.upload.reset();
+        self.download.reset();
+        self.set_response("Request failed: request thread stopped".to_string());
+        self.loading = false;
+    }
+
+    fn finish_request(&mut self, result: reqwest::Result<TimedResponse>) {
+        self.poll_stream();
+        self.stream = None;
+        self.upload.reset();
+        self.download.reset();
+        let response_text = match result {
             Ok(response) => {
-                let status = response.status();
-                self.status_code = Some(status.as_u16());
-                self.response_time = Some(elapsed);
-                match response.text() {
-                    Ok(body) => {
-                        self.response_size = Some(body.len());
-                        // Try to parse and pretty-print JSON
-                        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&body) {
-                            serde_json::to_string_pretty(&json).unwrap_or(body)
-                        } else {
-                            body
-                        }
+                self.response_label = format!(
+                    "{} {} ({})",
+                    self.http_method, self.url_input, response.status
+                );
+                self.status_code = Some(response.status);
+                self.response_time = Some(response.timings.total());
+                self.timings = Some(response.timings);
+                self.connection_reused = Some(response.connection_reused);
+                self.certificates = response.certificate.into_iter().collect();
+                self.certificate_url = response.url;
+                self.fetch_certificate_chain();
+                self.response_content_type = response.content_type;
+                self.response_filename = response.filename;
+                match response.body {
+                    Ok(ResponseBody::Bytes(bytes)) => {
+                        self.response_size = Some(bytes.len());
+                        self.json_tree = serde_json::from_slice(&bytes).ok().map(JsonTree::new);
+                        self.response_bytes = bytes;
+                        self.format_response()
+                    }
+                    Ok(ResponseBody::Saved { path, size }) => {
+                        self.response_size = Some(size as usize);
+                        self.status_message = Some(format!("Saved to {}", path.display()));
+                        format!(
+                            "Downloaded {} bytes of {} to {}",
+                            size,
+                            self.response_content_type
+                                .as_deref()
+                                .unwrap_or("unknown type"),
+                            path.display()
+                        )
                     }
                     Err(e) => format!("Error reading response: {}", e),
                 }
@@ -157,40 +1165,402 @@ impl App {
             Err(e) => format!("Request failed: {}", e),
         };
 
-        self.response = response_text;
+        self.set_response(response_text);
         self.loading = false;
+        self.restore_filter();
+    }
+
+    /// Apply the filter last used for this request to the new response
+    fn restore_filter(&mut self) {
+        self.filter_input = self
+            .filters
+            .get(&self.request_key())
+            .cloned()
+            .unwrap_or_default();
+        self.filter_cursor = self.filter_input.len();
+        self.update_filter_output();
+        self.update_diff();
+    }
+
+    /// Response body as shown in the Body tab: a hex dump for binary data,
+    /// otherwise the text pretty-printed for its Content-Type unless raw
+    fn format_response(&self) -> String {
+        let bytes = &self.response_bytes[..];
+        let content_type = self.response_content_type.as_deref();
+        let Some(body) = download::decode_text(bytes, content_type) else {
+            return download::describe_binary(bytes, content_type);
+        };
+        if self.response_raw {
+            return body;
+        }
+        if self.graphql_response {
+            if let Some(text) = graphql::format_response(&body) {
+                return text;
+            }
+        }
+        format::pretty(content_type, &body)
+    }
+
+    /// Switch the response body between raw and pretty-printed
+    pub fn toggle_response_raw(&mut self) {
+        self.response_raw = !self.response_raw;
+        self.status_message = Some(
+            if self.response_raw {
+                "Showing raw response"
+            } else {
+                "Showing pretty response"
+            }
+            .to_string(),
+        );
+        if let Some(stream) = &mut self.stream {
+            stream.reset(self.response_content_type.as_deref(), self.response_raw);
+            self.response.clear();
+            let bytes = self.response_bytes.clone();
+            self.append_stream(&bytes);
+            self.response_generation += 1;
+        } else if !self.response_bytes.is_empty() {
+            self.set_response(self.format_response());
+            self.update_filter_output();
+            self.update_diff();
+        }
+    }
+
+    /// Key under which the response filter is remembered
+    fn request_key(&self) -> String {
+        let url = self.url_input.split(['?', '#']).next().unwrap_or_default();
+        format!("{} {}", self.http_method, url)
+    }
+
+    pub fn open_filter(&mut self) {
+        self.filter_editing = true;
+        self.filter_cursor = self.filter_input.len();
+    }
+
+    /// Stop editing and remember the filter for this request
+    pub fn close_filter(&mut self) {
+        self.filter_editing = false;
+        let key = self.request_key();
+        if self.filter_input.trim().is_empty() {
+            self.filters.remove(&key);
+        } else {
+            self.filters.insert(key, self.filter_input.clone());
+        }
+    }
+
+    pub fn clear_filter(&mut self) {
+        self.filter_input.clear();
+        self.filter_cursor = 0;
+        self.close_filter();
+        self.update_filter_output();
+    }
+
+    pub fn filter_insert_char(&mut self, c: char) {
+        self.filter_input.insert(self.filter_cursor, c);
+        self.filter_cursor += c.len_utf8();
+        self.update_filter_output();
+    }
+
+    pub fn filter_backspace(&mut self) {
+        if let Some(c) = self.filter_input[..self.filter_cursor].chars().next_back() {
+            self.filter_cursor -= c.len_utf8();
+            self.filter_input.remove(self.filter_cursor);
+            self.update_filter_output();
+        }
+    }
+
+    pub fn filter_left(&mut self) {
+        if let Some(c) = self.filter_input[..self.filter_cursor].chars().next_back() {
+            self.filter_cursor -= c.len_utf8();
+        }
+    }
+
+    pub fn filter_right(&mut self) {
+        if let Some(c) = self.filter_input[self.filter_cursor..].chars().next() {
+            self.filter_cursor += c.len_utf8();
+        }
+    }
+
+    /// Re-run the filter against the parsed response
+    pub fn update_filter_output(&mut self) {
+        self.response_scroll = 0;
+        self.filter_output = if self.filter_input.trim().is_empty() {
+            None
+        } else {
+            match &self.json_tree {
+                Some(tree) => Some(filter::run(tree.root(), &self.filter_input)),
+                None => Some(Err("Response is not JSON".to_string())),
+            }
+        };
+        self.response_generation += 1;
+        self.update_search();
+    }
+
+    /// Response text currently shown in the Body tab
+    pub fn displayed_response(&self) -> &str {
+        match &self.filter_output {
+            Some(Ok(filtered)) => filtered,
+            _ => &self.response,
+        }
+    }
+
+    pub fn open_search(&mut self) {
+        self.search_editing = true;
+        self.search_cursor = self.search_input.len();
+    }
+
+    pub fn close_search(&mut self) {
+        self.search_editing = false;
+    }
+
+    pub fn clear_search(&mut self) {
+        self.search_input.clear();
+        self.search_cursor = 0;
+        self.search_editing = false;
+        self.update_search();
+    }
+
+    pub fn toggle_search_case(&mut self) {
+        self.search_case_sensitive = !self.search_case_sensitive;
+        self.update_search();
+    }
+
+    pub fn search_insert_char(&mut self, c: char) {
+        self.search_input.insert(self.search_cursor, c);
+        self.search_cursor += c.len_utf8();
+        self.update_search();
+    }
+
+    pub fn search_backspace(&mut self) {
+        if let Some(c) = self.search_input[..self.search_cursor].chars().next_back() {
+            self.search_cursor -= c.len_utf8();
+            self.search_input.remove(self.search_cursor);
+            self.update_search();
+        }
+    }
+
+    pub fn search_left(&mut self) {
+        if let Some(c) = self.search_input[..self.search_cursor].chars().next_back() {
+            self.search_cursor -= c.len_utf8();
+        }
+    }
+
+    pub fn search_right(&mut self) {
+        if let Some(c) = self.search_input[self.search_cursor..].chars().next() {
+            self.search_cursor += c.len_utf8();
+        }
+    }
+
+    /// Re-run the search and jump to the first match at or below the top of
+    /// the viewport
+    pub fn update_search(&mut self) {
+        let text = self.displayed_response();
+        let result = search::find_matches(text, &self.search_input, self.search_case_sensitive);
+        let top = search::line_at_row(text, self.response_scroll as usize, self.response_width);
+        match result {
+            Ok(matches) => {
+                self.search_index = matches.iter().position(|m| m.line >= top).unwrap_or(0);
+                self.search_matches = matches;
+                self.search_error = None;
+                self.scroll_to_match();
+            }
+            Err(e) => {
+                self.search_matches.clear();
+                self.search_error = Some(e);
+            }
+        }
+    }
+
+    pub fn search_next(&mut self) {
+        if !self.search_matches.is_empty() {
+            self.search_index = (self.search_index + 1) % self.search_matches.len();
+            self.scroll_to_match();
+        }
+    }
+
+    pub fn search_previous(&mut self) {
+        if !self.search_matches.is_empty() {
+            let len = self.search_matches.len();
+            self.search_index = (self.search_index + len - 1) % len;
+            self.scroll_to_match();
+        }
+    }
+
+    /// Scroll so the current match is near the top, with a little context
+    fn scroll_to_match(&mut self) {
+        if let Some(current) = self.search_matches.get(self.search_index) {
+            let row =
+                search::row_of_line(self.displayed_response(), current.line, self.response_width);
+            self.response_scroll = row.saturating_sub(SEARCH_CONTEXT_ROWS) as u16;
+        }
+    }
+
+    /// Keep the current response aside to diff later responses against
+    pub fn pin_response(&mut self) {
+        if self.loading || self.response_label.is_empty() {
+            self.status_message = Some("Nothing to pin yet".to_string());
+            return;
+        }
+        self.pinned = Some(PinnedResponse {
+            label: self.response_label.clone(),
+            text: self.response.clone(),
+            json: self.json_tree.as_ref().map(|tree| tree.root().clone()),
+        });
+        self.status_message = Some(format!("Pinned {}", self.response_label));
+        self.update_diff();
+    }
+
+    pub fn unpin_response(&mut self) {
+        self.pinned = None;
+        self.diff_rows.clear();
+    }
+
+    pub fn cycle_diff_mode(&mut self) {
+        self.diff_mode = self.diff_mode.next();
+        self.update_diff();
+    }
+
+    /// Diff mode in use: JSON needs both responses to be JSON
+    pub fn effective_diff_mode(&self) -> DiffMode {
+        let both_json = self.json_tree.is_some()
+            && self
+                .pinned
+                .as_ref()
+                .is_some_and(|pinned| pinned.json.is_some());
+        if both_json {
+            self.diff_mode
+        } else {
+            DiffMode::Text
+        }
+    }
+
+    fn update_diff(&mut self) {
+        let Some(pinned) = &self.pinned else {
+            return;
+        };
+        self.diff_rows = match (self.effective_diff_mode(), &pinned.json, &self.json_tree) {
+            (DiffMode::Json, Some(old), Some(tree)) => diff::json_diff(old, tree.root()),
+            _ => diff::text_diff(&pinned.text, &self.response),
+        };
+    }
+
+    /// Write the raw bytes of the last response to a new file in `download_dir`
+    pub fn save_response(&mut self) {
+        if self.status_code.is_none() || self.loading {
+            self.status_message = Some("Nothing to save yet".to_string());
+            return;
+        }
+        if self.response_bytes.is_empty() && self.response_size != Some(0) {
+            self.status_message = Some("Response was already saved to disk".to_string());
+            return;
+        }
+
+        let saved = download::create_unique(&self.download_dir, &self.response_filename)
+            .and_then(|(path, mut file)| file.write_all(&self.response_bytes).map(|()| path));
+        self.status_message = Some(match saved {
+            Ok(path) => format!("Saved to {}", path.display()),
+            Err(e) => format!(
+                "Failed to save {}: {}",
+                self.download_dir.join(&self.response_filename).display(),
+                e
+            ),
+        });
+    }
+
+    /// Copy the JSONPath of the selected tree node
+    pub fn tree_copy_path(&mut self) {
+        if let Some(path) = self.json_tree.as_ref().and_then(JsonTree::selected_path) {
+            self.status_message = Some(format!("Copied path {}", path));
+            self.clipboard_request = Some(path);
+        }
+    }
+
+    /// Copy the selected tree node as pretty-printed JSON
+    pub fn tree_copy_value(&mut self) {
+        if let Some(value) = self.json_tree.as_ref().and_then(JsonTree::selected_value) {
+            self.status_message = Some("Copied value".to_string());
+            self.clipboard_request = Some(value);
+        }
+    }
+
+    pub fn cycle_theme(&mut self) {
+        self.theme = self.theme.next();
+        self.status_message = Some(format!("Theme: {}", self.theme.label()));
+    }
+
+    pub fn toggle_download_mode(&mut self) {
+        self.download_mode = !self.download_mode;
     }
 
     pub fn handle_input_char(&mut self, c: char) {
-        self.url_input.insert(self.cursor_position, c);
-        self.cursor_position += 1;
+        text::insert(
+            &mut self.url_input,
+            &mut self.cursor_position,
+            c.encode_utf8(&mut [0; 4]),
+        );
+        self.sync_params_from_url();
     }
 
     pub fn handle_backspace(&mut self) {
-        if self.cursor_position > 0 {
-            self.cursor_position -= 1;
-            self.url_input.remove(self.cursor_position);
+        if text::remove_previous(&mut self.url_input, &mut self.cursor_position) {
+            self.sync_params_from_url();
         }
     }
 
     pub fn handle_delete(&mut self) {
-        if self.cursor_position < self.url_input.len() {
-            self.url_input.remove(self.cursor_position);
+        if text::remove_next(&mut self.url_input, self.cursor_position) {
+            self.sync_params_from_url();
         }
     }
 
-    pub fn move_cursor_left(&mut self) {
-        if self.cursor_position > 0 {
-            self.cursor_position -= 1;
+    // Query params table, kept in sync with the URL in both directions
+    pub fn sync_params_from_url(&mut self) {
+        let parsed = params::parse_query(&self.url_input);
+        let rows = params::merge(&self.params.rows, parsed);
+        self.params.set_rows(rows);
+    }
+
+    pub fn sync_url_from_params(&mut self) {
+        self.url_input = params::with_query(&self.url_input, &self.params.rows);
+        self.cursor_position = text::snap(&self.url_input, self.cursor_position);
+    }
+
+    pub fn params_insert_char(&mut self, c: char) {
+        self.params.insert_char(c);
+        self.sync_url_from_params();
+    }
+
+    pub fn params_backspace(&mut self) {
+        if self.params.backspace() {
+            self.sync_url_from_params();
         }
     }
 
-    pub fn move_cursor_right(&mut self) {
-        if self.cursor_position < self.url_input.len() {
-            self.cursor_position += 1;
+    pub fn params_delete(&mut self) {
+        if self.params.delete() {
+            self.sync_url_from_params();
+        }
+    }
+
+    pub fn params_toggle_enabled(&mut self) {
+        if self.params.toggle_enabled() {
+            self.sync_url_from_params();
         }
     }
 
+    pub fn params_delete_row(&mut self) {
+        if self.params.delete_row() {
+            self.sync_url_from_params();
+        }
+    }
+
+    pub fn move_cursor_left(&mut self) {
+        self.cursor_position = text::previous_boundary(&self.url_input, self.cursor_position);
+    }
+
+    pub fn move_cursor_right(&mut self) {
+        self.cursor_position = text::next_boundary(&self.url_input, self.cursor_position);
+    }
+
     pub fn move_cursor_to_start(&mut self) {
         self.cursor_position = 0;
     }
@@ -199,188 +1569,444 @@ impl App {
         self.cursor_position = self.url_input.len();
     }
 
-    // Multi-line text input helpers
-    pub fn handle_multiline_char(&mut self, c: char, is_headers: bool) {
-        let (lines, cursor_line, cursor_col) = if is_headers {
-            (
-                &mut self.headers_input,
-                &mut self.headers_cursor_line,
-                &mut self.headers_cursor_col,
-            )
+    /// Text and file extension to open in the external editor
+    pub fn external_edit_content(&self, target: EditTarget) -> (String, &'static str) {
+        match target {
+            EditTarget::Headers => (self.headers.text(), "txt"),
+            EditTarget::Body => (
+                self.body.text(),
+                external::extension(&self.body_content_type()),
+            ),
+        }
+    }
+
+    /// Content-Type of what the body editor holds
+    fn body_content_type(&self) -> String {
+        let content_type = if self.http_method == "WS" {
+            match self.ws_frame_type {
+                FrameType::Json => "application/json",
+                _ => "text/plain",
+            }
+        } else if self.http_method.starts_with("GRPC") {
+            "application/json"
+        } else if self.body_type == BodyType::GraphQl {
+            if self.graphql_variables_active {
+                "application/json"
+            } else {
+                "application/graphql"
+            }
         } else {
-            (
-                &mut self.body_input,
-                &mut self.body_cursor_line,
-                &mut self.body_cursor_col,
-            )
+            let header = self
+                .request_headers()
+                .into_iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case("content-type"));
+            if let Some((_, value)) = header {
+                return value;
+            }
+            self.body_type.content_type().unwrap_or("text/plain")
         };
+        content_type.to_string()
+    }
 
-        if *cursor_line >= lines.len() {
-            lines.push(String::new());
-            *cursor_line = lines.len() - 1;
+    /// Load the text saved in the external editor back into the pane, as
+    /// one undo step
+    pub fn finish_external_edit(&mut self, target: EditTarget, result: Result<String, String>) {
+        let text = match result {
+            Ok(text) => text,
+            Err(e) => {
+                self.status_message = Some(e);
+                return;
+            }
+        };
+        let editor = self.editor_mut(target);
+        if editor.text() == text {
+            self.status_message = Some(format!("{} unchanged", target.label()));
+        } else {
+            editor.set_text(&text);
+            self.status_message = Some(format!("{} updated from editor", target.label()));
         }
+    }
 
-        lines[*cursor_line].insert(*cursor_col, c);
-        *cursor_col += 1;
+    pub fn cycle_body_type(&mut self) {
+        // Other body types edit what was the GraphQL query
+        if self.graphql_variables_active {
+            self.toggle_graphql_pane();
+        }
+        self.body_type = self.body_type.next();
+        // Carry `key=value` lines over into an empty form table
+        if self.body_type == BodyType::FormUrlEncoded && self.form.rows.is_empty() {
+            if let Ok(pairs) = body::parse_form_lines(&self.body.text()) {
+                let rows = pairs
+                    .iter()
+                    .map(|(key, value)| QueryParam::new(key, value))
+                    .collect();
+                self.form = ParamTable::new(rows);
+            }
+        }
     }
 
-    pub fn handle_multiline_backspace(&mut self, is_headers: bool) {
-        let (lines, cursor_line, cursor_col) = if is_headers {
-            (
-                &mut self.headers_input,
-                &mut self.headers_cursor_line,
-                &mut self.headers_cursor_col,
-            )
+    /// Text of the GraphQL query pane
+    pub fn graphql_query(&self) -> String {
+        if self.graphql_variables_active {
+            self.graphql_parked.text()
         } else {
-            (
-                &mut self.body_input,
-                &mut self.body_cursor_line,
-                &mut self.body_cursor_col,
-            )
-        };
-
-        if *cursor_col > 0 {
-            *cursor_col -= 1;
-            lines[*cursor_line].remove(*cursor_col);
-        } else if *cursor_line > 0 {
-            let current_line = lines.remove(*cursor_line);
-            *cursor_line -= 1;
-            *cursor_col = lines[*cursor_line].len();
-            lines[*cursor_line].push_str(&current_line);
-        }
-    }
-
-    pub fn handle_multiline_enter(&mut self, is_headers: bool) {
-        let (lines, cursor_line, cursor_col) = if is_headers {
-            (
-                &mut self.headers_input,
-                &mut self.headers_cursor_line,
-                &mut self.headers_cursor_col,
-            )
-        } else {
-            (
-                &mut self.body_input,
-                &mut self.body_cursor_line,
-                &mut self.body_cursor_col,
-            )
-        };
-
-        let rest = lines[*cursor_line].split_off(*cursor_col);
-        *cursor_line += 1;
-        lines.insert(*cursor_line, rest);
-        *cursor_col = 0;
-    }
-
-    pub fn handle_multiline_up(&mut self, is_headers: bool) {
-        let (cursor_line, cursor_col, lines) = if is_headers {
-            (
-                &mut self.headers_cursor_line,
-                &mut self.headers_cursor_col,
-                &self.headers_input,
-            )
+            self.body.text()
+        }
+    }
+
+    /// Text of the GraphQL variables pane
+    pub fn graphql_variables(&self) -> String {
+        if self.graphql_variables_active {
+            self.body.text()
         } else {
-            (
-                &mut self.body_cursor_line,
-                &mut self.body_cursor_col,
-                &self.body_input,
-            )
-        };
+            self.graphql_parked.text()
+        }
+    }
+
+    /// Switch the body editor between the GraphQL query and variables
+    pub fn toggle_graphql_pane(&mut self) {
+        std::mem::swap(&mut self.body, &mut self.graphql_parked);
+        self.graphql_variables_active = !self.graphql_variables_active;
+    }
 
-        if *cursor_line > 0 {
-            *cursor_line -= 1;
-            *cursor_col = (*cursor_col).min(lines[*cursor_line].len());
+    /// Fetch the schema of the GraphQL endpoint at the URL on a background
+    /// thread, sending the request headers along
+    pub fn fetch_graphql_schema(&mut self) {
+        if self.url_input.is_empty() {
+            self.status_message = Some("Error: URL cannot be empty".to_string());
+            return;
+        }
+        let mut request = self
+            .http
+            .client()
+            .post(&self.url_input)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(serde_json::json!({ "query": graphql::INTROSPECTION_QUERY }).to_string());
+        for (key, value) in self.request_headers() {
+            request = request.header(key, value);
         }
+
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let result = request
+                .send()
+                .and_then(|response| response.text())
+                .map_err(|e| format!("Introspection failed: {}", e))
+                .and_then(|text| {
+                    serde_json::from_str(&text)
+                        .map_err(|e| format!("Introspection failed: invalid JSON: {}", e))
+                })
+                .and_then(|value| Schema::from_introspection(&value));
+            let _ = sender.send(result);
+        });
+        self.schema_pending = Some(receiver);
+        self.status_message = Some("Fetching GraphQL schema...".to_string());
     }
 
-    pub fn handle_multiline_down(&mut self, is_headers: bool) {
-        let (cursor_line, cursor_col, lines) = if is_headers {
-            (
-                &mut self.headers_cursor_line,
-                &mut self.headers_cursor_col,
-                &self.headers_input,
-            )
-        } else {
-            (
-                &mut self.body_cursor_line,
-                &mut self.body_cursor_col,
-                &self.body_input,
-            )
+    /// Store the schema once the introspection request has finished
+    fn poll_schema(&mut self) {
+        let Some(pending) = &self.schema_pending else {
+            return;
         };
-
-        if *cursor_line + 1 < lines.len() {
-            *cursor_line += 1;
-            *cursor_col = (*cursor_col).min(lines[*cursor_line].len());
+        let result = match pending.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                Err("Introspection failed: request thread stopped".to_string())
+            }
+        };
+        self.schema_pending = None;
+        match result {
+            Ok(schema) => {
+                self.status_message = Some(format!("Schema loaded: {} types", schema.type_count()));
+                self.graphql_schema = Some(schema);
+            }
+            Err(e) => self.status_message = Some(e),
         }
     }
 
-    pub fn handle_multiline_left(&mut self, is_headers: bool) {
-        let cursor_col = if is_headers {
-            &mut self.headers_cursor_col
-        } else {
-            &mut self.body_cursor_col
+    /// Complete the field name at the cursor in the GraphQL query pane.
+    /// Several matches are completed as far as they agree and listed in
+    /// the status line.
+    pub fn graphql_complete(&mut self) {
+        if self.graphql_variables_active {
+            return;
+        }
+        let Some(schema) = &self.graphql_schema else {
+            self.status_message = Some("No schema yet, fetch it with Ctrl+R".to_string());
+            return;
         };
+        let line = &self.body.lines[self.body.cursor_line];
+        let mut before_cursor = self.body.lines[..self.body.cursor_line].join("\n");
+        if self.body.cursor_line > 0 {
+            before_cursor.push('\n');
+        }
+        before_cursor.push_str(&line[..self.body.cursor_col.min(line.len())]);
 
-        if *cursor_col > 0 {
-            *cursor_col -= 1;
+        let (prefix, names) = graphql::complete(schema, &before_cursor);
+        match completion(prefix, &names) {
+            Some(insert) => {
+                self.status_message = (names.len() > 1).then(|| names.join("  "));
+                self.body.insert_str(&insert);
+            }
+            None => self.status_message = Some("No completions".to_string()),
         }
     }
 
-    pub fn handle_multiline_right(&mut self, is_headers: bool) {
-        let (cursor_line, cursor_col, lines) = if is_headers {
-            (
-                &self.headers_cursor_line,
-                &mut self.headers_cursor_col,
-                &self.headers_input,
-            )
-        } else {
-            (
-                &self.body_cursor_line,
-                &mut self.body_cursor_col,
-                &self.body_input,
-            )
-        };
+    /// Complete the header name or value at the cursor, the same way as
+    /// GraphQL fields
+    pub fn header_complete(&mut self) {
+        let (prefix, names) = headers::complete(self.headers_before_cursor());
+        match completion(prefix, &names) {
+            Some(insert) => {
+                self.status_message = (names.len() > 1).then(|| names.join("  "));
+                self.headers.insert_str(&insert);
+            }
+            None => self.status_message = Some("No completions".to_string()),
+        }
+    }
+
+    /// What `header_complete` would insert, shown after the cursor while
+    /// it is at the end of a line
+    pub fn header_suggestion(&self) -> Option<String> {
+        let line = &self.headers.lines[self.headers.cursor_line];
+        if self.headers.cursor_col < line.len() || line.trim().is_empty() {
+            return None;
+        }
+        let (prefix, names) = headers::complete(line);
+        completion(prefix, &names).filter(|insert| !insert.is_empty())
+    }
+
+    fn headers_before_cursor(&self) -> &str {
+        let line = &self.headers.lines[self.headers.cursor_line];
+        &line[..self.headers.cursor_col.min(line.len())]
+    }
+
+    /// Whether the body editor holds JSON for the current method and body type
+    fn body_is_json(&self) -> bool {
+        match self.http_method.as_str() {
+            "WS" => self.ws_frame_type == FrameType::Json,
+            method if method.starts_with("GRPC") => true,
+            _ => match self.body_type {
+                BodyType::Json => true,
+                BodyType::GraphQl => self.graphql_variables_active,
+                _ => false,
+            },
+        }
+    }
+
+    /// Syntax error in the body editor while it holds JSON
+    pub fn body_json_error(&mut self) -> Option<JsonError> {
+        if !self.body_is_json() {
+            return None;
+        }
+        let revision = self.body.revision();
+        match &self.body_error {
+            Some((checked, error)) if *checked == revision => error.clone(),
+            _ => {
+                let error = body::json_error(&self.body.text());
+                self.body_error = Some((revision, error.clone()));
+                error
+            }
+        }
+    }
 
-        if *cursor_col < lines[*cursor_line].len() {
-            *cursor_col += 1;
+    /// Move the body cursor to the JSON syntax error
+    pub fn jump_to_body_error(&mut self) {
+        match self.body_json_error() {
+            Some(error) => {
+                self.body.move_to((error.line, error.column));
+                self.status_message = Some(error.message);
+            }
+            None => self.status_message = Some("No errors in the body".to_string()),
         }
     }
 
-    pub fn ensure_body_cursor_visible(&mut self, visible_lines: usize) {
-        if visible_lines == 0 {
+    pub fn format_body_json(&mut self) {
+        if !self.body_is_json() {
+            self.status_message = Some("Body type is not JSON".to_string());
             return;
         }
-        let scroll = self.body_scroll as usize;
-        if self.body_cursor_line < scroll {
-            self.body_scroll = self.body_cursor_line as u16;
-        } else if self.body_cursor_line >= scroll + visible_lines {
-            self.body_scroll = (self.body_cursor_line - visible_lines + 1) as u16;
+        let body_text = self.body.text();
+        match serde_json::from_str::<serde_json::Value>(&body_text) {
+            Ok(json) => {
+                if let Ok(formatted) = serde_json::to_string_pretty(&json) {
+                    self.body.set_text(&formatted);
+                }
+            }
+            // The body pane marks where; say why
+            Err(_) => {
+                if let Some(error) = self.body_json_error() {
+                    self.status_message = Some(format!(
+                        "Cannot format, line {}: {}",
+                        error.line + 1,
+                        error.message
+                    ));
+                }
+            }
         }
     }
 
-    pub fn ensure_headers_cursor_visible(&mut self, visible_lines: usize) {
-        if visible_lines == 0 {
+    pub fn next_response_tab(&mut self) {
+        let index = ResponseTab::ALL
+            .iter()
+            .position(|tab| *tab == self.response_tab)
+            .unwrap_or(0);
+        self.response_tab = ResponseTab::ALL[(index + 1) % ResponseTab::ALL.len()];
+        self.fetch_certificate_chain();
+    }
+
+    pub fn previous_response_tab(&mut self) {
+        let index = ResponseTab::ALL
+            .iter()
+            .position(|tab| *tab == self.response_tab)
+            .unwrap_or(0);
+        self.response_tab =
+            ResponseTab::ALL[(index + ResponseTab::ALL.len() - 1) % ResponseTab::ALL.len()];
+        self.fetch_certificate_chain();
+    }
+
+    /// Fetch the rest of the certificate chain in the background, once per
+    /// response and only while the Certificate tab is shown
+    fn fetch_certificate_chain(&mut self) {
+        if self.response_tab != ResponseTab::Certificate
+            || self.chain_requested
+            || self.certificates.len() != 1
+        {
             return;
         }
-        let scroll = self.headers_scroll as usize;
-        if self.headers_cursor_line < scroll {
-            self.headers_scroll = self.headers_cursor_line as u16;
-        } else if self.headers_cursor_line >= scroll + visible_lines {
-            self.headers_scroll = (self.headers_cursor_line - visible_lines + 1) as u16;
+        self.chain_requested = true;
+        let settings = self.http.settings().clone();
+        let url = self.certificate_url.clone();
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let result = http::certificate_chain(&settings, &url).map_err(|e| format!("{:#}", e));
+            let _ = sender.send(result);
+        });
+        self.chain_pending = Some(receiver);
+        self.chain_note = Some("Fetching the rest of the chain...".to_string());
+    }
+
+    /// Swap in the full chain once it has arrived, as long as it belongs to
+    /// the server that answered the request
+    fn poll_chain(&mut self) {
+        let Some(pending) = &self.chain_pending else {
+            return;
+        };
+        let result = match pending.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => Err("chain fetch stopped".to_string()),
+        };
+        self.chain_pending = None;
+        self.chain_note = match result {
+            Ok(chain) if chain.first().map(|leaf| &leaf.sha256) == self.certificates.first().map(|leaf| &leaf.sha256) => {
+                self.certificates = chain;
+                None
+            }
+            Ok(_) => Some("The server presented a different certificate on a new connection, showing the leaf only".to_string()),
+            Err(e) => Some(format!("Rest of the chain unavailable: {}", e)),
+        };
+    }
+
+    // Client settings overlay
+    pub fn toggle_settings(&mut self) {
+        self.settings_open = !self.settings_open;
+        self.settings_edit = None;
+        self.settings_error = None;
+    }
+
+    pub fn settings_select_up(&mut self) {
+        if self.settings_index > 0 {
+            self.settings_index -= 1;
+        } else {
+            self.settings_index = SettingsField::ALL.len() - 1;
         }
     }
 
-    pub fn format_body_json(&mut self) {
-        let body_text = self.body_input.join("\n");
-        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&body_text) {
-            if let Ok(formatted) = serde_json::to_string_pretty(&json) {
-                self.body_input = formatted.lines().map(String::from).collect();
-                // Reset cursor to start
-                self.body_cursor_line = 0;
-                self.body_cursor_col = 0;
+    pub fn settings_select_down(&mut self) {
+        if self.settings_index < SettingsField::ALL.len() - 1 {
+            self.settings_index += 1;
+        } else {
+            self.settings_index = 0;
+        }
+    }
+
+    pub fn selected_settings_field(&self) -> SettingsField {
+        SettingsField::ALL[self.settings_index.min(SettingsField::ALL.len() - 1)]
+    }
+
+    /// Toggle the selected field, or start/commit a text edit for it
+    pub fn settings_activate(&mut self) {
+        let field = self.selected_settings_field();
+        let mut settings = self.http.settings().clone();
+
+        if field.is_toggle() {
+            field.toggle(&mut settings);
+        } else if let Some(value) = self.settings_edit.take() {
+            if let Err(e) = field.set(&mut settings, &value) {
+                self.settings_error = Some(e);
+                self.settings_edit = Some(value);
+                return;
             }
+        } else {
+            self.settings_edit = Some(field.value(&settings));
+            self.settings_error = None;
+            return;
         }
-        // If invalid JSON, do nothing silently
+
+        self.apply_client_settings(settings);
+    }
+
+    pub fn settings_cancel_edit(&mut self) {
+        self.settings_edit = None;
+        self.settings_error = None;
     }
+
+    pub fn settings_edit_char(&mut self, c: char) {
+        if let Some(value) = self.settings_edit.as_mut() {
+            value.push(c);
+        }
+    }
+
+    pub fn settings_edit_backspace(&mut self) {
+        if let Some(value) = self.settings_edit.as_mut() {
+            value.pop();
+        }
+    }
+
+    /// Rebuild the HTTP client, but only when the settings actually changed
+    pub fn apply_client_settings(&mut self, settings: ClientSettings) {
+        if &settings == self.http.settings() {
+            return;
+        }
+        match HttpClient::new(settings) {
+            Ok(http) => {
+                self.http = http;
+                self.settings_error = None;
+            }
+            Err(e) => {
+                self.settings_error = Some(format!("Failed to build client: {:#}", e));
+            }
+        }
+    }
+}
+
+/// Text to insert for a completion: what `names` have in common after the
+/// typed `prefix`, or `None` when nothing matches
+fn completion(prefix: &str, names: &[String]) -> Option<String> {
+    let first = names.first()?;
+    let common = names.iter().fold(first.as_str(), |common, name| {
+        let len = common
+            .char_indices()
+            .zip(name.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map(|((i, a), _)| i + a.len_utf8())
+            .unwrap_or(0);
+        &common[..len]
+    });
+    Some(common.get(prefix.len()..).unwrap_or("").to_string())
 }
 
 #[cfg(test)]
@@ -0,0 +1,297 @@
+use std::fs::File;
+use std::sync::Arc;
+
+use reqwest::blocking::multipart::{Form, Part};
+use reqwest::blocking::{Body, RequestBuilder};
+
+use crate::graphql;
+use crate::http::{ProgressReader, TransferProgress};
+use crate::params::QueryParam;
+use crate::text;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BodyType {
+    Json,
+    Text,
+    Xml,
+    FormUrlEncoded,
+    Multipart,
+    File,
+    /// Query and variables sent as a JSON request
+    GraphQl,
+}
+
+impl BodyType {
+    pub const ALL: &'static [BodyType] = &[
+        BodyType::Json,
+        BodyType::Text,
+        BodyType::Xml,
+        BodyType::FormUrlEncoded,
+        BodyType::Multipart,
+        BodyType::File,
+        BodyType::GraphQl,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            BodyType::Json => "JSON",
+            BodyType::Text => "Text",
+            BodyType::Xml => "XML",
+            BodyType::FormUrlEncoded => "Form",
+            BodyType::Multipart => "Multipart",
+            BodyType::File => "File",
+            BodyType::GraphQl => "GraphQL",
+        }
+    }
+
+    /// Hint shown in the body pane title for the expected editor format
+    pub fn hint(&self) -> &'static str {
+        match self {
+            BodyType::FormUrlEncoded => "key = value",
+            BodyType::Multipart => "name=value or name=@path per line",
+            BodyType::File => "path on the first line",
+            _ => "",
+        }
+    }
+
+    pub fn next(&self) -> BodyType {
+        let index = Self::ALL.iter().position(|t| t == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Default Content-Type; multipart sets its own including the boundary and
+    /// file bodies guess one from the file extension
+    pub fn content_type(&self) -> Option<&'static str> {
+        match self {
+            BodyType::Json | BodyType::GraphQl => Some("application/json"),
+            BodyType::Text => Some("text/plain; charset=utf-8"),
+            BodyType::Xml => Some("application/xml"),
+            BodyType::FormUrlEncoded => Some("application/x-www-form-urlencoded"),
+            BodyType::Multipart | BodyType::File => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormValue {
+    Text(String),
+    File(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EncodedBody {
+    Empty,
+    Raw {
+        content_type: &'static str,
+        data: String,
+    },
+    Multipart(Vec<(String, FormValue)>),
+    File {
+        content_type: String,
+        path: String,
+    },
+}
+
+/// Parse `key=value` lines, skipping blank lines
+pub fn parse_form_lines(text: &str) -> Result<Vec<(String, String)>, String> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| match line.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => {
+                Ok((key.trim().to_string(), value.trim().to_string()))
+            }
+            _ => Err(format!("Line {}: expected key=value", i + 1)),
+        })
+        .collect()
+}
+
+/// Encode the body editor text for the given body type. Text and XML are
+/// sent exactly as typed.
+pub fn encode(body_type: BodyType, text: &str) -> Result<EncodedBody, String> {
+    if text.trim().is_empty() {
+        return Ok(EncodedBody::Empty);
+    }
+
+    match body_type {
+        BodyType::Json => {
+            let text = text.trim();
+            serde_json::from_str::<serde_json::Value>(text)
+                .map_err(|e| format!("Invalid JSON in body: {}", e))?;
+            Ok(raw(body_type, text.to_string()))
+        }
+        BodyType::Text | BodyType::Xml => Ok(raw(body_type, text.to_string())),
+        BodyType::FormUrlEncoded => {
+            let rows: Vec<QueryParam> = parse_form_lines(text)
+                .map_err(|e| format!("Invalid form body: {}", e))?
+                .iter()
+                .map(|(key, value)| QueryParam::new(key, value))
+                .collect();
+            Ok(encode_form(&rows))
+        }
+        BodyType::Multipart => {
+            let parts = parse_form_lines(text)
+                .map_err(|e| format!("Invalid multipart body: {}", e))?
+                .into_iter()
+                .map(|(name, value)| match value.strip_prefix('@') {
+                    Some(path) => (name, FormValue::File(path.to_string())),
+                    None => (name, FormValue::Text(value)),
+                })
+                .collect();
+            Ok(EncodedBody::Multipart(parts))
+        }
+        BodyType::File => {
+            let path = text
+                .trim()
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string();
+            Ok(EncodedBody::File {
+                content_type: guess_content_type(&path),
+                path,
+            })
+        }
+        BodyType::GraphQl => encode_graphql(text, ""),
+    }
+}
+
+/// Encode the enabled, non-blank rows of the form table
+pub fn encode_form(rows: &[QueryParam]) -> EncodedBody {
+    let pairs = rows
+        .iter()
+        .filter(|row| row.enabled && !row.is_blank())
+        .map(|row| (row.key.as_str(), row.value.as_str()));
+    let data = reqwest::Url::parse_with_params("http://form", pairs)
+        .ok()
+        .and_then(|url| url.query().map(str::to_string))
+        .unwrap_or_default();
+    if data.is_empty() {
+        EncodedBody::Empty
+    } else {
+        raw(BodyType::FormUrlEncoded, data)
+    }
+}
+
+/// Where and why JSON in an editor fails to parse
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonError {
+    /// Editor line, from 0
+    pub line: usize,
+    /// Byte offset in the line, on a character boundary
+    pub column: usize,
+    pub message: String,
+}
+
+/// First syntax error in `text`, or `None` when it is valid JSON or blank
+pub fn json_error(text: &str) -> Option<JsonError> {
+    if text.trim().is_empty() {
+        return None;
+    }
+    let error = serde_json::from_str::<serde_json::Value>(text).err()?;
+    let line = error.line().saturating_sub(1);
+    let line_text = text.split('\n').nth(line).unwrap_or_default();
+    let message = error.to_string();
+    // The location is reported separately
+    let message = match message.rsplit_once(" at line ") {
+        Some((message, _)) => message.to_string(),
+        None => message,
+    };
+    Some(JsonError {
+        line,
+        column: text::snap(line_text, error.column().saturating_sub(1)),
+        message,
+    })
+}
+
+/// Encode a GraphQL query and its variables editor text
+pub fn encode_graphql(query: &str, variables: &str) -> Result<EncodedBody, String> {
+    let data = graphql::encode(query, variables)?;
+    Ok(raw(BodyType::GraphQl, data))
+}
+
+/// Content-Type for a file based on its extension
+pub fn guess_content_type(path: &str) -> String {
+    mime_guess::from_path(path)
+        .first_or_octet_stream()
+        .essence_str()
+        .to_string()
+}
+
+/// Path a file body points at: its first non-blank line
+pub fn file_path(text: &str) -> &str {
+    text.trim().lines().next().unwrap_or_default().trim()
+}
+
+/// Size of the file a file body points at
+pub fn file_size(text: &str) -> Result<u64, String> {
+    let path = file_path(text);
+    if path.is_empty() {
+        return Err("No file selected".to_string());
+    }
+    std::fs::metadata(path)
+        .map(|metadata| metadata.len())
+        .map_err(|e| format!("Failed to read file {}: {}", path, e))
+}
+
+fn raw(body_type: BodyType, data: String) -> EncodedBody {
+    EncodedBody::Raw {
+        content_type: body_type
+            .content_type()
+            .unwrap_or("application/octet-stream"),
+        data,
+    }
+}
+
+/// Attach an encoded body to the request.
+///
+/// The default Content-Type is only added when the user has not set one.
+/// File bodies are streamed from disk and report their progress to `upload`.
+pub fn apply(
+    mut request: RequestBuilder,
+    body: EncodedBody,
+    has_content_type: bool,
+    upload: &Arc<TransferProgress>,
+) -> Result<RequestBuilder, String> {
+    match body {
+        EncodedBody::Empty => Ok(request),
+        EncodedBody::Raw { content_type, data } => {
+            if !has_content_type {
+                request = request.header(reqwest::header::CONTENT_TYPE, content_type);
+            }
+            Ok(request.body(data))
+        }
+        EncodedBody::Multipart(parts) => {
+            let mut form = Form::new();
+            for (name, value) in parts {
+                form = match value {
+                    FormValue::Text(text) => form.text(name, text),
+                    FormValue::File(path) => {
+                        let part = Part::file(&path)
+                            .map_err(|e| format!("Failed to read file part {}: {}", path, e))?;
+                        form.part(name, part)
+                    }
+                };
+            }
+            Ok(request.multipart(form))
+        }
+        EncodedBody::File { content_type, path } => {
+            let file =
+                File::open(&path).map_err(|e| format!("Failed to read file {}: {}", path, e))?;
+            let len = file
+                .metadata()
+                .map_err(|e| format!("Failed to read file {}: {}", path, e))?
+                .len();
+            if !has_content_type {
+                request = request.header(reqwest::header::CONTENT_TYPE, content_type);
+            }
+            upload.start(len);
+            let reader = ProgressReader::new(file, Arc::clone(upload));
+            Ok(request.body(Body::sized(reader, len)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,287 @@
+use super::*;
+use crate::params::QueryParam;
+
+fn echo_server() -> String {
+    crate::http::test_server::serve_raw(|request| {
+        let mut response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: {}\r\n\r\n",
+            request.len()
+        )
+        .into_bytes();
+        response.extend_from_slice(request);
+        response
+    })
+}
+
+fn send(body: EncodedBody, has_content_type: bool) -> String {
+    send_with_progress(body, has_content_type, &Arc::default())
+}
+
+fn send_with_progress(
+    body: EncodedBody,
+    has_content_type: bool,
+    upload: &Arc<TransferProgress>,
+) -> String {
+    let client = reqwest::blocking::Client::new();
+    let request = client.post(echo_server());
+    apply(request, body, has_content_type, upload)
+        .unwrap()
+        .send()
+        .unwrap()
+        .text()
+        .unwrap()
+        .to_ascii_lowercase()
+}
+
+#[test]
+fn test_body_type_cycles() {
+    let mut body_type = BodyType::Json;
+    for _ in 0..BodyType::ALL.len() {
+        body_type = body_type.next();
+    }
+
+    assert_eq!(body_type, BodyType::Json);
+    assert_eq!(BodyType::Json.next(), BodyType::Text);
+}
+
+#[test]
+fn test_content_types() {
+    assert_eq!(BodyType::Json.content_type(), Some("application/json"));
+    assert_eq!(BodyType::Xml.content_type(), Some("application/xml"));
+    assert_eq!(
+        BodyType::FormUrlEncoded.content_type(),
+        Some("application/x-www-form-urlencoded")
+    );
+    assert_eq!(BodyType::Multipart.content_type(), None);
+}
+
+#[test]
+fn test_encode_empty_body() {
+    for body_type in BodyType::ALL {
+        assert_eq!(encode(*body_type, "  \n "), Ok(EncodedBody::Empty));
+    }
+}
+
+#[test]
+fn test_encode_json_validates() {
+    let error = encode(BodyType::Json, "{invalid}").unwrap_err();
+
+    assert!(error.starts_with("Invalid JSON in body"));
+}
+
+#[test]
+fn test_encode_graphql() {
+    assert_eq!(
+        encode_graphql("{ me { id } }", "{\"a\": 1}"),
+        Ok(EncodedBody::Raw {
+            content_type: "application/json",
+            data: r#"{"query":"{ me { id } }","variables":{"a":1}}"#.to_string(),
+        })
+    );
+    assert_eq!(
+        encode(BodyType::GraphQl, "{ me { id } }"),
+        encode_graphql("{ me { id } }", "")
+    );
+}
+
+#[test]
+fn test_encode_text_and_xml_are_raw() {
+    assert_eq!(
+        encode(BodyType::Xml, "<a>{not json}</a>"),
+        Ok(EncodedBody::Raw {
+            content_type: "application/xml",
+            data: "<a>{not json}</a>".to_string(),
+        })
+    );
+    assert!(matches!(
+        encode(BodyType::Text, "hello"),
+        Ok(EncodedBody::Raw { .. })
+    ));
+}
+
+#[test]
+fn test_encode_text_is_not_trimmed() {
+    assert_eq!(
+        encode(BodyType::Text, "  indented\n\n"),
+        Ok(EncodedBody::Raw {
+            content_type: "text/plain; charset=utf-8",
+            data: "  indented\n\n".to_string(),
+        })
+    );
+}
+
+#[test]
+fn test_encode_form_rows_skips_disabled_and_blank() {
+    let mut disabled = QueryParam::new("debug", "1");
+    disabled.enabled = false;
+    let rows = vec![
+        QueryParam::new("name", "Jörn Doe"),
+        disabled,
+        QueryParam::new("", ""),
+        QueryParam::new("empty", ""),
+    ];
+
+    assert_eq!(
+        encode_form(&rows),
+        EncodedBody::Raw {
+            content_type: "application/x-www-form-urlencoded",
+            data: "name=J%C3%B6rn+Doe&empty=".to_string(),
+        }
+    );
+    assert_eq!(encode_form(&[]), EncodedBody::Empty);
+}
+
+#[test]
+fn test_encode_form_urlencoded() {
+    let encoded = encode(BodyType::FormUrlEncoded, "name=Jörn Doe\n\nq = a&b=c\n").unwrap();
+
+    assert_eq!(
+        encoded,
+        EncodedBody::Raw {
+            content_type: "application/x-www-form-urlencoded",
+            data: "name=J%C3%B6rn+Doe&q=a%26b%3Dc".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_encode_form_rejects_malformed_line() {
+    let error = encode(BodyType::FormUrlEncoded, "a=1\nnot a pair").unwrap_err();
+
+    assert!(error.contains("Line 2"));
+}
+
+#[test]
+fn test_encode_multipart_text_and_file_parts() {
+    let encoded = encode(BodyType::Multipart, "title=Report\nupload=@/tmp/report.csv").unwrap();
+
+    assert_eq!(
+        encoded,
+        EncodedBody::Multipart(vec![
+            ("title".to_string(), FormValue::Text("Report".to_string())),
+            (
+                "upload".to_string(),
+                FormValue::File("/tmp/report.csv".to_string())
+            ),
+        ])
+    );
+}
+
+#[test]
+fn test_parse_form_lines_requires_key() {
+    assert!(parse_form_lines("=value").is_err());
+}
+
+#[test]
+fn test_apply_sets_content_type() {
+    let echoed = send(encode(BodyType::Xml, "<a/>").unwrap(), false);
+
+    assert!(echoed.contains("content-type: application/xml"));
+    assert!(echoed.ends_with("<a/>"));
+}
+
+#[test]
+fn test_apply_keeps_user_content_type() {
+    let echoed = send(encode(BodyType::Xml, "<a/>").unwrap(), true);
+
+    assert!(!echoed.contains("content-type"));
+}
+
+#[test]
+fn test_apply_multipart() {
+    let path = std::env::temp_dir().join(format!("jorna-multipart-{}.txt", std::process::id()));
+    std::fs::write(&path, "file contents").unwrap();
+    let body = format!("field=value\nattachment=@{}", path.display());
+
+    let echoed = send(encode(BodyType::Multipart, &body).unwrap(), false);
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(echoed.contains("content-type: multipart/form-data; boundary="));
+    assert!(echoed.contains("name=\"field\""));
+    assert!(echoed.contains("file contents"));
+}
+
+#[test]
+fn test_apply_multipart_missing_file() {
+    let client = reqwest::blocking::Client::new();
+    let body = encode(BodyType::Multipart, "f=@/nonexistent/file.bin").unwrap();
+
+    let error = apply(
+        client.post("http://localhost"),
+        body,
+        false,
+        &Arc::default(),
+    )
+    .unwrap_err();
+
+    assert!(error.contains("/nonexistent/file.bin"));
+}
+
+#[test]
+fn test_encode_file_guesses_content_type() {
+    let body = encode(BodyType::File, "  /tmp/upload.png \nignored").unwrap();
+
+    assert_eq!(
+        body,
+        EncodedBody::File {
+            content_type: "image/png".to_string(),
+            path: "/tmp/upload.png".to_string(),
+        }
+    );
+    assert_eq!(
+        guess_content_type("data.unknownext"),
+        "application/octet-stream"
+    );
+}
+
+#[test]
+fn test_apply_file_streams_contents() {
+    let path = std::env::temp_dir().join(format!("jorna-file-body-{}.json", std::process::id()));
+    std::fs::write(&path, "{\"from\": \"disk\"}").unwrap();
+    let upload = Arc::new(TransferProgress::default());
+
+    let body = encode(BodyType::File, &path.display().to_string()).unwrap();
+    let echoed = send_with_progress(body, false, &upload);
+    let size = file_size(&path.display().to_string());
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(echoed.contains("content-type: application/json"));
+    assert!(echoed.contains("content-length: 16"));
+    assert!(echoed.ends_with("{\"from\": \"disk\"}"));
+    assert_eq!(upload.get(), Some((16, 16)));
+    assert_eq!(size, Ok(16));
+}
+
+#[test]
+fn test_file_size_errors() {
+    assert_eq!(file_size("  "), Err("No file selected".to_string()));
+    assert!(file_size("/nonexistent/file.bin")
+        .unwrap_err()
+        .contains("/nonexistent/file.bin"));
+}
+
+#[test]
+fn test_json_error_location() {
+    assert_eq!(json_error(""), None);
+    assert_eq!(json_error("  \n "), None);
+    assert_eq!(json_error("{\"a\": [1, 2]}"), None);
+
+    assert_eq!(
+        json_error("{\n  \"a\" 1\n}"),
+        Some(JsonError {
+            line: 1,
+            column: 6,
+            message: "expected `:`".to_string(),
+        })
+    );
+
+    // Columns are byte offsets on character boundaries
+    let error = json_error("{\"é\": x}").unwrap();
+    assert_eq!((error.line, error.column), (0, 7));
+    assert_eq!(error.message, "expected value");
+
+    // A missing closing brace is reported at the last character
+    let error = json_error("{\"a\": 1").unwrap();
+    assert_eq!((error.line, error.column), (0, 6));
+    assert_eq!(error.message, "EOF while parsing an object");
+}
@@ -0,0 +1,40 @@
+//! The OS clipboard, used for copies unless the session is remote.
+
+use arboard::Clipboard;
+use std::sync::{Mutex, OnceLock};
+
+/// Variables set by sshd in the remote shell
+const SSH_VARIABLES: &[&str] = &["SSH_CONNECTION", "SSH_CLIENT", "SSH_TTY"];
+
+/// Clipboard kept open for the whole session: on X11 and Wayland copied text
+/// is served by this process and would vanish with a short-lived handle
+fn clipboard() -> Option<&'static Mutex<Clipboard>> {
+    static CLIPBOARD: OnceLock<Option<Mutex<Clipboard>>> = OnceLock::new();
+    CLIPBOARD
+        .get_or_init(|| Clipboard::new().ok().map(Mutex::new))
+        .as_ref()
+}
+
+/// Whether the environment looks like an SSH session, whose OS clipboard is
+/// on the wrong machine
+fn is_remote(var: impl Fn(&str) -> bool) -> bool {
+    SSH_VARIABLES.iter().any(|name| var(name))
+}
+
+/// Put text on the OS clipboard. Returns false over SSH and when there is no
+/// clipboard, so the caller can fall back to the OSC 52 escape sequence.
+pub fn copy(text: &str) -> bool {
+    if is_remote(|name| std::env::var_os(name).is_some()) {
+        return false;
+    }
+    let Some(clipboard) = clipboard() else {
+        return false;
+    };
+    let Ok(mut clipboard) = clipboard.lock() else {
+        return false;
+    };
+    clipboard.set_text(text).is_ok()
+}
+
+#[cfg(test)]
+mod tests;
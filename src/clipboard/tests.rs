@@ -0,0 +1,8 @@
+use super::*;
+
+#[test]
+fn test_ssh_sessions_are_remote() {
+    assert!(is_remote(|name| name == "SSH_TTY"));
+    assert!(is_remote(|name| name == "SSH_CONNECTION"));
+    assert!(!is_remote(|name| name == "DISPLAY"));
+}
@@ -0,0 +1,82 @@
+//! Requests written out as code to run elsewhere.
+
+use crate::body::{EncodedBody, FormValue};
+
+/// Quote a word for a POSIX shell
+pub fn shell_quote(word: &str) -> String {
+    let plain = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c));
+    if plain {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+/// `curl` command sending the same request, one option per line
+pub fn curl(method: &str, url: &str, headers: &[(String, String)], body: &EncodedBody) -> String {
+    let mut args = vec![format!("curl {}", shell_quote(url))];
+    // A body makes curl send POST, and `--head` refuses one, so both need
+    // the method spelled out then
+    let has_body = !matches!(body, EncodedBody::Empty);
+    match method {
+        "GET" if !has_body => {}
+        "HEAD" if !has_body => args.push("--head".to_string()),
+        _ => args.push(format!("-X {}", shell_quote(method))),
+    }
+    for (key, value) in headers {
+        args.push(format!(
+            "-H {}",
+            shell_quote(&format!("{}: {}", key, value))
+        ));
+    }
+
+    let has_content_type = headers
+        .iter()
+        .any(|(key, _)| key.eq_ignore_ascii_case("content-type"));
+    let content_type = |content_type: &str| {
+        format!(
+            "-H {}",
+            shell_quote(&format!("Content-Type: {}", content_type))
+        )
+    };
+    match body {
+        EncodedBody::Empty => {}
+        EncodedBody::Raw {
+            content_type: default,
+            data,
+        } => {
+            if !has_content_type {
+                args.push(content_type(default));
+            }
+            args.push(format!("--data-raw {}", shell_quote(data)));
+        }
+        EncodedBody::Multipart(parts) => {
+            for (name, value) in parts {
+                let part = match value {
+                    FormValue::Text(text) => format!("{}={}", name, text),
+                    FormValue::File(path) => format!("{}=@{}", name, path),
+                };
+                args.push(format!("-F {}", shell_quote(&part)));
+            }
+        }
+        EncodedBody::File {
+            content_type: guessed,
+            path,
+        } => {
+            if !has_content_type {
+                args.push(content_type(guessed));
+            }
+            args.push(format!(
+                "--data-binary {}",
+                shell_quote(&format!("@{}", path))
+            ));
+        }
+    }
+    args.join(" \\\n  ")
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,70 @@
+use super::*;
+
+#[test]
+fn test_shell_quote() {
+    assert_eq!(shell_quote("https://x.io/a?b=1"), "'https://x.io/a?b=1'");
+    assert_eq!(shell_quote("https://x.io/a"), "https://x.io/a");
+    assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    assert_eq!(shell_quote(""), "''");
+}
+
+#[test]
+fn test_curl_get_with_headers() {
+    let headers = vec![("Accept".to_string(), "application/json".to_string())];
+    assert_eq!(
+        curl("GET", "https://x.io/a", &headers, &EncodedBody::Empty),
+        "curl https://x.io/a \\\n  -H 'Accept: application/json'"
+    );
+    assert_eq!(
+        curl("HEAD", "https://x.io", &[], &EncodedBody::Empty),
+        "curl https://x.io \\\n  --head"
+    );
+}
+
+#[test]
+fn test_curl_bodies() {
+    let json = EncodedBody::Raw {
+        content_type: "application/json",
+        data: r#"{"name":"O'Brien"}"#.to_string(),
+    };
+    assert_eq!(
+        curl("POST", "https://x.io", &[], &json),
+        "curl https://x.io \\\n  -X POST \\\n  -H 'Content-Type: application/json' \\\n  --data-raw '{\"name\":\"O'\\''Brien\"}'"
+    );
+
+    // A Content-Type header replaces the body type's
+    let headers = vec![("content-type".to_string(), "text/csv".to_string())];
+    let file = EncodedBody::File {
+        content_type: "text/plain".to_string(),
+        path: "/tmp/a b.csv".to_string(),
+    };
+    assert_eq!(
+        curl("PUT", "https://x.io", &headers, &file),
+        "curl https://x.io \\\n  -X PUT \\\n  -H 'content-type: text/csv' \\\n  --data-binary '@/tmp/a b.csv'"
+    );
+
+    let multipart = EncodedBody::Multipart(vec![
+        ("title".to_string(), FormValue::Text("Hi".to_string())),
+        ("file".to_string(), FormValue::File("a.png".to_string())),
+    ]);
+    assert_eq!(
+        curl("PROPPATCH", "https://x.io", &[], &multipart),
+        "curl https://x.io \\\n  -X PROPPATCH \\\n  -F title=Hi \\\n  -F file=@a.png"
+    );
+}
+
+#[test]
+fn test_curl_get_and_head_with_a_body() {
+    let body = EncodedBody::Raw {
+        content_type: "text/plain",
+        data: "q".to_string(),
+    };
+    assert_eq!(
+        curl("GET", "https://x.io", &[], &body),
+        "curl https://x.io \\\n  -X GET \\\n  -H 'Content-Type: text/plain' \\\n  --data-raw q"
+    );
+    assert_eq!(
+        curl("HEAD", "https://x.io", &[], &body),
+        "curl https://x.io \\\n  -X HEAD \\\n  -H 'Content-Type: text/plain' \\\n  --data-raw q"
+    );
+}
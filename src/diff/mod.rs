@@ -0,0 +1,243 @@
+use serde_json::Value;
+
+use crate::tree;
+
+/// Largest number of line pairs compared by the text diff; bigger changes
+/// are shown as one replaced block
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiffMode {
+    /// Line by line
+    Text,
+    /// Added, removed and changed JSON values, ignoring key order
+    Json,
+}
+
+impl DiffMode {
+    pub const ALL: &'static [DiffMode] = &[DiffMode::Json, DiffMode::Text];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DiffMode::Text => "text",
+            DiffMode::Json => "JSON",
+        }
+    }
+
+    pub fn next(&self) -> DiffMode {
+        let index = Self::ALL.iter().position(|m| m == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiffKind {
+    Same,
+    Removed,
+    Added,
+    Changed,
+}
+
+/// One row of the split pane, with the pinned side on the left
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffRow {
+    pub kind: DiffKind,
+    pub left: Option<String>,
+    pub right: Option<String>,
+}
+
+impl DiffRow {
+    fn new(kind: DiffKind, left: Option<&str>, right: Option<&str>) -> Self {
+        Self {
+            kind,
+            left: left.map(str::to_string),
+            right: right.map(str::to_string),
+        }
+    }
+}
+
+/// Line diff of two texts. Runs of removed lines followed by added lines are
+/// paired up as changed rows so they line up side by side.
+pub fn text_diff(old: &str, new: &str) -> Vec<DiffRow> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    let mut rows: Vec<DiffRow> = old[..prefix]
+        .iter()
+        .map(|line| DiffRow::new(DiffKind::Same, Some(line), Some(line)))
+        .collect();
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    for (left, right) in lcs_alignment(old_middle, new_middle) {
+        match (left, right) {
+            (Some(line), Some(_)) => {
+                flush_changes(&mut rows, &mut removed, &mut added);
+                rows.push(DiffRow::new(DiffKind::Same, Some(line), Some(line)));
+            }
+            (Some(line), None) => removed.push(line),
+            (None, Some(line)) => added.push(line),
+            (None, None) => {}
+        }
+    }
+    flush_changes(&mut rows, &mut removed, &mut added);
+    rows.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|line| DiffRow::new(DiffKind::Same, Some(line), Some(line))),
+    );
+    rows
+}
+
+fn flush_changes<'a>(
+    rows: &mut Vec<DiffRow>,
+    removed: &mut Vec<&'a str>,
+    added: &mut Vec<&'a str>,
+) {
+    for i in 0..removed.len().max(added.len()) {
+        let kind = match (removed.get(i), added.get(i)) {
+            (Some(_), Some(_)) => DiffKind::Changed,
+            (Some(_), None) => DiffKind::Removed,
+            _ => DiffKind::Added,
+        };
+        rows.push(DiffRow::new(
+            kind,
+            removed.get(i).copied(),
+            added.get(i).copied(),
+        ));
+    }
+    removed.clear();
+    added.clear();
+}
+
+/// Longest common subsequence alignment: pairs of equal lines, or a line on
+/// only one side
+fn lcs_alignment<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(Option<&'a str>, Option<&'a str>)> {
+    if old.len() * new.len() > MAX_LCS_CELLS {
+        return old
+            .iter()
+            .map(|line| (Some(*line), None))
+            .chain(new.iter().map(|line| (None, Some(*line))))
+            .collect();
+    }
+
+    // lengths[i][j] is the LCS length of old[i..] and new[j..]
+    let width = new.len() + 1;
+    let mut lengths = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i * width + j] = if old[i] == new[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut alignment = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            alignment.push((Some(old[i]), Some(new[j])));
+            i += 1;
+            j += 1;
+        } else if j == new.len()
+            || (i < old.len() && lengths[(i + 1) * width + j] >= lengths[i * width + j + 1])
+        {
+            alignment.push((Some(old[i]), None));
+            i += 1;
+        } else {
+            alignment.push((None, Some(new[j])));
+            j += 1;
+        }
+    }
+    alignment
+}
+
+/// Structural diff of two JSON values, one row per added, removed or changed
+/// path. Objects are compared by key and arrays by index.
+pub fn json_diff(old: &Value, new: &Value) -> Vec<DiffRow> {
+    let mut rows = Vec::new();
+    diff_values("$", old, new, &mut rows);
+    rows
+}
+
+fn diff_values(path: &str, old: &Value, new: &Value, rows: &mut Vec<DiffRow>) {
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(new_map)) => {
+            for (key, old_child) in old_map {
+                let child_path = tree::child_path(path, key);
+                match new_map.get(key) {
+                    Some(new_child) => diff_values(&child_path, old_child, new_child, rows),
+                    None => rows.push(entry(DiffKind::Removed, &child_path, Some(old_child), None)),
+                }
+            }
+            for (key, new_child) in new_map {
+                if !old_map.contains_key(key) {
+                    let child_path = tree::child_path(path, key);
+                    rows.push(entry(DiffKind::Added, &child_path, None, Some(new_child)));
+                }
+            }
+        }
+        (Value::Array(old_items), Value::Array(new_items)) => {
+            for i in 0..old_items.len().max(new_items.len()) {
+                let child_path = format!("{}[{}]", path, i);
+                match (old_items.get(i), new_items.get(i)) {
+                    (Some(old_child), Some(new_child)) => {
+                        diff_values(&child_path, old_child, new_child, rows)
+                    }
+                    (Some(old_child), None) => {
+                        rows.push(entry(DiffKind::Removed, &child_path, Some(old_child), None))
+                    }
+                    (None, Some(new_child)) => {
+                        rows.push(entry(DiffKind::Added, &child_path, None, Some(new_child)))
+                    }
+                    (None, None) => {}
+                }
+            }
+        }
+        _ if old != new => rows.push(entry(DiffKind::Changed, path, Some(old), Some(new))),
+        _ => {}
+    }
+}
+
+fn entry(kind: DiffKind, path: &str, old: Option<&Value>, new: Option<&Value>) -> DiffRow {
+    let describe = |value: &Value| format!("{}: {}", path, value);
+    DiffRow {
+        kind,
+        left: old.map(describe),
+        right: new.map(describe),
+    }
+}
+
+/// Counts of changed rows, e.g. "2 changed, 1 added" or "No differences"
+pub fn summary(rows: &[DiffRow]) -> String {
+    let count = |kind| rows.iter().filter(|row| row.kind == kind).count();
+    let parts: Vec<String> = [
+        (DiffKind::Changed, "changed"),
+        (DiffKind::Added, "added"),
+        (DiffKind::Removed, "removed"),
+    ]
+    .into_iter()
+    .map(|(kind, label)| (count(kind), label))
+    .filter(|(count, _)| *count > 0)
+    .map(|(count, label)| format!("{} {}", count, label))
+    .collect();
+    if parts.is_empty() {
+        "No differences".to_string()
+    } else {
+        parts.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,104 @@
+use super::*;
+use serde_json::json;
+
+fn kinds(rows: &[DiffRow]) -> Vec<DiffKind> {
+    rows.iter().map(|row| row.kind).collect()
+}
+
+#[test]
+fn test_text_diff_identical() {
+    let rows = text_diff("a\nb", "a\nb");
+    assert_eq!(kinds(&rows), vec![DiffKind::Same, DiffKind::Same]);
+    assert_eq!(summary(&rows), "No differences");
+}
+
+#[test]
+fn test_text_diff_pairs_changed_lines() {
+    let rows = text_diff("a\nb\nc", "a\nx\nc");
+    assert_eq!(
+        rows[1],
+        DiffRow::new(DiffKind::Changed, Some("b"), Some("x"))
+    );
+    assert_eq!(summary(&rows), "1 changed");
+}
+
+#[test]
+fn test_text_diff_added_and_removed_lines() {
+    let rows = text_diff("a\nb\nc\nd", "b\nc\nd\ne");
+    assert_eq!(
+        kinds(&rows),
+        vec![
+            DiffKind::Removed,
+            DiffKind::Same,
+            DiffKind::Same,
+            DiffKind::Same,
+            DiffKind::Added
+        ]
+    );
+    assert_eq!(rows[0].left.as_deref(), Some("a"));
+    assert_eq!(rows[0].right, None);
+    assert_eq!(rows[4].right.as_deref(), Some("e"));
+    assert_eq!(summary(&rows), "1 added, 1 removed");
+}
+
+#[test]
+fn test_text_diff_uneven_change_block() {
+    let rows = text_diff("a\nb\nz", "a\nx\ny\nz");
+    assert_eq!(
+        kinds(&rows),
+        vec![
+            DiffKind::Same,
+            DiffKind::Changed,
+            DiffKind::Added,
+            DiffKind::Same
+        ]
+    );
+}
+
+#[test]
+fn test_json_diff_ignores_key_order() {
+    let old: Value = serde_json::from_str(r#"{"a": 1, "b": 2}"#).unwrap();
+    let new: Value = serde_json::from_str(r#"{"b": 2, "a": 1}"#).unwrap();
+    assert!(json_diff(&old, &new).is_empty());
+}
+
+#[test]
+fn test_json_diff_reports_paths() {
+    let old = json!({"name": "snorlax", "stats": [1, 2, 3], "gone": true, "odd key": 1});
+    let new = json!({"name": "mew", "stats": [1, 2], "added": null, "odd key": 1});
+    let rows = json_diff(&old, &new);
+
+    assert!(rows.contains(&DiffRow::new(
+        DiffKind::Changed,
+        Some("$.name: \"snorlax\""),
+        Some("$.name: \"mew\"")
+    )));
+    assert!(rows.contains(&DiffRow::new(
+        DiffKind::Removed,
+        Some("$.stats[2]: 3"),
+        None
+    )));
+    assert!(rows.contains(&DiffRow::new(DiffKind::Removed, Some("$.gone: true"), None)));
+    assert!(rows.contains(&DiffRow::new(DiffKind::Added, None, Some("$.added: null"))));
+    assert_eq!(summary(&rows), "1 changed, 1 added, 2 removed");
+}
+
+#[test]
+fn test_json_diff_type_change_is_one_row() {
+    let rows = json_diff(&json!({"a": {"b": 1}}), &json!({"a": [1]}));
+    assert_eq!(
+        rows,
+        vec![DiffRow::new(
+            DiffKind::Changed,
+            Some("$.a: {\"b\":1}"),
+            Some("$.a: [1]")
+        )]
+    );
+}
+
+#[test]
+fn test_diff_mode_cycles() {
+    assert_eq!(DiffMode::Json.next(), DiffMode::Text);
+    assert_eq!(DiffMode::Text.next(), DiffMode::Json);
+    assert_eq!(DiffMode::Text.label(), "text");
+}
@@ -0,0 +1,216 @@
+use std::fs::{File, OpenOptions};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use encoding_rs::{Decoder, Encoding, UTF_8, WINDOWS_1252};
+use percent_encoding::percent_decode_str;
+
+/// Bytes shown in the hex dump of a binary response
+pub const HEX_DUMP_LIMIT: usize = 4096;
+
+/// File name for a response, taken from `Content-Disposition` when present and
+/// otherwise from the last URL path segment
+pub fn filename(url: &str, content_disposition: Option<&str>) -> String {
+    content_disposition
+        .and_then(disposition_filename)
+        .or_else(|| url_filename(url))
+        .map(|name| sanitize(&name))
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "download".to_string())
+}
+
+fn disposition_filename(header: &str) -> Option<String> {
+    let params: Vec<(String, &str)> = header
+        .split(';')
+        .filter_map(|param| param.split_once('='))
+        .map(|(key, value)| (key.trim().to_ascii_lowercase(), value.trim()))
+        .collect();
+
+    // RFC 6266: filename* (charset''percent-encoded) takes precedence
+    let extended = params
+        .iter()
+        .find(|(key, _)| key == "filename*")
+        .and_then(|(_, value)| {
+            let (_, encoded) = value.split_once("''")?;
+            Some(percent_decode_str(encoded).decode_utf8_lossy().into_owned())
+        });
+    extended.or_else(|| {
+        params
+            .iter()
+            .find(|(key, _)| key == "filename")
+            .map(|(_, value)| value.trim_matches('"').to_string())
+    })
+}
+
+fn url_filename(url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(url).ok()?;
+    let segment = url.path_segments()?.rfind(|segment| !segment.is_empty())?;
+    Some(percent_decode_str(segment).decode_utf8_lossy().into_owned())
+}
+
+/// Keep only the final path component so a server cannot write outside the
+/// download directory
+fn sanitize(name: &str) -> String {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let name: String = name.chars().filter(|c| !c.is_control()).collect();
+    match name.trim() {
+        "." | ".." => String::new(),
+        name => name.to_string(),
+    }
+}
+
+/// Create a new file in `dir` for `name`, adding ` (n)` before the extension
+/// when the name is taken. The file is opened with `create_new`, so a file
+/// that appears in the meantime is never overwritten; the next name is tried.
+pub fn create_unique(dir: &Path, name: &str) -> std::io::Result<(PathBuf, File)> {
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+        _ => (name, String::new()),
+    };
+    let mut n = 0;
+    loop {
+        let path = match n {
+            0 => dir.join(name),
+            n => dir.join(format!("{} ({}){}", stem, n, extension)),
+        };
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => n += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Whether a body of unknown type should be shown as binary rather than text
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes.contains(&0) || std::str::from_utf8(bytes).is_err()
+}
+
+/// Text of a response body, or `None` when it should be shown as binary.
+///
+/// A charset in the Content-Type is used to decode the body. Without one,
+/// text types are read as UTF-8, falling back to windows-1252 as browsers
+/// do, other known types are binary, and anything else is sniffed.
+pub fn decode_text(bytes: &[u8], content_type: Option<&str>) -> Option<String> {
+    let (essence, charset) = parse_content_type(content_type);
+    if let Some(encoding) = charset {
+        return Some(encoding.decode(bytes).0.into_owned());
+    }
+    if is_text_type(&essence) {
+        let encoding = match Encoding::for_bom(bytes) {
+            Some((encoding, _)) => encoding,
+            None if std::str::from_utf8(bytes).is_ok() => UTF_8,
+            None => WINDOWS_1252,
+        };
+        return Some(encoding.decode(bytes).0.into_owned());
+    }
+    if is_binary_type(&essence) || is_binary(bytes) {
+        return None;
+    }
+    Some(String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// Decoder for a body read in chunks, which keeps a character split between
+/// chunks until the rest of it arrives. `None` for known binary types; the
+/// declared charset is used, and UTF-8 otherwise.
+pub fn text_decoder(content_type: Option<&str>) -> Option<Decoder> {
+    let (essence, charset) = parse_content_type(content_type);
+    match charset {
+        Some(encoding) => Some(encoding.new_decoder()),
+        None if is_binary_type(&essence) => None,
+        None => Some(UTF_8.new_decoder()),
+    }
+}
+
+/// Lowercase essence of a Content-Type and the encoding its charset names
+fn parse_content_type(content_type: Option<&str>) -> (String, Option<&'static Encoding>) {
+    let mut params = content_type.unwrap_or_default().split(';');
+    let essence = params
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    let encoding = params
+        .filter_map(|param| param.split_once('='))
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case("charset"))
+        .and_then(|(_, value)| Encoding::for_label(value.trim().trim_matches('"').as_bytes()));
+    (essence, encoding)
+}
+
+fn is_text_type(essence: &str) -> bool {
+    essence.starts_with("text/")
+        || essence.ends_with("+json")
+        || essence.ends_with("+xml")
+        || matches!(
+            essence,
+            "application/json"
+                | "application/xml"
+                | "application/javascript"
+                | "application/ecmascript"
+                | "application/graphql"
+                | "application/x-ndjson"
+                | "application/x-www-form-urlencoded"
+                | "application/yaml"
+                | "application/x-yaml"
+        )
+}
+
+fn is_binary_type(essence: &str) -> bool {
+    ["image/", "audio/", "video/", "font/"]
+        .iter()
+        .any(|prefix| essence.starts_with(prefix))
+        || matches!(
+            essence,
+            "application/pdf"
+                | "application/zip"
+                | "application/gzip"
+                | "application/wasm"
+                | "application/protobuf"
+                | "application/x-protobuf"
+                | "application/grpc"
+        )
+}
+
+/// Summary and hex dump shown instead of the raw bytes of a binary response
+pub fn describe_binary(bytes: &[u8], content_type: Option<&str>) -> String {
+    let mut text = format!(
+        "{} bytes of {}",
+        bytes.len(),
+        content_type.unwrap_or("binary data")
+    );
+    text.push_str("\n\n");
+    text.push_str(&hex_dump(&bytes[..bytes.len().min(HEX_DUMP_LIMIT)]));
+    if bytes.len() > HEX_DUMP_LIMIT {
+        text.push_str(&format!(
+            "\n… {} more bytes, save the response to see them all",
+            bytes.len() - HEX_DUMP_LIMIT
+        ));
+    }
+    text
+}
+
+/// `xxd`-style dump: offset, 16 hex bytes and their printable ASCII
+pub fn hex_dump(bytes: &[u8]) -> String {
+    bytes
+        .chunks(16)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let hex: Vec<String> = chunk.iter().map(|byte| format!("{:02x}", byte)).collect();
+            let ascii: String = chunk
+                .iter()
+                .map(|&byte| {
+                    if byte.is_ascii_graphic() || byte == b' ' {
+                        byte as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            format!("{:08x}  {:<47}  {}", i * 16, hex.join(" "), ascii)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,131 @@
+use super::*;
+
+#[test]
+fn test_filename_from_url() {
+    assert_eq!(
+        filename("https://x.test/files/report.pdf?v=2", None),
+        "report.pdf"
+    );
+    assert_eq!(filename("https://x.test/a/b/", None), "b");
+    assert_eq!(
+        filename("https://x.test/my%20file.txt", None),
+        "my file.txt"
+    );
+    assert_eq!(filename("https://x.test/", None), "download");
+}
+
+#[test]
+fn test_filename_from_content_disposition() {
+    assert_eq!(
+        filename(
+            "https://x.test/get",
+            Some("attachment; filename=\"data.csv\"")
+        ),
+        "data.csv"
+    );
+    assert_eq!(
+        filename(
+            "https://x.test/get",
+            Some("attachment; filename=\"fallback.txt\"; filename*=UTF-8''na%C3%AFve.txt")
+        ),
+        "naïve.txt"
+    );
+    assert_eq!(
+        filename("https://x.test/get.bin", Some("inline")),
+        "get.bin"
+    );
+}
+
+#[test]
+fn test_filename_strips_directories() {
+    assert_eq!(
+        filename(
+            "https://x.test/",
+            Some("attachment; filename=\"../../etc/passwd\"")
+        ),
+        "passwd"
+    );
+    assert_eq!(
+        filename("https://x.test/", Some("attachment; filename=\"..\"")),
+        "download"
+    );
+}
+
+#[test]
+fn test_create_unique_avoids_existing_files() {
+    let dir = std::env::temp_dir().join(format!("jorna-unique-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("image.png"), b"").unwrap();
+    std::fs::write(dir.join("image (1).png"), b"").unwrap();
+
+    let (path, _) = create_unique(&dir, "image.png").unwrap();
+    let (next, _) = create_unique(&dir, "image.png").unwrap();
+    let (fresh, _) = create_unique(&dir, "other").unwrap();
+    let untouched = std::fs::read(dir.join("image.png")).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(path, dir.join("image (2).png"));
+    assert_eq!(next, dir.join("image (3).png"));
+    assert_eq!(fresh, dir.join("other"));
+    assert!(untouched.is_empty());
+}
+
+#[test]
+fn test_is_binary() {
+    assert!(!is_binary("{\"ünïcode\": true}".as_bytes()));
+    assert!(is_binary(&[0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a]));
+    assert!(is_binary(b"text\0with nul"));
+}
+
+#[test]
+fn test_decode_text_uses_declared_charset() {
+    assert_eq!(
+        decode_text(b"caf\xe9", Some("text/html; charset=iso-8859-1")).as_deref(),
+        Some("café")
+    );
+    assert_eq!(
+        decode_text(b"\xff\xfeh\0i\0", Some("text/plain; charset=\"UTF-16\"")).as_deref(),
+        Some("hi")
+    );
+}
+
+#[test]
+fn test_decode_text_falls_back_for_text_types() {
+    assert_eq!(
+        decode_text(b"caf\xe9", Some("text/plain")).as_deref(),
+        Some("café")
+    );
+    assert_eq!(
+        decode_text("{\"ü\": 1}".as_bytes(), Some("application/problem+json")).as_deref(),
+        Some("{\"ü\": 1}")
+    );
+}
+
+#[test]
+fn test_decode_text_binary_types_and_sniffing() {
+    assert_eq!(decode_text(b"GIF89a", Some("image/gif")), None);
+    assert_eq!(decode_text(b"plain", None).as_deref(), Some("plain"));
+    assert_eq!(decode_text(b"caf\xe9", None), None);
+    assert_eq!(decode_text(b"a\0b", Some("application/octet-stream")), None);
+}
+
+#[test]
+fn test_hex_dump() {
+    let dump = hex_dump(b"0123456789abcdefXY\x00");
+
+    assert_eq!(
+        dump,
+        "00000000  30 31 32 33 34 35 36 37 38 39 61 62 63 64 65 66  0123456789abcdef\n\
+         00000010  58 59 00                                         XY."
+    );
+}
+
+#[test]
+fn test_describe_binary_truncates() {
+    let bytes = vec![0u8; HEX_DUMP_LIMIT + 10];
+
+    let text = describe_binary(&bytes, Some("image/png"));
+
+    assert!(text.starts_with(&format!("{} bytes of image/png", HEX_DUMP_LIMIT + 10)));
+    assert!(text.ends_with("… 10 more bytes, save the response to see them all"));
+}
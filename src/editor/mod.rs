@@ -0,0 +1,590 @@
+//! Multi-line text editor shared by the headers and body panes: the lines,
+//! cursor, scroll, selection and undo history of one pane.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::text;
+
+/// Undo steps kept per editor
+const MAX_HISTORY: usize = 200;
+
+/// Source of revisions, shared so no two editors' texts get the same one
+static NEXT_REVISION: AtomicU64 = AtomicU64::new(0);
+
+fn next_revision() -> u64 {
+    NEXT_REVISION.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Line index and byte offset in that line
+pub type Position = (usize, usize);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EditKind {
+    Insert,
+    Delete,
+}
+
+/// Text replaced at `start`: `removed` was there before the edit and
+/// `inserted` after it
+#[derive(Debug, Clone, PartialEq)]
+struct Change {
+    start: Position,
+    removed: String,
+    inserted: String,
+}
+
+/// One undo step: the changes in the order they were made and the cursor
+/// before and after them
+#[derive(Debug, Clone, PartialEq)]
+struct Step {
+    changes: Vec<Change>,
+    before: Position,
+    after: Position,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Editor {
+    pub lines: Vec<String>,
+    pub cursor_line: usize,
+    pub cursor_col: usize,
+    pub scroll: u16,
+    /// Where the selection started; the cursor is its other end
+    pub anchor: Option<Position>,
+    undo: Vec<Step>,
+    redo: Vec<Step>,
+    /// Kind of the last edit, so a run of typing or deleting undoes as one step
+    last_edit: Option<EditKind>,
+    /// Cursor at a checkpoint whose step starts with the next change
+    pending: Option<Position>,
+    /// Changes whenever the text is edited through the editor's methods
+    revision: u64,
+}
+
+impl Default for Editor {
+    fn default() -> Self {
+        Self::new(vec![String::new()])
+    }
+}
+
+impl Editor {
+    pub fn new(lines: Vec<String>) -> Self {
+        let lines = if lines.is_empty() {
+            vec![String::new()]
+        } else {
+            lines
+        };
+        Self {
+            lines,
+            cursor_line: 0,
+            cursor_col: 0,
+            scroll: 0,
+            anchor: None,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            last_edit: None,
+            pending: None,
+            revision: next_revision(),
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    pub fn cursor(&self) -> Position {
+        (self.cursor_line, self.cursor_col)
+    }
+
+    /// Identifies the current text, so work derived from it can be cached.
+    /// Assigning `lines` directly does not change it.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Replace the whole text as one undo step and move to the start
+    pub fn set_text(&mut self, text: &str) {
+        self.checkpoint(None);
+        self.anchor = None;
+        self.clamp();
+        let text = text.lines().collect::<Vec<_>>().join("\n");
+        self.replace((0, 0), self.end_of_text(), &text);
+        self.cursor_line = 0;
+        self.cursor_col = 0;
+        self.scroll = 0;
+        self.anchor = None;
+    }
+
+    /// Start and end of the selected text, in order
+    pub fn selection(&self) -> Option<(Position, Position)> {
+        let anchor = self.anchor?;
+        let cursor = self.cursor();
+        // The lines may have been replaced since the selection was made
+        let valid = |(line, col): Position| {
+            self.lines
+                .get(line)
+                .is_some_and(|text| text.is_char_boundary(col))
+        };
+        if !valid(anchor) || !valid(cursor) {
+            return None;
+        }
+        match anchor.cmp(&cursor) {
+            std::cmp::Ordering::Less => Some((anchor, cursor)),
+            std::cmp::Ordering::Greater => Some((cursor, anchor)),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    pub fn selected_text(&self) -> Option<String> {
+        let (start, end) = self.selection()?;
+        Some(self.text_between(start, end))
+    }
+
+    pub fn select_all(&mut self) {
+        self.anchor = Some((0, 0));
+        self.cursor_line = self.lines.len() - 1;
+        self.cursor_col = self.lines[self.cursor_line].len();
+        self.last_edit = None;
+    }
+
+    /// Selected text, or the cursor line when nothing is selected
+    pub fn copy(&self) -> String {
+        self.selected_text()
+            .unwrap_or_else(|| self.lines[self.cursor_line].clone())
+    }
+
+    /// Remove and return the selected text, or the cursor line when nothing
+    /// is selected
+    pub fn cut(&mut self) -> String {
+        let copied = self.copy();
+        if self.selection().is_some() {
+            self.checkpoint(None);
+            self.delete_selection();
+        } else {
+            self.delete_line();
+        }
+        copied
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let Some(mut step) = self.undo.pop() else {
+            return false;
+        };
+        step.after = self.cursor();
+        for change in step.changes.iter().rev() {
+            let end = end_of(change.start, &change.inserted);
+            self.splice(change.start, end, &change.removed);
+        }
+        self.restore(step.before);
+        self.redo.push(step);
+        true
+    }
+
+    pub fn redo(&mut self) -> bool {
+        let Some(step) = self.redo.pop() else {
+            return false;
+        };
+        for change in &step.changes {
+            let end = end_of(change.start, &change.removed);
+            self.splice(change.start, end, &change.inserted);
+        }
+        self.restore(step.after);
+        self.undo.push(step);
+        true
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        let kind = match self.selection() {
+            Some(_) => None,
+            None => Some(EditKind::Insert),
+        };
+        self.checkpoint(kind);
+        self.delete_selection();
+        self.clamp();
+        let end = self.replace(self.cursor(), self.cursor(), c.encode_utf8(&mut [0; 4]));
+        let line = &self.lines[end.0];
+        // A combining mark typed before existing text joins the cluster after it
+        self.cursor_line = end.0;
+        self.cursor_col = if end.1 == text::snap(line, end.1) {
+            end.1
+        } else {
+            text::next_boundary(line, end.1)
+        };
+        // Each word typed undoes on its own
+        if c.is_whitespace() {
+            self.last_edit = None;
+        }
+    }
+
+    /// Insert text that may span several lines, replacing the selection
+    pub fn insert_str(&mut self, insert: &str) {
+        self.checkpoint(None);
+        self.delete_selection();
+        self.clamp();
+        let insert = insert.replace("\r\n", "\n").replace('\r', "\n");
+        (self.cursor_line, self.cursor_col) = self.replace(self.cursor(), self.cursor(), &insert);
+        self.cursor_col = text::snap(&self.lines[self.cursor_line], self.cursor_col);
+    }
+
+    /// Insert two spaces at the cursor
+    pub fn indent(&mut self) {
+        self.insert_str("  ");
+    }
+
+    /// Split the line at the cursor
+    pub fn newline(&mut self) {
+        self.checkpoint(None);
+        self.delete_selection();
+        self.clamp();
+        (self.cursor_line, self.cursor_col) = self.replace(self.cursor(), self.cursor(), "\n");
+    }
+
+    pub fn backspace(&mut self) {
+        if self.selection().is_some() {
+            self.checkpoint(None);
+            self.delete_selection();
+            return;
+        }
+        self.anchor = None;
+        self.clamp();
+        if self.cursor() == (0, 0) {
+            return;
+        }
+        self.checkpoint(Some(EditKind::Delete));
+        let start = if self.cursor_col > 0 {
+            let line = &self.lines[self.cursor_line];
+            (
+                self.cursor_line,
+                text::previous_boundary(line, self.cursor_col),
+            )
+        } else {
+            let above = self.cursor_line - 1;
+            (above, self.lines[above].len())
+        };
+        self.replace(start, self.cursor(), "");
+        (self.cursor_line, self.cursor_col) = start;
+    }
+
+    pub fn delete(&mut self) {
+        if self.selection().is_some() {
+            self.checkpoint(None);
+            self.delete_selection();
+            return;
+        }
+        self.anchor = None;
+        self.clamp();
+        let at_line_end = self.cursor_col == self.lines[self.cursor_line].len();
+        if at_line_end && self.cursor_line + 1 == self.lines.len() {
+            return;
+        }
+        self.checkpoint(Some(EditKind::Delete));
+        let end = if at_line_end {
+            (self.cursor_line + 1, 0)
+        } else {
+            let line = &self.lines[self.cursor_line];
+            (self.cursor_line, text::next_boundary(line, self.cursor_col))
+        };
+        self.replace(self.cursor(), end, "");
+    }
+
+    /// Remove the cursor line
+    pub fn delete_line(&mut self) {
+        self.checkpoint(None);
+        self.anchor = None;
+        self.clamp();
+        let column = text::column(&self.lines[self.cursor_line], self.cursor_col);
+        let line = self.cursor_line;
+        let (start, end) = if self.lines.len() == 1 {
+            ((0, 0), (0, self.lines[0].len()))
+        } else if line + 1 < self.lines.len() {
+            ((line, 0), (line + 1, 0))
+        } else {
+            (
+                (line - 1, self.lines[line - 1].len()),
+                (line, self.lines[line].len()),
+            )
+        };
+        self.replace(start, end, "");
+        self.cursor_line = line.min(self.lines.len() - 1);
+        self.cursor_col = text::cursor_at_column(&self.lines[self.cursor_line], column);
+    }
+
+    pub fn move_left(&mut self, select: bool) {
+        if self.collapse_selection(select, true) {
+            return;
+        }
+        if self.cursor_col > 0 {
+            let line = &self.lines[self.cursor_line];
+            self.cursor_col = text::previous_boundary(line, self.cursor_col);
+        } else if self.cursor_line > 0 {
+            self.cursor_line -= 1;
+            self.cursor_col = self.lines[self.cursor_line].len();
+        }
+    }
+
+    pub fn move_right(&mut self, select: bool) {
+        if self.collapse_selection(select, false) {
+            return;
+        }
+        let line = &self.lines[self.cursor_line];
+        if self.cursor_col < line.len() {
+            self.cursor_col = text::next_boundary(line, self.cursor_col);
+        } else if self.cursor_line + 1 < self.lines.len() {
+            self.cursor_line += 1;
+            self.cursor_col = 0;
+        }
+    }
+
+    /// Move to the start of the previous word, or the end of the line above
+    pub fn word_left(&mut self, select: bool) {
+        self.start_move(select);
+        if self.cursor_col == 0 && self.cursor_line > 0 {
+            self.cursor_line -= 1;
+            self.cursor_col = self.lines[self.cursor_line].len();
+        } else {
+            self.cursor_col = text::previous_word(&self.lines[self.cursor_line], self.cursor_col);
+        }
+    }
+
+    /// Move to the end of the next word, or the start of the line below
+    pub fn word_right(&mut self, select: bool) {
+        self.start_move(select);
+        let line = &self.lines[self.cursor_line];
+        if self.cursor_col == line.len() && self.cursor_line + 1 < self.lines.len() {
+            self.cursor_line += 1;
+            self.cursor_col = 0;
+        } else {
+            self.cursor_col = text::next_word(line, self.cursor_col);
+        }
+    }
+
+    /// Move up a line, keeping the display column
+    pub fn move_up(&mut self, select: bool) {
+        self.start_move(select);
+        if self.cursor_line > 0 {
+            let column = text::column(&self.lines[self.cursor_line], self.cursor_col);
+            self.cursor_line -= 1;
+            self.cursor_col = text::cursor_at_column(&self.lines[self.cursor_line], column);
+        }
+    }
+
+    /// Move down a line, keeping the display column
+    pub fn move_down(&mut self, select: bool) {
+        self.start_move(select);
+        if self.cursor_line + 1 < self.lines.len() {
+            let column = text::column(&self.lines[self.cursor_line], self.cursor_col);
+            self.cursor_line += 1;
+            self.cursor_col = text::cursor_at_column(&self.lines[self.cursor_line], column);
+        }
+    }
+
+    pub fn home(&mut self, select: bool) {
+        self.start_move(select);
+        self.cursor_col = 0;
+    }
+
+    pub fn end(&mut self, select: bool) {
+        self.start_move(select);
+        self.cursor_col = self.lines[self.cursor_line].len();
+    }
+
+    /// Put the cursor at `position`, dropping the selection
+    pub fn move_to(&mut self, position: Position) {
+        self.start_move(false);
+        (self.cursor_line, self.cursor_col) = position;
+        self.clamp();
+    }
+
+    /// Scroll so the cursor line is one of the `visible_lines` shown
+    pub fn ensure_cursor_visible(&mut self, visible_lines: usize) {
+        if visible_lines == 0 {
+            return;
+        }
+        let scroll = self.scroll as usize;
+        if self.cursor_line < scroll {
+            self.scroll = self.cursor_line as u16;
+        } else if self.cursor_line >= scroll + visible_lines {
+            self.scroll = (self.cursor_line - visible_lines + 1) as u16;
+        }
+    }
+
+    /// Begin a cursor movement, extending the selection with `select` and
+    /// dropping it otherwise
+    fn start_move(&mut self, select: bool) {
+        self.last_edit = None;
+        self.clamp();
+        if !select {
+            self.anchor = None;
+        } else if self.anchor.is_none() {
+            self.anchor = Some(self.cursor());
+        }
+    }
+
+    /// Without `select`, left and right first move to the start or end of
+    /// the selection. Returns whether that happened.
+    fn collapse_selection(&mut self, select: bool, to_start: bool) -> bool {
+        let selection = self.selection();
+        self.start_move(select);
+        match selection {
+            Some((start, end)) if !select => {
+                (self.cursor_line, self.cursor_col) = if to_start { start } else { end };
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Remove the selected text, returning whether there was any
+    fn delete_selection(&mut self) -> bool {
+        let selection = self.selection();
+        self.anchor = None;
+        let Some((start, end)) = selection else {
+            return false;
+        };
+        self.replace(start, end, "");
+        (self.cursor_line, self.cursor_col) = start;
+        true
+    }
+
+    /// Keep the cursor on an existing line and grapheme boundary after the
+    /// lines were replaced from outside
+    fn clamp(&mut self) {
+        if self.lines.is_empty() {
+            self.lines.push(String::new());
+        }
+        self.cursor_line = self.cursor_line.min(self.lines.len() - 1);
+        self.cursor_col = text::snap(&self.lines[self.cursor_line], self.cursor_col);
+        if let Some((line, col)) = self.anchor {
+            let line = line.min(self.lines.len() - 1);
+            self.anchor = Some((line, text::snap(&self.lines[line], col)));
+        }
+    }
+
+    /// Start a new undo step with the next change; edits of the same kind
+    /// in a row share a step
+    fn checkpoint(&mut self, kind: Option<EditKind>) {
+        if kind.is_some() && kind == self.last_edit {
+            return;
+        }
+        self.last_edit = kind;
+        self.pending = Some(self.cursor());
+    }
+
+    /// Replace the text between two positions, recording the change for
+    /// undo. Returns the end of the inserted text.
+    fn replace(&mut self, start: Position, end: Position, insert: &str) -> Position {
+        let removed = self.text_between(start, end);
+        if removed.is_empty() && insert.is_empty() {
+            return start;
+        }
+        if let Some(before) = self.pending.take() {
+            self.undo.push(Step {
+                changes: Vec::new(),
+                before,
+                after: before,
+            });
+            if self.undo.len() > MAX_HISTORY {
+                self.undo.remove(0);
+            }
+            self.redo.clear();
+        }
+        let inserted_end = self.splice(start, end, insert);
+        let Some(step) = self.undo.last_mut() else {
+            return inserted_end;
+        };
+        // Runs of typing and deleting grow the last change instead of adding one
+        match step.changes.last_mut() {
+            Some(last)
+                if last.removed.is_empty()
+                    && removed.is_empty()
+                    && end_of(last.start, &last.inserted) == start =>
+            {
+                last.inserted.push_str(insert);
+            }
+            Some(last) if last.inserted.is_empty() && insert.is_empty() && last.start == start => {
+                last.removed.push_str(&removed);
+            }
+            Some(last) if last.inserted.is_empty() && insert.is_empty() && end == last.start => {
+                last.removed.insert_str(0, &removed);
+                last.start = start;
+            }
+            _ => step.changes.push(Change {
+                start,
+                removed,
+                inserted: insert.to_string(),
+            }),
+        }
+        inserted_end
+    }
+
+    /// Replace the text between two positions without recording it
+    fn splice(&mut self, start: Position, end: Position, insert: &str) -> Position {
+        let start = self.valid(start);
+        let end = self.valid(end).max(start);
+        self.revision = next_revision();
+        let tail = self.lines[end.0][end.1..].to_string();
+        self.lines.drain(start.0 + 1..=end.0);
+        self.lines[start.0].truncate(start.1);
+        let mut line = start.0;
+        let mut pieces = insert.split('\n');
+        self.lines[line].push_str(pieces.next().unwrap_or_default());
+        for piece in pieces {
+            line += 1;
+            self.lines.insert(line, piece.to_string());
+        }
+        let col = self.lines[line].len();
+        self.lines[line].push_str(&tail);
+        (line, col)
+    }
+
+    fn text_between(&self, start: Position, end: Position) -> String {
+        let start = self.valid(start);
+        let end = self.valid(end).max(start);
+        if start.0 == end.0 {
+            return self.lines[start.0][start.1..end.1].to_string();
+        }
+        let mut text = self.lines[start.0][start.1..].to_string();
+        for line in &self.lines[start.0 + 1..end.0] {
+            text.push('\n');
+            text.push_str(line);
+        }
+        text.push('\n');
+        text.push_str(&self.lines[end.0][..end.1]);
+        text
+    }
+
+    fn end_of_text(&self) -> Position {
+        let last = self.lines.len() - 1;
+        (last, self.lines[last].len())
+    }
+
+    /// `position` moved onto an existing line and character boundary, in
+    /// case the lines were replaced from outside since it was recorded
+    fn valid(&self, (line, col): Position) -> Position {
+        let line = line.min(self.lines.len() - 1);
+        let text = &self.lines[line];
+        let mut col = col.min(text.len());
+        while !text.is_char_boundary(col) {
+            col -= 1;
+        }
+        (line, col)
+    }
+
+    fn restore(&mut self, cursor: Position) {
+        (self.cursor_line, self.cursor_col) = cursor;
+        self.anchor = None;
+        self.last_edit = None;
+        self.pending = None;
+        self.clamp();
+    }
+}
+
+/// Position after `text` when it is inserted at `start`
+fn end_of(start: Position, text: &str) -> Position {
+    match text.rfind('\n') {
+        Some(index) => (start.0 + text.matches('\n').count(), text.len() - index - 1),
+        None => (start.0, start.1 + text.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests;
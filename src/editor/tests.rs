@@ -0,0 +1,190 @@
+use super::*;
+
+fn editor(text: &str) -> Editor {
+    let mut editor = Editor::default();
+    editor.set_text(text);
+    editor
+}
+
+#[test]
+fn test_typing_undoes_word_by_word() {
+    let mut editor = Editor::default();
+    for c in "hello world".chars() {
+        editor.insert_char(c);
+    }
+
+    assert!(editor.undo());
+    assert_eq!(editor.text(), "hello ");
+    assert!(editor.undo());
+    assert_eq!(editor.text(), "");
+    assert!(!editor.undo());
+
+    assert!(editor.redo());
+    assert!(editor.redo());
+    assert_eq!(editor.text(), "hello world");
+    assert_eq!(editor.cursor(), (0, 11));
+    assert!(!editor.redo());
+}
+
+#[test]
+fn test_new_edit_clears_redo() {
+    let mut editor = editor("ab");
+    editor.end(false);
+    editor.backspace();
+    editor.undo();
+    editor.insert_char('x');
+
+    assert!(!editor.redo());
+    assert_eq!(editor.text(), "abx");
+}
+
+#[test]
+fn test_backspace_and_delete_join_lines() {
+    let mut editor = editor("ab\ncd");
+    editor.move_down(false);
+    editor.backspace();
+    assert_eq!(editor.text(), "abcd");
+    assert_eq!(editor.cursor(), (0, 2));
+
+    editor.newline();
+    editor.move_up(false);
+    editor.end(false);
+    editor.delete();
+    assert_eq!(editor.text(), "abcd");
+
+    // A run of deletes is one undo step
+    editor.undo();
+    assert_eq!(editor.text(), "ab\ncd");
+}
+
+#[test]
+fn test_shift_selection_across_lines() {
+    let mut editor = editor("one\ntwo\nthree");
+    editor.move_right(false);
+    editor.move_down(true);
+    editor.move_down(true);
+
+    assert_eq!(editor.selection(), Some(((0, 1), (2, 1))));
+    assert_eq!(editor.selected_text().unwrap(), "ne\ntwo\nt");
+
+    editor.insert_char('X');
+    assert_eq!(editor.text(), "oXhree");
+    editor.undo();
+    assert_eq!(editor.text(), "one\ntwo\nthree");
+}
+
+#[test]
+fn test_arrow_without_shift_collapses_selection() {
+    let mut editor = editor("abcdef");
+    editor.move_right(false);
+    editor.end(true);
+    assert_eq!(editor.selected_text().as_deref(), Some("bcdef"));
+
+    editor.move_left(false);
+    assert_eq!(editor.cursor(), (0, 1));
+    assert_eq!(editor.selection(), None);
+}
+
+#[test]
+fn test_word_motion_crosses_lines() {
+    let mut editor = editor("Accept: */*\nX-Id: 1");
+    editor.word_right(false);
+    assert_eq!(editor.cursor(), (0, 6));
+    editor.end(false);
+    editor.word_right(false);
+    assert_eq!(editor.cursor(), (1, 0));
+    editor.word_left(false);
+    assert_eq!(editor.cursor(), (0, 11));
+    editor.word_left(true);
+    assert_eq!(editor.selected_text().as_deref(), Some("Accept: */*"));
+}
+
+#[test]
+fn test_copy_cut_and_paste() {
+    let mut editor = editor("first\nsecond");
+    // Without a selection the cursor line is copied and cut
+    assert_eq!(editor.copy(), "first");
+    assert_eq!(editor.cut(), "first");
+    assert_eq!(editor.text(), "second");
+
+    editor.end(false);
+    editor.insert_str("\r\nthird\nfourth");
+    assert_eq!(editor.text(), "second\nthird\nfourth");
+    assert_eq!(editor.cursor(), (2, 6));
+
+    editor.select_all();
+    assert_eq!(editor.cut(), "second\nthird\nfourth");
+    assert_eq!(editor.lines, vec![String::new()]);
+}
+
+#[test]
+fn test_delete_line_keeps_column() {
+    let mut editor = editor("abc\nd\nefgh");
+    editor.move_down(false);
+    editor.move_down(false);
+    editor.end(false);
+    editor.delete_line();
+    assert_eq!(editor.text(), "abc\nd");
+    assert_eq!(editor.cursor(), (1, 1));
+}
+
+#[test]
+fn test_stale_cursor_is_clamped() {
+    let mut editor = editor("a\nb\nc");
+    editor.move_down(false);
+    editor.move_down(false);
+    editor.end(true);
+    editor.lines = vec!["é".to_string()];
+    editor.cursor_col = 1;
+
+    assert_eq!(editor.selection(), None);
+    editor.insert_char('x');
+    assert_eq!(editor.text(), "xé");
+}
+
+#[test]
+fn test_move_to_clamps_and_clears_selection() {
+    let mut editor = Editor::new(vec!["héllo".to_string(), "x".to_string()]);
+    editor.select_all();
+
+    editor.move_to((0, 2));
+    assert_eq!(editor.cursor(), (0, 1));
+    assert_eq!(editor.selection(), None);
+
+    editor.move_to((5, 9));
+    assert_eq!(editor.cursor(), (1, 1));
+}
+
+#[test]
+fn test_undo_keeps_only_the_changed_text() {
+    let body = "line\n".repeat(1000);
+    let mut editor = editor(&body);
+    editor.move_down(false);
+    for c in "abc".chars() {
+        editor.insert_char(c);
+    }
+    editor.backspace();
+    editor.backspace();
+    editor.delete();
+    editor.insert_str("x\ny");
+
+    let step = editor.undo.last().unwrap();
+    assert_eq!(
+        step.changes,
+        vec![Change {
+            start: (1, 1),
+            removed: String::new(),
+            inserted: "x\ny".to_string(),
+        }]
+    );
+    let deleting = &editor.undo[editor.undo.len() - 2];
+    assert_eq!(deleting.changes.len(), 1);
+    assert_eq!(deleting.changes[0].removed, "bcl");
+
+    let edited = editor.text();
+    while editor.undo() {}
+    assert_eq!(editor.text(), "");
+    while editor.redo() {}
+    assert_eq!(editor.text(), edited);
+    assert_eq!(editor.cursor(), (2, 1));
+}
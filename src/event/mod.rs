@@ -1,9 +1,42 @@
-use crate::app::{App, AppFocus, METHODS};
+use crate::app::{App, AppFocus, EditTarget, ResponseTab};
+use crate::body::BodyType;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
 pub fn handle_key_event(app: &mut App, key: KeyEvent) {
+    // The settings overlay captures all input while open
+    if app.settings_open {
+        handle_settings_key(app, key);
+        return;
+    }
+
+    // The response filter and search bars capture all input while edited
+    if app.filter_editing {
+        handle_filter_key(app, key);
+        return;
+    }
+    if app.search_editing {
+        handle_search_key(app, key);
+        return;
+    }
+    if app.method_edit.is_some() {
+        handle_method_edit_key(app, key);
+        return;
+    }
+
     // Global keybindings
     match key.code {
+        KeyCode::F(2) => {
+            app.toggle_settings();
+            return;
+        }
+        KeyCode::F(3) => {
+            app.cycle_theme();
+            return;
+        }
+        KeyCode::F(4) => {
+            app.copy_as_curl();
+            return;
+        }
         KeyCode::Esc => {
             app.should_quit = true;
             return;
@@ -11,7 +44,8 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
         KeyCode::Tab => {
             app.focus = match app.focus {
                 AppFocus::MethodSelector => AppFocus::UrlInput,
-                AppFocus::UrlInput => AppFocus::HeadersInput,
+                AppFocus::UrlInput => AppFocus::ParamsInput,
+                AppFocus::ParamsInput => AppFocus::HeadersInput,
                 AppFocus::HeadersInput => AppFocus::BodyInput,
                 AppFocus::BodyInput => AppFocus::Response,
                 AppFocus::Response => AppFocus::MethodSelector,
@@ -22,7 +56,8 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
             app.focus = match app.focus {
                 AppFocus::MethodSelector => AppFocus::Response,
                 AppFocus::UrlInput => AppFocus::MethodSelector,
-                AppFocus::HeadersInput => AppFocus::UrlInput,
+                AppFocus::ParamsInput => AppFocus::UrlInput,
+                AppFocus::HeadersInput => AppFocus::ParamsInput,
                 AppFocus::BodyInput => AppFocus::HeadersInput,
                 AppFocus::Response => AppFocus::BodyInput,
             };
@@ -31,29 +66,26 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
         _ => {}
     }
 
+    // The JSON tree takes over navigation keys of the response pane
+    if app.focus == AppFocus::Response
+        && app.response_tab == ResponseTab::Tree
+        && handle_tree_key(app, key)
+    {
+        return;
+    }
+
     // Context-specific keybindings
     match app.focus {
         AppFocus::MethodSelector => {
             if !app.loading {
                 match key.code {
-                    KeyCode::Up => {
-                        if app.method_index > 0 {
-                            app.method_index -= 1;
-                        } else {
-                            app.method_index = METHODS.len() - 1;
-                        }
-                        app.http_method = METHODS[app.method_index].to_string();
-                    }
-                    KeyCode::Down => {
-                        if app.method_index < METHODS.len() - 1 {
-                            app.method_index += 1;
-                        } else {
-                            app.method_index = 0;
-                        }
-                        app.http_method = METHODS[app.method_index].to_string();
-                    }
+                    KeyCode::Up => app.previous_method(),
+                    KeyCode::Down => app.next_method(),
                     KeyCode::Enter => {
-                        app.send_request();
+                        app.start_request();
+                    }
+                    KeyCode::Char(c) if is_text(&key) => {
+                        app.start_method_edit(c);
                     }
                     _ => {}
                 }
@@ -63,9 +95,16 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
             if !app.loading {
                 match key.code {
                     KeyCode::Enter => {
-                        app.send_request();
+                        app.start_request();
+                    }
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.copy_url();
                     }
-                    KeyCode::Char(c) => {
+                    KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        let clipboard = app.clipboard.clone();
+                        app.paste(&clipboard);
+                    }
+                    KeyCode::Char(c) if is_text(&key) => {
                         app.handle_input_char(c);
                     }
                     KeyCode::Backspace => {
@@ -90,125 +129,362 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
                 }
             }
         }
-        AppFocus::HeadersInput => {
+        AppFocus::ParamsInput => {
             if !app.loading {
                 match key.code {
-                    KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        // Insert 2 spaces at cursor (indent)
-                        app.handle_multiline_char(' ', true);
-                        app.handle_multiline_char(' ', true);
+                    KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.params_toggle_enabled();
+                    }
+                    KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.params_delete_row();
+                        app.params.ensure_cursor_visible(3);
                     }
                     KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        app.send_request();
+                        app.start_request();
                     }
-                    KeyCode::Char(c) => {
-                        app.handle_multiline_char(c, true);
+                    KeyCode::Char(c) if is_text(&key) => {
+                        app.params_insert_char(c);
                     }
                     KeyCode::Backspace => {
-                        app.handle_multiline_backspace(true);
-                        app.ensure_headers_cursor_visible(3);
+                        app.params_backspace();
+                    }
+                    KeyCode::Delete => {
+                        app.params_delete();
                     }
                     KeyCode::Enter
                         if key.modifiers.contains(KeyModifiers::CONTROL)
                             || key.modifiers.contains(KeyModifiers::ALT) =>
                     {
-                        app.send_request();
+                        app.start_request();
                     }
                     KeyCode::Enter => {
-                        app.handle_multiline_enter(true);
-                        app.ensure_headers_cursor_visible(3);
+                        app.params.enter();
+                        app.params.ensure_cursor_visible(3);
                     }
                     KeyCode::Up => {
-                        app.handle_multiline_up(true);
-                        app.ensure_headers_cursor_visible(3);
+                        app.params.up();
+                        app.params.ensure_cursor_visible(3);
                     }
                     KeyCode::Down => {
-                        app.handle_multiline_down(true);
-                        app.ensure_headers_cursor_visible(3);
+                        app.params.down();
+                        app.params.ensure_cursor_visible(3);
                     }
                     KeyCode::Left => {
-                        app.handle_multiline_left(true);
+                        app.params.left();
                     }
                     KeyCode::Right => {
-                        app.handle_multiline_right(true);
+                        app.params.right();
                     }
                     _ => {}
                 }
             }
         }
-        AppFocus::BodyInput => {
+        AppFocus::HeadersInput => {
             if !app.loading {
                 match key.code {
-                    KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        // Insert 2 spaces at cursor (indent)
-                        app.handle_multiline_char(' ', false);
-                        app.handle_multiline_char(' ', false);
+                    KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.start_request();
                     }
+                    KeyCode::Enter
+                        if key.modifiers.contains(KeyModifiers::CONTROL)
+                            || key.modifiers.contains(KeyModifiers::ALT) =>
+                    {
+                        app.start_request();
+                    }
+                    KeyCode::Char(' ') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.header_complete();
+                    }
+                    _ => handle_editor_key(app, EditTarget::Headers, key, 3),
+                }
+            }
+        }
+        AppFocus::BodyInput => {
+            if !app.loading {
+                match key.code {
                     KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                         app.format_body_json();
                     }
+                    KeyCode::Char('l') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.jump_to_body_error();
+                        app.body.ensure_cursor_visible(6);
+                    }
+                    KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        if app.http_method == "WS" {
+                            app.cycle_ws_frame_type();
+                        } else if app.http_method.starts_with("GRPC") {
+                            app.cycle_grpc_method();
+                        } else {
+                            app.cycle_body_type();
+                        }
+                    }
                     KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        app.send_request();
+                        app.start_request();
                     }
-                    KeyCode::Char(c) => {
-                        app.handle_multiline_char(c, false);
+                    KeyCode::Char('g')
+                        if key.modifiers.contains(KeyModifiers::CONTROL)
+                            && app.body_type == BodyType::GraphQl =>
+                    {
+                        app.toggle_graphql_pane();
                     }
-                    KeyCode::Backspace => {
-                        app.handle_multiline_backspace(false);
-                        app.ensure_body_cursor_visible(6);
+                    KeyCode::Char('r')
+                        if key.modifiers.contains(KeyModifiers::CONTROL)
+                            && app.body_type == BodyType::GraphQl =>
+                    {
+                        app.fetch_graphql_schema();
                     }
-                    KeyCode::Enter if key.modifiers.contains(KeyModifiers::SHIFT) => {
-                        app.handle_multiline_enter(false);
-                        app.ensure_body_cursor_visible(6);
+                    KeyCode::Char(' ')
+                        if key.modifiers.contains(KeyModifiers::CONTROL)
+                            && app.body_type == BodyType::GraphQl =>
+                    {
+                        app.graphql_complete();
                     }
                     KeyCode::Enter
                         if key.modifiers.contains(KeyModifiers::CONTROL)
                             || key.modifiers.contains(KeyModifiers::ALT) =>
                     {
-                        app.send_request();
-                    }
-                    KeyCode::Enter => {
-                        app.handle_multiline_enter(false);
-                        app.ensure_body_cursor_visible(6);
-                    }
-                    KeyCode::Up => {
-                        app.handle_multiline_up(false);
-                        app.ensure_body_cursor_visible(6);
+                        app.start_request();
                     }
-                    KeyCode::Down => {
-                        app.handle_multiline_down(false);
-                        app.ensure_body_cursor_visible(6);
-                    }
-                    KeyCode::Left => {
-                        app.handle_multiline_left(false);
-                    }
-                    KeyCode::Right => {
-                        app.handle_multiline_right(false);
-                    }
-                    _ => {}
+                    _ if app.form_body_active() => handle_form_key(app, key),
+                    _ => handle_editor_key(app, EditTarget::Body, key, 6),
                 }
             }
         }
         AppFocus::Response => match key.code {
+            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app.save_response();
+            }
+            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app.toggle_download_mode();
+            }
+            KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app.pin_response();
+            }
+            KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app.toggle_stream_mode();
+            }
+            KeyCode::Char('x') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if app.ws.is_some() {
+                    app.ws_close();
+                } else {
+                    app.stop_stream();
+                }
+            }
+            KeyCode::Char('m') if app.response_tab == ResponseTab::Diff => {
+                app.cycle_diff_mode();
+            }
+            KeyCode::Char('u') if app.response_tab == ResponseTab::Diff => {
+                app.unpin_response();
+            }
+            KeyCode::Char('f') if app.response_tab == ResponseTab::Body => {
+                app.open_filter();
+            }
+            KeyCode::Char('r') if app.response_tab == ResponseTab::Body => {
+                app.toggle_response_raw();
+            }
+            KeyCode::Char('/') if app.response_tab == ResponseTab::Body => {
+                app.open_search();
+            }
+            KeyCode::Char('n') if app.response_tab == ResponseTab::Body => {
+                app.search_next();
+            }
+            KeyCode::Char('N') if app.response_tab == ResponseTab::Body => {
+                app.search_previous();
+            }
+            KeyCode::Char('y') if app.response_tab == ResponseTab::Body => {
+                app.copy_response();
+            }
             KeyCode::Up => {
+                app.stream_follow = false;
                 app.response_scroll = app.response_scroll.saturating_sub(1);
             }
             KeyCode::Down => {
                 app.response_scroll = app.response_scroll.saturating_add(1);
             }
             KeyCode::PageUp => {
+                app.stream_follow = false;
                 app.response_scroll = app.response_scroll.saturating_sub(10);
             }
             KeyCode::PageDown => {
                 app.response_scroll = app.response_scroll.saturating_add(10);
             }
             KeyCode::Home => {
+                app.stream_follow = false;
                 app.response_scroll = 0;
             }
+            KeyCode::End => {
+                app.stream_follow = true;
+                app.scroll_to_end();
+            }
+            KeyCode::Left => {
+                app.previous_response_tab();
+            }
+            KeyCode::Right => {
+                app.next_response_tab();
+            }
             _ => {}
         },
     }
 }
 
+/// Whether a character key types text in a single-line input. Ctrl and Alt
+/// chords are shortcuts, but AltGr arrives as Ctrl+Alt and types text.
+fn is_text(key: &KeyEvent) -> bool {
+    key.modifiers.contains(KeyModifiers::CONTROL) == key.modifiers.contains(KeyModifiers::ALT)
+}
+
+/// Navigation and copy keys of the JSON tree; returns false for keys the
+/// response pane handles as usual
+fn handle_tree_key(app: &mut App, key: KeyEvent) -> bool {
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        return false;
+    }
+    match key.code {
+        KeyCode::Char('c') => app.tree_copy_value(),
+        KeyCode::Char('p') => app.tree_copy_path(),
+        code => {
+            let Some(tree) = app.json_tree.as_mut() else {
+                return false;
+            };
+            match code {
+                KeyCode::Up => tree.select_up(1),
+                KeyCode::Down => tree.select_down(1),
+                KeyCode::PageUp => tree.select_up(10),
+                KeyCode::PageDown => tree.select_down(10),
+                KeyCode::Home => tree.select_first(),
+                KeyCode::Enter | KeyCode::Char(' ') => tree.toggle_selected(),
+                _ => return false,
+            }
+        }
+    }
+    true
+}
+
+fn handle_filter_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Enter => app.close_filter(),
+        KeyCode::Esc => app.clear_filter(),
+        KeyCode::Backspace => app.filter_backspace(),
+        KeyCode::Left => app.filter_left(),
+        KeyCode::Right => app.filter_right(),
+        KeyCode::Char(c) if is_text(&key) => app.filter_insert_char(c),
+        _ => {}
+    }
+}
+
+/// Keys for the form body table, laid out like the params table
+fn handle_form_key(app: &mut App, key: KeyEvent) {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Char('e') if ctrl => {
+            app.form.toggle_enabled();
+        }
+        KeyCode::Char('d') if ctrl => {
+            app.form.delete_row();
+        }
+        KeyCode::Char(c) if is_text(&key) => app.form.insert_char(c),
+        KeyCode::Backspace => {
+            app.form.backspace();
+        }
+        KeyCode::Delete => {
+            app.form.delete();
+        }
+        KeyCode::Enter => app.form.enter(),
+        KeyCode::Up => app.form.up(),
+        KeyCode::Down => app.form.down(),
+        KeyCode::Left => app.form.left(),
+        KeyCode::Right => app.form.right(),
+        _ => {}
+    }
+    app.form.ensure_cursor_visible(6);
+}
+
+/// Editing keys shared by the headers and body panes; `visible_lines` is
+/// the height of the pane
+fn handle_editor_key(app: &mut App, target: EditTarget, key: KeyEvent, visible_lines: usize) {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+    match key.code {
+        KeyCode::Char('c') if ctrl => app.editor_copy(target),
+        KeyCode::Char('x') if ctrl => app.editor_cut(target),
+        KeyCode::Char('v') if ctrl => app.editor_paste(target),
+        KeyCode::Char('e') if ctrl => app.external_edit = Some(target),
+        _ => {
+            let editor = app.editor_mut(target);
+            match key.code {
+                KeyCode::Char('z') if ctrl => {
+                    editor.undo();
+                }
+                KeyCode::Char('y') | KeyCode::Char('Z') if ctrl => {
+                    editor.redo();
+                }
+                KeyCode::Char('a') if ctrl => editor.select_all(),
+                KeyCode::Char('k') if ctrl => editor.delete_line(),
+                // Insert 2 spaces at cursor (indent)
+                KeyCode::Char('t') if ctrl => editor.indent(),
+                // Other Ctrl combinations are not text; AltGr arrives as Ctrl+Alt
+                KeyCode::Char(_) if ctrl && !key.modifiers.contains(KeyModifiers::ALT) => {}
+                KeyCode::Char(c) => editor.insert_char(c),
+                KeyCode::Backspace => editor.backspace(),
+                KeyCode::Delete => editor.delete(),
+                KeyCode::Enter => editor.newline(),
+                KeyCode::Left if ctrl => editor.word_left(shift),
+                KeyCode::Right if ctrl => editor.word_right(shift),
+                KeyCode::Left => editor.move_left(shift),
+                KeyCode::Right => editor.move_right(shift),
+                KeyCode::Up => editor.move_up(shift),
+                KeyCode::Down => editor.move_down(shift),
+                KeyCode::Home => editor.home(shift),
+                KeyCode::End => editor.end(shift),
+                _ => {}
+            }
+        }
+    }
+    app.editor_mut(target).ensure_cursor_visible(visible_lines);
+}
+
+fn handle_method_edit_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Enter => app.finish_method_edit(),
+        KeyCode::Esc => app.cancel_method_edit(),
+        KeyCode::Backspace => app.method_edit_backspace(),
+        KeyCode::Char(c) if is_text(&key) => app.method_edit_char(c),
+        _ => {}
+    }
+}
+
+fn handle_search_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Enter => app.close_search(),
+        KeyCode::Esc => app.clear_search(),
+        KeyCode::Tab => app.toggle_search_case(),
+        KeyCode::Backspace => app.search_backspace(),
+        KeyCode::Left => app.search_left(),
+        KeyCode::Right => app.search_right(),
+        KeyCode::Char(c) if is_text(&key) => app.search_insert_char(c),
+        _ => {}
+    }
+}
+
+fn handle_settings_key(app: &mut App, key: KeyEvent) {
+    if app.settings_edit.is_some() {
+        match key.code {
+            KeyCode::Esc => app.settings_cancel_edit(),
+            KeyCode::Enter => app.settings_activate(),
+            KeyCode::Backspace => app.settings_edit_backspace(),
+            KeyCode::Char(c) if is_text(&key) => app.settings_edit_char(c),
+            _ => {}
+        }
+        return;
+    }
+
+    match key.code {
+        KeyCode::Esc | KeyCode::F(2) => app.toggle_settings(),
+        KeyCode::Up => app.settings_select_up(),
+        KeyCode::Down => app.settings_select_down(),
+        KeyCode::Enter | KeyCode::Char(' ') => app.settings_activate(),
+        _ => {}
+    }
+}
+
 #[cfg(test)]
 mod tests;
@@ -1,4 +1,5 @@
 use super::*;
+use crate::app::ResponseTab;
 use crossterm::event::KeyModifiers;
 
 fn create_key_event(code: KeyCode) -> KeyEvent {
@@ -23,6 +24,9 @@ fn test_tab_cycles_focus() {
     handle_key_event(&mut app, create_key_event(KeyCode::Tab));
     assert_eq!(app.focus, AppFocus::UrlInput);
 
+    handle_key_event(&mut app, create_key_event(KeyCode::Tab));
+    assert_eq!(app.focus, AppFocus::ParamsInput);
+
     handle_key_event(&mut app, create_key_event(KeyCode::Tab));
     assert_eq!(app.focus, AppFocus::HeadersInput);
 
@@ -45,16 +49,16 @@ fn test_method_selector_up_cycles_methods() {
 
     handle_key_event(&mut app, create_key_event(KeyCode::Up));
 
-    assert_eq!(app.method_index, 6);
-    assert_eq!(app.http_method, "OPTIONS");
+    assert_eq!(app.method_index, 9);
+    assert_eq!(app.http_method, "GRPC-WEB");
 }
 
 #[test]
 fn test_method_selector_down_cycles_methods() {
     let mut app = App::new();
     app.focus = AppFocus::MethodSelector;
-    app.method_index = 6;
-    app.http_method = "OPTIONS".to_string();
+    app.method_index = 9;
+    app.http_method = "GRPC-WEB".to_string();
 
     handle_key_event(&mut app, create_key_event(KeyCode::Down));
 
@@ -75,6 +79,40 @@ fn test_method_selector_down_increments() {
     assert_eq!(app.http_method, "POST");
 }
 
+#[test]
+fn test_method_selector_typed_custom_method() {
+    let mut app = App::new();
+    app.focus = AppFocus::MethodSelector;
+
+    for c in "PROPfind".chars() {
+        handle_key_event(&mut app, create_key_event(KeyCode::Char(c)));
+    }
+    // Control chords are not typed into the method
+    handle_key_event(
+        &mut app,
+        KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL),
+    );
+    assert_eq!(app.method_edit.as_deref(), Some("PROPfind"));
+    for _ in 0..4 {
+        handle_key_event(&mut app, create_key_event(KeyCode::Backspace));
+    }
+    for c in "FIND".chars() {
+        handle_key_event(&mut app, create_key_event(KeyCode::Char(c)));
+    }
+    handle_key_event(&mut app, create_key_event(KeyCode::Enter));
+
+    assert_eq!(app.method_edit, None);
+    assert_eq!(app.http_method, "PROPFIND");
+    assert!(!app.should_quit);
+
+    // Esc cancels the edit instead of quitting
+    handle_key_event(&mut app, create_key_event(KeyCode::Char('x')));
+    handle_key_event(&mut app, create_key_event(KeyCode::Esc));
+    assert_eq!(app.method_edit, None);
+    assert_eq!(app.http_method, "PROPFIND");
+    assert!(!app.should_quit);
+}
+
 #[test]
 fn test_url_input_char() {
     let mut app = App::new();
@@ -193,46 +231,46 @@ fn test_loading_blocks_method_change() {
 fn test_headers_input_char() {
     let mut app = App::new();
     app.focus = AppFocus::HeadersInput;
-    app.headers_input = vec!["".to_string()];
-    app.headers_cursor_line = 0;
-    app.headers_cursor_col = 0;
+    app.headers.lines = vec!["".to_string()];
+    app.headers.cursor_line = 0;
+    app.headers.cursor_col = 0;
 
     handle_key_event(&mut app, create_key_event(KeyCode::Char('C')));
     handle_key_event(&mut app, create_key_event(KeyCode::Char('o')));
 
-    assert_eq!(app.headers_input[0], "Co");
-    assert_eq!(app.headers_cursor_col, 2);
+    assert_eq!(app.headers.lines[0], "Co");
+    assert_eq!(app.headers.cursor_col, 2);
 }
 
 #[test]
 fn test_headers_input_backspace() {
     let mut app = App::new();
     app.focus = AppFocus::HeadersInput;
-    app.headers_input = vec!["Content".to_string()];
-    app.headers_cursor_line = 0;
-    app.headers_cursor_col = 7;
+    app.headers.lines = vec!["Content".to_string()];
+    app.headers.cursor_line = 0;
+    app.headers.cursor_col = 7;
 
     handle_key_event(&mut app, create_key_event(KeyCode::Backspace));
 
-    assert_eq!(app.headers_input[0], "Conten");
-    assert_eq!(app.headers_cursor_col, 6);
+    assert_eq!(app.headers.lines[0], "Conten");
+    assert_eq!(app.headers.cursor_col, 6);
 }
 
 #[test]
 fn test_headers_input_enter_new_line() {
     let mut app = App::new();
     app.focus = AppFocus::HeadersInput;
-    app.headers_input = vec!["First".to_string()];
-    app.headers_cursor_line = 0;
-    app.headers_cursor_col = 5;
+    app.headers.lines = vec!["First".to_string()];
+    app.headers.cursor_line = 0;
+    app.headers.cursor_col = 5;
 
     handle_key_event(&mut app, create_key_event(KeyCode::Enter));
 
     // Regular Enter should create new line in headers
-    assert_eq!(app.headers_input.len(), 2);
-    assert_eq!(app.headers_input[0], "First");
-    assert_eq!(app.headers_input[1], "");
-    assert_eq!(app.headers_cursor_line, 1);
+    assert_eq!(app.headers.lines.len(), 2);
+    assert_eq!(app.headers.lines[0], "First");
+    assert_eq!(app.headers.lines[1], "");
+    assert_eq!(app.headers.cursor_line, 1);
 }
 
 #[test]
@@ -240,7 +278,7 @@ fn test_headers_input_ctrl_s_sends_request() {
     let mut app = App::new();
     app.focus = AppFocus::HeadersInput;
     app.url_input = "https://httpbin.org/get".to_string();
-    app.headers_input = vec!["Content-Type: application/json".to_string()];
+    app.headers.lines = vec!["Content-Type: application/json".to_string()];
 
     handle_key_event(
         &mut app,
@@ -256,7 +294,7 @@ fn test_headers_input_ctrl_enter_sends_request() {
     let mut app = App::new();
     app.focus = AppFocus::HeadersInput;
     app.url_input = "https://httpbin.org/get".to_string();
-    app.headers_input = vec!["Content-Type: application/json".to_string()];
+    app.headers.lines = vec!["Content-Type: application/json".to_string()];
 
     handle_key_event(
         &mut app,
@@ -271,52 +309,52 @@ fn test_headers_input_ctrl_enter_sends_request() {
 fn test_headers_input_ctrl_t_inserts_two_spaces() {
     let mut app = App::new();
     app.focus = AppFocus::HeadersInput;
-    app.headers_input = vec!["test".to_string()];
-    app.headers_cursor_line = 0;
-    app.headers_cursor_col = 0;
+    app.headers.lines = vec!["test".to_string()];
+    app.headers.cursor_line = 0;
+    app.headers.cursor_col = 0;
 
     handle_key_event(
         &mut app,
         KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL),
     );
 
-    assert_eq!(app.headers_input[0], "  test");
-    assert_eq!(app.headers_cursor_col, 2);
+    assert_eq!(app.headers.lines[0], "  test");
+    assert_eq!(app.headers.cursor_col, 2);
 }
 
 #[test]
 fn test_headers_input_arrow_keys() {
     let mut app = App::new();
     app.focus = AppFocus::HeadersInput;
-    app.headers_input = vec!["First".to_string(), "Second".to_string()];
-    app.headers_cursor_line = 0;
-    app.headers_cursor_col = 5;
+    app.headers.lines = vec!["First".to_string(), "Second".to_string()];
+    app.headers.cursor_line = 0;
+    app.headers.cursor_col = 5;
 
     handle_key_event(&mut app, create_key_event(KeyCode::Down));
-    assert_eq!(app.headers_cursor_line, 1);
+    assert_eq!(app.headers.cursor_line, 1);
 
     handle_key_event(&mut app, create_key_event(KeyCode::Up));
-    assert_eq!(app.headers_cursor_line, 0);
+    assert_eq!(app.headers.cursor_line, 0);
 
     handle_key_event(&mut app, create_key_event(KeyCode::Left));
-    assert_eq!(app.headers_cursor_col, 4);
+    assert_eq!(app.headers.cursor_col, 4);
 
     handle_key_event(&mut app, create_key_event(KeyCode::Right));
-    assert_eq!(app.headers_cursor_col, 5);
+    assert_eq!(app.headers.cursor_col, 5);
 }
 
 #[test]
 fn test_loading_blocks_headers_input() {
     let mut app = App::new();
     app.focus = AppFocus::HeadersInput;
-    app.headers_input = vec!["Content".to_string()];
-    app.headers_cursor_col = 7;
+    app.headers.lines = vec!["Content".to_string()];
+    app.headers.cursor_col = 7;
     app.loading = true;
 
     handle_key_event(&mut app, create_key_event(KeyCode::Char('X')));
 
     // Should not change because loading is true
-    assert_eq!(app.headers_input[0], "Content");
+    assert_eq!(app.headers.lines[0], "Content");
 }
 
 // Body input tests
@@ -324,45 +362,45 @@ fn test_loading_blocks_headers_input() {
 fn test_body_input_char() {
     let mut app = App::new();
     app.focus = AppFocus::BodyInput;
-    app.body_input = vec!["".to_string()];
-    app.body_cursor_line = 0;
-    app.body_cursor_col = 0;
+    app.body.lines = vec!["".to_string()];
+    app.body.cursor_line = 0;
+    app.body.cursor_col = 0;
 
     handle_key_event(&mut app, create_key_event(KeyCode::Char('{')));
     handle_key_event(&mut app, create_key_event(KeyCode::Char('}')));
 
-    assert_eq!(app.body_input[0], "{}");
-    assert_eq!(app.body_cursor_col, 2);
+    assert_eq!(app.body.lines[0], "{}");
+    assert_eq!(app.body.cursor_col, 2);
 }
 
 #[test]
 fn test_body_input_backspace() {
     let mut app = App::new();
     app.focus = AppFocus::BodyInput;
-    app.body_input = vec!["{ }".to_string()];
-    app.body_cursor_line = 0;
-    app.body_cursor_col = 2;
+    app.body.lines = vec!["{ }".to_string()];
+    app.body.cursor_line = 0;
+    app.body.cursor_col = 2;
 
     handle_key_event(&mut app, create_key_event(KeyCode::Backspace));
 
-    assert_eq!(app.body_input[0], "{}");
-    assert_eq!(app.body_cursor_col, 1);
+    assert_eq!(app.body.lines[0], "{}");
+    assert_eq!(app.body.cursor_col, 1);
 }
 
 #[test]
 fn test_body_input_enter_new_line() {
     let mut app = App::new();
     app.focus = AppFocus::BodyInput;
-    app.body_input = vec!["{".to_string()];
-    app.body_cursor_line = 0;
-    app.body_cursor_col = 1;
+    app.body.lines = vec!["{".to_string()];
+    app.body.cursor_line = 0;
+    app.body.cursor_col = 1;
 
     handle_key_event(&mut app, create_key_event(KeyCode::Enter));
 
     // Regular Enter should create new line in body
-    assert_eq!(app.body_input.len(), 2);
-    assert_eq!(app.body_input[0], "{");
-    assert_eq!(app.body_input[1], "");
+    assert_eq!(app.body.lines.len(), 2);
+    assert_eq!(app.body.lines[0], "{");
+    assert_eq!(app.body.lines[1], "");
 }
 
 #[test]
@@ -371,7 +409,7 @@ fn test_body_input_ctrl_enter_sends_request() {
     app.focus = AppFocus::BodyInput;
     app.url_input = "https://httpbin.org/post".to_string();
     app.http_method = "POST".to_string();
-    app.body_input = vec!["{}".to_string()];
+    app.body.lines = vec!["{}".to_string()];
 
     handle_key_event(
         &mut app,
@@ -388,7 +426,7 @@ fn test_body_input_ctrl_s_sends_request() {
     app.focus = AppFocus::BodyInput;
     app.url_input = "https://httpbin.org/post".to_string();
     app.http_method = "POST".to_string();
-    app.body_input = vec!["{}".to_string()];
+    app.body.lines = vec!["{}".to_string()];
 
     handle_key_event(
         &mut app,
@@ -403,50 +441,50 @@ fn test_body_input_ctrl_s_sends_request() {
 fn test_body_input_shift_enter_new_line() {
     let mut app = App::new();
     app.focus = AppFocus::BodyInput;
-    app.body_input = vec!["test".to_string()];
-    app.body_cursor_line = 0;
-    app.body_cursor_col = 4;
+    app.body.lines = vec!["test".to_string()];
+    app.body.cursor_line = 0;
+    app.body.cursor_col = 4;
 
     handle_key_event(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::SHIFT));
 
-    assert_eq!(app.body_input.len(), 2);
-    assert_eq!(app.body_input[0], "test");
-    assert_eq!(app.body_input[1], "");
+    assert_eq!(app.body.lines.len(), 2);
+    assert_eq!(app.body.lines[0], "test");
+    assert_eq!(app.body.lines[1], "");
 }
 
 #[test]
 fn test_body_input_arrow_keys() {
     let mut app = App::new();
     app.focus = AppFocus::BodyInput;
-    app.body_input = vec!["{".to_string(), "}".to_string()];
-    app.body_cursor_line = 0;
-    app.body_cursor_col = 1;
+    app.body.lines = vec!["{".to_string(), "}".to_string()];
+    app.body.cursor_line = 0;
+    app.body.cursor_col = 1;
 
     handle_key_event(&mut app, create_key_event(KeyCode::Down));
-    assert_eq!(app.body_cursor_line, 1);
+    assert_eq!(app.body.cursor_line, 1);
 
     handle_key_event(&mut app, create_key_event(KeyCode::Up));
-    assert_eq!(app.body_cursor_line, 0);
+    assert_eq!(app.body.cursor_line, 0);
 
     handle_key_event(&mut app, create_key_event(KeyCode::Left));
-    assert_eq!(app.body_cursor_col, 0);
+    assert_eq!(app.body.cursor_col, 0);
 
     handle_key_event(&mut app, create_key_event(KeyCode::Right));
-    assert_eq!(app.body_cursor_col, 1);
+    assert_eq!(app.body.cursor_col, 1);
 }
 
 #[test]
 fn test_loading_blocks_body_input() {
     let mut app = App::new();
     app.focus = AppFocus::BodyInput;
-    app.body_input = vec!["{}".to_string()];
-    app.body_cursor_col = 2;
+    app.body.lines = vec!["{}".to_string()];
+    app.body.cursor_col = 2;
     app.loading = true;
 
     handle_key_event(&mut app, create_key_event(KeyCode::Char('x')));
 
     // Should not change because loading is true
-    assert_eq!(app.body_input[0], "{}");
+    assert_eq!(app.body.lines[0], "{}");
 }
 
 // Ctrl+T indent tests
@@ -454,34 +492,34 @@ fn test_loading_blocks_body_input() {
 fn test_body_input_ctrl_t_inserts_two_spaces() {
     let mut app = App::new();
     app.focus = AppFocus::BodyInput;
-    app.body_input = vec!["test".to_string()];
-    app.body_cursor_line = 0;
-    app.body_cursor_col = 0;
+    app.body.lines = vec!["test".to_string()];
+    app.body.cursor_line = 0;
+    app.body.cursor_col = 0;
 
     handle_key_event(
         &mut app,
         KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL),
     );
 
-    assert_eq!(app.body_input[0], "  test");
-    assert_eq!(app.body_cursor_col, 2);
+    assert_eq!(app.body.lines[0], "  test");
+    assert_eq!(app.body.cursor_col, 2);
 }
 
 #[test]
 fn test_body_input_ctrl_t_inserts_at_cursor() {
     let mut app = App::new();
     app.focus = AppFocus::BodyInput;
-    app.body_input = vec!["hello".to_string()];
-    app.body_cursor_line = 0;
-    app.body_cursor_col = 2;
+    app.body.lines = vec!["hello".to_string()];
+    app.body.cursor_line = 0;
+    app.body.cursor_col = 2;
 
     handle_key_event(
         &mut app,
         KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL),
     );
 
-    assert_eq!(app.body_input[0], "he  llo");
-    assert_eq!(app.body_cursor_col, 4);
+    assert_eq!(app.body.lines[0], "he  llo");
+    assert_eq!(app.body.cursor_col, 4);
 }
 
 // Ctrl+F format JSON tests
@@ -489,30 +527,30 @@ fn test_body_input_ctrl_t_inserts_at_cursor() {
 fn test_body_input_ctrl_f_formats_json() {
     let mut app = App::new();
     app.focus = AppFocus::BodyInput;
-    app.body_input = vec!["{\"key\":\"value\"}".to_string()];
-    app.body_cursor_line = 0;
-    app.body_cursor_col = 5;
+    app.body.lines = vec!["{\"key\":\"value\"}".to_string()];
+    app.body.cursor_line = 0;
+    app.body.cursor_col = 5;
 
     handle_key_event(
         &mut app,
         KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL),
     );
 
-    assert_eq!(app.body_input.len(), 3);
-    assert_eq!(app.body_input[0], "{");
-    assert_eq!(app.body_input[1], "  \"key\": \"value\"");
-    assert_eq!(app.body_input[2], "}");
-    assert_eq!(app.body_cursor_line, 0);
-    assert_eq!(app.body_cursor_col, 0);
+    assert_eq!(app.body.lines.len(), 3);
+    assert_eq!(app.body.lines[0], "{");
+    assert_eq!(app.body.lines[1], "  \"key\": \"value\"");
+    assert_eq!(app.body.lines[2], "}");
+    assert_eq!(app.body.cursor_line, 0);
+    assert_eq!(app.body.cursor_col, 0);
 }
 
 #[test]
 fn test_body_input_ctrl_f_invalid_json_no_change() {
     let mut app = App::new();
     app.focus = AppFocus::BodyInput;
-    app.body_input = vec!["not valid json".to_string()];
-    app.body_cursor_line = 0;
-    app.body_cursor_col = 5;
+    app.body.lines = vec!["not valid json".to_string()];
+    app.body.cursor_line = 0;
+    app.body.cursor_col = 5;
 
     handle_key_event(
         &mut app,
@@ -520,17 +558,17 @@ fn test_body_input_ctrl_f_invalid_json_no_change() {
     );
 
     // Should not change anything
-    assert_eq!(app.body_input, vec!["not valid json".to_string()]);
-    assert_eq!(app.body_cursor_line, 0);
-    assert_eq!(app.body_cursor_col, 5);
+    assert_eq!(app.body.lines, vec!["not valid json".to_string()]);
+    assert_eq!(app.body.cursor_line, 0);
+    assert_eq!(app.body.cursor_col, 5);
 }
 
 #[test]
 fn test_body_input_ctrl_i_blocked_when_loading() {
     let mut app = App::new();
     app.focus = AppFocus::BodyInput;
-    app.body_input = vec!["test".to_string()];
-    app.body_cursor_col = 0;
+    app.body.lines = vec!["test".to_string()];
+    app.body.cursor_col = 0;
     app.loading = true;
 
     handle_key_event(
@@ -539,15 +577,15 @@ fn test_body_input_ctrl_i_blocked_when_loading() {
     );
 
     // Should not change because loading is true
-    assert_eq!(app.body_input[0], "test");
-    assert_eq!(app.body_cursor_col, 0);
+    assert_eq!(app.body.lines[0], "test");
+    assert_eq!(app.body.cursor_col, 0);
 }
 
 #[test]
 fn test_body_input_ctrl_f_blocked_when_loading() {
     let mut app = App::new();
     app.focus = AppFocus::BodyInput;
-    app.body_input = vec!["{\"key\":\"value\"}".to_string()];
+    app.body.lines = vec!["{\"key\":\"value\"}".to_string()];
     app.loading = true;
 
     handle_key_event(
@@ -556,5 +594,330 @@ fn test_body_input_ctrl_f_blocked_when_loading() {
     );
 
     // Should not format because loading is true
-    assert_eq!(app.body_input, vec!["{\"key\":\"value\"}".to_string()]);
+    assert_eq!(app.body.lines, vec!["{\"key\":\"value\"}".to_string()]);
+}
+
+#[test]
+fn test_f2_opens_and_closes_settings() {
+    let mut app = App::new();
+
+    handle_key_event(&mut app, create_key_event(KeyCode::F(2)));
+    assert!(app.settings_open);
+
+    handle_key_event(&mut app, create_key_event(KeyCode::F(2)));
+    assert!(!app.settings_open);
+}
+
+#[test]
+fn test_esc_closes_settings_instead_of_quitting() {
+    let mut app = App::new();
+    app.settings_open = true;
+
+    handle_key_event(&mut app, create_key_event(KeyCode::Esc));
+
+    assert!(!app.settings_open);
+    assert!(!app.should_quit);
+}
+
+#[test]
+fn test_settings_captures_navigation_keys() {
+    let mut app = App::new();
+    app.settings_open = true;
+    app.focus = AppFocus::UrlInput;
+
+    handle_key_event(&mut app, create_key_event(KeyCode::Down));
+    handle_key_event(&mut app, create_key_event(KeyCode::Tab));
+    handle_key_event(&mut app, create_key_event(KeyCode::Char('x')));
+
+    assert_eq!(app.settings_index, 1);
+    assert_eq!(app.focus, AppFocus::UrlInput);
+    assert_eq!(app.url_input, "https://pokeapi.co/api/v2/pokemon/snorlax");
+}
+
+#[test]
+fn test_settings_edit_via_keys() {
+    let mut app = App::new();
+    app.settings_open = true;
+
+    handle_key_event(&mut app, create_key_event(KeyCode::Enter));
+    handle_key_event(&mut app, create_key_event(KeyCode::Backspace));
+    handle_key_event(&mut app, create_key_event(KeyCode::Backspace));
+    handle_key_event(&mut app, create_key_event(KeyCode::Char('7')));
+    handle_key_event(&mut app, create_key_event(KeyCode::Enter));
+
+    assert_eq!(app.http.settings().timeout_secs, 7);
+    assert!(app.settings_open);
+}
+
+#[test]
+fn test_settings_esc_cancels_edit_first() {
+    let mut app = App::new();
+    app.settings_open = true;
+    app.settings_edit = Some("99".to_string());
+
+    handle_key_event(&mut app, create_key_event(KeyCode::Esc));
+
+    assert!(app.settings_edit.is_none());
+    assert!(app.settings_open);
+    assert_eq!(app.http.settings().timeout_secs, 30);
+}
+
+#[test]
+fn test_response_left_right_switch_tabs() {
+    let mut app = App::new();
+    app.focus = AppFocus::Response;
+
+    handle_key_event(&mut app, create_key_event(KeyCode::Right));
+    assert_eq!(app.response_tab, ResponseTab::Tree);
+
+    handle_key_event(&mut app, create_key_event(KeyCode::Left));
+    assert_eq!(app.response_tab, ResponseTab::Body);
+}
+
+#[test]
+fn test_tree_keys_navigate_and_toggle() {
+    let mut app = App::new();
+    app.focus = AppFocus::Response;
+    app.response_tab = ResponseTab::Tree;
+    app.json_tree = Some(crate::tree::JsonTree::new(
+        serde_json::json!({"a": {"b": 1}, "c": 2}),
+    ));
+
+    handle_key_event(&mut app, create_key_event(KeyCode::Down));
+    handle_key_event(&mut app, create_key_event(KeyCode::Enter));
+    let tree = app.json_tree.as_ref().unwrap();
+    assert_eq!(tree.selected, 1);
+    assert_eq!(tree.rows().len(), 3);
+    assert_eq!(app.response_scroll, 0);
+
+    handle_key_event(&mut app, create_key_event(KeyCode::Char('p')));
+    assert_eq!(app.clipboard_request.as_deref(), Some("$.a"));
+
+    handle_key_event(&mut app, create_key_event(KeyCode::Right));
+    assert_eq!(app.response_tab, ResponseTab::Timing);
+}
+
+#[test]
+fn test_filter_bar_captures_keys() {
+    let mut app = App::new();
+    app.focus = AppFocus::Response;
+
+    handle_key_event(&mut app, create_key_event(KeyCode::Char('f')));
+    assert!(app.filter_editing);
+
+    handle_key_event(&mut app, create_key_event(KeyCode::Char('.')));
+    handle_key_event(&mut app, create_key_event(KeyCode::Tab));
+    assert_eq!(app.filter_input, ".");
+    assert_eq!(app.focus, AppFocus::Response);
+
+    handle_key_event(&mut app, create_key_event(KeyCode::Esc));
+    assert!(!app.filter_editing);
+    assert!(!app.should_quit);
+    assert_eq!(app.filter_input, "");
+}
+
+#[test]
+fn test_search_keys() {
+    let mut app = App::new();
+    app.focus = AppFocus::Response;
+    app.response = "one\ntwo\none".to_string();
+
+    handle_key_event(&mut app, create_key_event(KeyCode::Char('/')));
+    handle_key_event(&mut app, create_key_event(KeyCode::Char('o')));
+    handle_key_event(&mut app, create_key_event(KeyCode::Char('n')));
+    handle_key_event(&mut app, create_key_event(KeyCode::Tab));
+    assert!(app.search_case_sensitive);
+    handle_key_event(&mut app, create_key_event(KeyCode::Enter));
+    assert!(!app.search_editing);
+    assert_eq!(app.search_matches.len(), 2);
+
+    handle_key_event(&mut app, create_key_event(KeyCode::Char('n')));
+    assert_eq!(app.search_index, 1);
+
+    handle_key_event(&mut app, create_key_event(KeyCode::Char('N')));
+    assert_eq!(app.search_index, 0);
+}
+
+#[test]
+fn test_response_ctrl_d_toggles_download_mode() {
+    let mut app = App::new();
+    app.focus = AppFocus::Response;
+
+    handle_key_event(
+        &mut app,
+        KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL),
+    );
+    assert!(app.download_mode);
+
+    handle_key_event(
+        &mut app,
+        KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL),
+    );
+    assert!(!app.download_mode);
+}
+
+#[test]
+fn test_response_ctrl_s_saves_instead_of_sending() {
+    let mut app = App::new();
+    app.focus = AppFocus::Response;
+
+    handle_key_event(
+        &mut app,
+        KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL),
+    );
+
+    assert!(!app.loading);
+    assert_eq!(app.status_message.as_deref(), Some("Nothing to save yet"));
+}
+
+#[test]
+fn test_back_tab_passes_through_params() {
+    let mut app = App::new();
+    app.focus = AppFocus::HeadersInput;
+
+    handle_key_event(&mut app, create_key_event(KeyCode::BackTab));
+    assert_eq!(app.focus, AppFocus::ParamsInput);
+
+    handle_key_event(&mut app, create_key_event(KeyCode::BackTab));
+    assert_eq!(app.focus, AppFocus::UrlInput);
+}
+
+#[test]
+fn test_params_input_keys_edit_url() {
+    let mut app = App::new();
+    app.focus = AppFocus::ParamsInput;
+    app.url_input = "http://x/".to_string();
+    app.sync_params_from_url();
+
+    handle_key_event(&mut app, create_key_event(KeyCode::Char('a')));
+    handle_key_event(&mut app, create_key_event(KeyCode::Enter));
+    handle_key_event(&mut app, create_key_event(KeyCode::Char('1')));
+
+    assert_eq!(app.url_input, "http://x/?a=1");
+
+    handle_key_event(
+        &mut app,
+        KeyEvent::new(KeyCode::Char('e'), KeyModifiers::CONTROL),
+    );
+    assert_eq!(app.url_input, "http://x/");
+
+    handle_key_event(
+        &mut app,
+        KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL),
+    );
+    assert!(app.params.rows.is_empty());
+}
+
+#[test]
+fn test_loading_blocks_params_input() {
+    let mut app = App::new();
+    app.focus = AppFocus::ParamsInput;
+    app.loading = true;
+
+    handle_key_event(&mut app, create_key_event(KeyCode::Char('a')));
+
+    assert!(app.params.rows.is_empty());
+}
+
+#[test]
+fn test_body_input_ctrl_b_cycles_body_type() {
+    let mut app = App::new();
+    app.focus = AppFocus::BodyInput;
+
+    handle_key_event(
+        &mut app,
+        KeyEvent::new(KeyCode::Char('b'), KeyModifiers::CONTROL),
+    );
+
+    assert_eq!(app.body_type, crate::body::BodyType::Text);
+    assert_eq!(app.body.lines, vec![String::new()]);
+}
+
+#[test]
+fn test_body_editor_undo_and_selection_keys() {
+    let mut app = App::new();
+    app.focus = AppFocus::BodyInput;
+    let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+
+    for c in "abc".chars() {
+        handle_key_event(&mut app, create_key_event(KeyCode::Char(c)));
+    }
+    handle_key_event(&mut app, KeyEvent::new(KeyCode::Left, KeyModifiers::SHIFT));
+    handle_key_event(&mut app, KeyEvent::new(KeyCode::Left, KeyModifiers::SHIFT));
+    handle_key_event(&mut app, ctrl('x'));
+    assert_eq!(app.body.lines, vec!["a".to_string()]);
+    assert_eq!(app.clipboard, "bc");
+
+    handle_key_event(&mut app, create_key_event(KeyCode::Home));
+    handle_key_event(&mut app, ctrl('v'));
+    assert_eq!(app.body.lines, vec!["bca".to_string()]);
+
+    handle_key_event(&mut app, ctrl('z'));
+    handle_key_event(&mut app, ctrl('z'));
+    assert_eq!(app.body.lines, vec!["abc".to_string()]);
+    handle_key_event(&mut app, ctrl('y'));
+    assert_eq!(app.body.lines, vec!["a".to_string()]);
+
+    // Unbound Ctrl combinations do not type their letter
+    handle_key_event(&mut app, ctrl('q'));
+    assert_eq!(app.body.lines, vec!["a".to_string()]);
+}
+
+#[test]
+fn test_ctrl_e_requests_external_editor() {
+    let mut app = App::new();
+    app.focus = AppFocus::HeadersInput;
+    handle_key_event(
+        &mut app,
+        KeyEvent::new(KeyCode::Char('e'), KeyModifiers::CONTROL),
+    );
+    assert_eq!(app.external_edit, Some(EditTarget::Headers));
+
+    app.focus = AppFocus::BodyInput;
+    handle_key_event(
+        &mut app,
+        KeyEvent::new(KeyCode::Char('e'), KeyModifiers::CONTROL),
+    );
+    assert_eq!(app.external_edit, Some(EditTarget::Body));
+}
+
+#[test]
+fn test_single_line_inputs_ignore_control_and_alt_chords() {
+    let chords = [
+        KeyEvent::new(KeyCode::Char('k'), KeyModifiers::CONTROL),
+        KeyEvent::new(KeyCode::Char('x'), KeyModifiers::ALT),
+        // AltGr arrives as Ctrl+Alt and types text
+        KeyEvent::new(
+            KeyCode::Char('@'),
+            KeyModifiers::CONTROL | KeyModifiers::ALT,
+        ),
+    ];
+    let mut app = App::new();
+    app.focus = AppFocus::UrlInput;
+    app.url_input.clear();
+    app.cursor_position = 0;
+    for key in chords {
+        handle_key_event(&mut app, key);
+    }
+    assert_eq!(app.url_input, "@");
+
+    app.filter_editing = true;
+    for key in chords {
+        handle_key_event(&mut app, key);
+    }
+    assert_eq!(app.filter_input, "@");
+    app.filter_editing = false;
+
+    app.search_editing = true;
+    for key in chords {
+        handle_key_event(&mut app, key);
+    }
+    assert_eq!(app.search_input, "@");
+    app.search_editing = false;
+
+    app.focus = AppFocus::ParamsInput;
+    for key in chords {
+        handle_key_event(&mut app, key);
+    }
+    assert_eq!(app.params.rows[0].key, "@");
 }
@@ -0,0 +1,135 @@
+//! Editing the headers or body in the user's own editor.
+
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Names tried before giving up on creating the temporary file
+const TEMP_ATTEMPTS: usize = 16;
+
+/// Editor used when neither `$VISUAL` nor `$EDITOR` is set
+const DEFAULT_EDITOR: &str = "vi";
+
+/// Editor command from `$VISUAL`, then `$EDITOR`
+pub fn editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|command| !command.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_string())
+}
+
+/// File extension for a Content-Type, so the editor picks the right syntax
+pub fn extension(content_type: &str) -> &'static str {
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    match mime.as_str() {
+        "application/json" | "application/ld+json" => "json",
+        "application/graphql" => "graphql",
+        "text/html" => "html",
+        "application/xml" | "text/xml" => "xml",
+        "application/x-yaml" | "application/yaml" | "text/yaml" => "yaml",
+        "text/csv" => "csv",
+        "text/markdown" => "md",
+        _ if mime.ends_with("+json") => "json",
+        _ if mime.ends_with("+xml") => "xml",
+        _ => "txt",
+    }
+}
+
+/// Open `text` in the editor and return what was saved, without the final
+/// newline most editors add
+pub fn edit(text: &str, extension: &str) -> Result<String, String> {
+    edit_with(&editor_command(), text, extension)
+}
+
+/// Like `edit` with an explicit command. Arguments in the command, such as
+/// `code --wait`, are split on whitespace.
+pub fn edit_with(command: &str, text: &str, extension: &str) -> Result<String, String> {
+    let (path, mut file) = create_temp(extension)?;
+    let written = file
+        .write_all(text.as_bytes())
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e));
+    drop(file);
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&path);
+        return Err(e);
+    }
+    let result = run(command, &path).and_then(|()| {
+        std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+    });
+    let _ = std::fs::remove_file(&path);
+
+    let mut edited = result?;
+    if edited.ends_with('\n') && !text.ends_with('\n') {
+        edited.pop();
+        if edited.ends_with('\r') {
+            edited.pop();
+        }
+    }
+    Ok(edited)
+}
+
+fn run(command: &str, path: &Path) -> Result<(), String> {
+    let mut words = command.split_whitespace();
+    let program = words.next().ok_or("No editor command set")?;
+    let status = Command::new(program)
+        .args(words)
+        .arg(path)
+        .status()
+        .map_err(|e| format!("Failed to start {}: {}", program, e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} exited with {}, nothing changed",
+            program, status
+        ))
+    }
+}
+
+/// Create a new file in the temp directory that only the current user can
+/// read. An existing file, such as one planted by another user of a shared
+/// `/tmp`, is never opened; another name is tried instead.
+fn create_temp(extension: &str) -> Result<(PathBuf, File), String> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let mut last_error = None;
+    for _ in 0..TEMP_ATTEMPTS {
+        let n = COUNTER.fetch_add(1, Ordering::SeqCst);
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.subsec_nanos())
+            .unwrap_or_default();
+        let path = std::env::temp_dir().join(format!(
+            "jorna-{}-{}-{:08x}.{}",
+            std::process::id(),
+            n,
+            nanos,
+            extension
+        ));
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        match options.open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => last_error = Some(e),
+            Err(e) => return Err(format!("Failed to create {}: {}", path.display(), e)),
+        }
+    }
+    Err(format!(
+        "Failed to create a temporary file: {}",
+        last_error.map(|e| e.to_string()).unwrap_or_default()
+    ))
+}
+
+#[cfg(test)]
+mod tests;
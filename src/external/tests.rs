@@ -0,0 +1,48 @@
+use super::*;
+
+#[test]
+fn test_extension_from_content_type() {
+    assert_eq!(extension("application/json; charset=utf-8"), "json");
+    assert_eq!(extension("application/problem+json"), "json");
+    assert_eq!(extension("Application/XML"), "xml");
+    assert_eq!(extension("application/atom+xml"), "xml");
+    assert_eq!(extension("application/graphql"), "graphql");
+    assert_eq!(extension("application/x-www-form-urlencoded"), "txt");
+    assert_eq!(extension(""), "txt");
+}
+
+#[test]
+fn test_edit_with_reads_back_the_file() {
+    let edited = edit_with("sed -i s/old/new/", "{\"a\": \"old\"}", "json").unwrap();
+    // sed adds a final newline, which is dropped again
+    assert_eq!(edited, "{\"a\": \"new\"}");
+
+    let kept = edit_with("true", "line\n", "txt").unwrap();
+    assert_eq!(kept, "line\n");
+}
+
+#[test]
+fn test_edit_with_failing_editor() {
+    let error = edit_with("false", "text", "txt").unwrap_err();
+    assert!(error.starts_with("false exited with"));
+    assert!(error.ends_with("nothing changed"));
+
+    let error = edit_with("/nonexistent/editor", "text", "txt").unwrap_err();
+    assert!(error.starts_with("Failed to start /nonexistent/editor"));
+}
+
+#[test]
+fn test_temp_file_is_new_and_private() {
+    let (first, _) = create_temp("json").unwrap();
+    let (second, _) = create_temp("json").unwrap();
+    assert_ne!(first, second);
+    assert_eq!(first.extension().unwrap(), "json");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&first).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+    let _ = std::fs::remove_file(first);
+    let _ = std::fs::remove_file(second);
+}
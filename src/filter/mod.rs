@@ -0,0 +1,413 @@
+use std::borrow::Cow;
+
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(i64),
+    Slice(Option<i64>, Option<i64>),
+    Wildcard,
+    /// Every descendant named `key`, or every descendant for `None`
+    Descendants(Option<String>),
+    Filter(Condition),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Condition {
+    path: Vec<Segment>,
+    /// Comparison operator and literal; `None` only checks that the path exists
+    comparison: Option<(String, Value)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Stage {
+    Path(Vec<Segment>),
+    Keys,
+    Length,
+}
+
+/// Evaluate a jq-style or JSONPath expression against `value`, returning
+/// every result.
+///
+/// Expressions starting with `$` are JSONPath, anything else is jq-style.
+/// Both support `.key`, `["key"]`, `[0]`, `[-1]`, `[1:3]`, `[*]` / `.[]`,
+/// `..key` and `[?(@.key == value)]`; jq also supports pipes with the `keys`
+/// and `length` builtins.
+///
+/// Results borrow from `value` where they are part of it, so filtering a
+/// large response does not copy it.
+pub fn evaluate<'a>(value: &'a Value, expression: &str) -> Result<Vec<Cow<'a, Value>>, String> {
+    let expression = expression.trim();
+    if let Some(path) = expression.strip_prefix('$') {
+        let segments = parse_path(path)?;
+        return select(vec![Cow::Borrowed(value)], &segments, false);
+    }
+
+    let mut values = vec![Cow::Borrowed(value)];
+    for stage in split_pipes(expression) {
+        let stage = parse_stage(stage.trim())?;
+        values = match stage {
+            Stage::Path(segments) => select(values, &segments, true)?,
+            Stage::Keys => values
+                .iter()
+                .map(|v| keys(v).map(Cow::Owned))
+                .collect::<Result<_, _>>()?,
+            Stage::Length => values
+                .iter()
+                .map(|v| length(v).map(Cow::Owned))
+                .collect::<Result<_, _>>()?,
+        };
+    }
+    Ok(values)
+}
+
+/// Evaluate an expression and pretty-print the output for display. JSONPath
+/// matches are shown as one array, jq results one after another.
+pub fn run(value: &Value, expression: &str) -> Result<String, String> {
+    let results = evaluate(value, expression)?;
+    if expression.trim().starts_with('$') {
+        return serde_json::to_string_pretty(&results).map_err(|e| e.to_string());
+    }
+    results
+        .iter()
+        .map(|result| serde_json::to_string_pretty(result).map_err(|e| e.to_string()))
+        .collect::<Result<Vec<_>, _>>()
+        .map(|outputs| outputs.join("\n"))
+}
+
+/// Split a jq expression on top-level pipes, ignoring pipes in strings and brackets
+fn split_pipes(expression: &str) -> Vec<&str> {
+    let mut stages = Vec::new();
+    let mut depth = 0;
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in expression.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '[' | '(') => depth += 1,
+            (None, ']' | ')') => depth -= 1,
+            (None, '|') if depth == 0 => {
+                stages.push(&expression[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    stages.push(&expression[start..]);
+    stages
+}
+
+fn parse_stage(stage: &str) -> Result<Stage, String> {
+    match stage {
+        "keys" => Ok(Stage::Keys),
+        "length" => Ok(Stage::Length),
+        "" | "." => Ok(Stage::Path(Vec::new())),
+        _ if stage.starts_with('.') => parse_path(stage).map(Stage::Path),
+        _ => Err(format!("Unknown filter '{}'", stage)),
+    }
+}
+
+fn parse_path(path: &str) -> Result<Vec<Segment>, String> {
+    let mut segments = Vec::new();
+    let mut rest = path.trim();
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("..") {
+            let (name, remaining) = take_name(after);
+            segments.push(match name {
+                "" | "*" => Segment::Descendants(None),
+                name => Segment::Descendants(Some(name.to_string())),
+            });
+            rest = remaining;
+        } else if let Some(after) = rest.strip_prefix('.') {
+            let (name, remaining) = take_name(after);
+            match name {
+                "" => {}
+                "*" => segments.push(Segment::Wildcard),
+                name => segments.push(Segment::Key(name.to_string())),
+            }
+            rest = remaining;
+        } else if rest.starts_with('[') {
+            let end = closing_bracket(rest).ok_or("Missing ']'")?;
+            segments.push(parse_bracket(rest[1..end].trim())?);
+            rest = &rest[end + 1..];
+        } else if let Some(after) = rest.strip_prefix('?') {
+            // jq's optional operator; errors are already lenient
+            rest = after;
+        } else {
+            return Err(format!("Unexpected '{}'", rest));
+        }
+    }
+    Ok(segments)
+}
+
+fn take_name(text: &str) -> (&str, &str) {
+    let end = text
+        .find(|c: char| c == '.' || c == '[' || c == '?' || c.is_whitespace())
+        .unwrap_or(text.len());
+    text.split_at(end)
+}
+
+/// Byte index of the `]` matching the `[` at the start of `text`
+fn closing_bracket(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '[') => depth += 1,
+            (None, ']') => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_bracket(inner: &str) -> Result<Segment, String> {
+    if inner.is_empty() || inner == "*" {
+        return Ok(Segment::Wildcard);
+    }
+    if let Some(condition) = inner.strip_prefix('?') {
+        let condition = condition
+            .trim()
+            .strip_prefix('(')
+            .and_then(|c| c.strip_suffix(')'))
+            .ok_or("Filters look like [?(@.key == value)]")?;
+        return parse_condition(condition.trim()).map(Segment::Filter);
+    }
+    if inner.starts_with(['"', '\'']) {
+        return unquote(inner).map(Segment::Key);
+    }
+    if let Some((start, end)) = inner.split_once(':') {
+        return Ok(Segment::Slice(parse_bound(start)?, parse_bound(end)?));
+    }
+    inner
+        .parse::<i64>()
+        .map(Segment::Index)
+        .map_err(|_| format!("Invalid index '{}'", inner))
+}
+
+fn parse_bound(bound: &str) -> Result<Option<i64>, String> {
+    match bound.trim() {
+        "" => Ok(None),
+        bound => bound
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("Invalid slice bound '{}'", bound)),
+    }
+}
+
+fn unquote(text: &str) -> Result<String, String> {
+    if text.starts_with('\'') {
+        return text
+            .strip_prefix('\'')
+            .and_then(|t| t.strip_suffix('\''))
+            .map(str::to_string)
+            .ok_or_else(|| format!("Unterminated string {}", text));
+    }
+    serde_json::from_str(text).map_err(|_| format!("Invalid string {}", text))
+}
+
+const OPERATORS: &[&str] = &["==", "!=", "<=", ">=", "<", ">"];
+
+fn parse_condition(condition: &str) -> Result<Condition, String> {
+    let operator = OPERATORS
+        .iter()
+        .filter_map(|op| condition.find(op).map(|i| (i, *op)))
+        .min_by_key(|(i, op)| (*i, std::cmp::Reverse(op.len())));
+
+    let (path, comparison) = match operator {
+        Some((i, op)) => {
+            let literal = condition[i + op.len()..].trim();
+            let literal = if literal.starts_with('\'') {
+                Value::String(unquote(literal)?)
+            } else {
+                serde_json::from_str(literal)
+                    .map_err(|_| format!("Invalid value '{}' in filter", literal))?
+            };
+            (condition[..i].trim(), Some((op.to_string(), literal)))
+        }
+        None => (condition, None),
+    };
+    let path = path.strip_prefix('@').ok_or("Filter paths start with @")?;
+    Ok(Condition {
+        path: parse_path(path)?,
+        comparison,
+    })
+}
+
+fn select<'a>(
+    values: Vec<Cow<'a, Value>>,
+    segments: &[Segment],
+    strict: bool,
+) -> Result<Vec<Cow<'a, Value>>, String> {
+    let mut values = values;
+    for segment in segments {
+        let mut next = Vec::new();
+        for value in &values {
+            match value {
+                Cow::Borrowed(value) => apply_segment(value, segment, strict, &mut next)?,
+                // Values the filter built itself, such as slices, own their children
+                Cow::Owned(value) => {
+                    let mut children = Vec::new();
+                    apply_segment(value, segment, strict, &mut children)?;
+                    next.extend(children.into_iter().map(|c| Cow::Owned(c.into_owned())));
+                }
+            }
+        }
+        values = next;
+    }
+    Ok(values)
+}
+
+fn apply_segment<'a>(
+    value: &'a Value,
+    segment: &Segment,
+    strict: bool,
+    out: &mut Vec<Cow<'a, Value>>,
+) -> Result<(), String> {
+    match (segment, value) {
+        (Segment::Key(key), Value::Object(map)) => match map.get(key) {
+            Some(child) => out.push(Cow::Borrowed(child)),
+            None if strict => out.push(Cow::Owned(Value::Null)),
+            None => {}
+        },
+        (Segment::Key(_), Value::Null) if strict => out.push(Cow::Owned(Value::Null)),
+        (Segment::Key(key), other) if strict => {
+            return Err(format!(
+                "Cannot index {} with \"{}\"",
+                type_name(other),
+                key
+            ));
+        }
+        (Segment::Index(index), Value::Array(items)) => {
+            match resolve_index(*index, items.len()).and_then(|i| items.get(i)) {
+                Some(child) => out.push(Cow::Borrowed(child)),
+                None if strict => out.push(Cow::Owned(Value::Null)),
+                None => {}
+            }
+        }
+        (Segment::Index(index), other) if strict && !other.is_null() => {
+            return Err(format!("Cannot index {} with {}", type_name(other), index));
+        }
+        (Segment::Slice(start, end), Value::Array(items)) => {
+            let len = items.len();
+            let start = start.map_or(0, |s| resolve_bound(s, len));
+            let end = end.map_or(len, |e| resolve_bound(e, len));
+            out.push(Cow::Owned(Value::Array(
+                items
+                    .get(start..end.max(start))
+                    .unwrap_or_default()
+                    .to_vec(),
+            )));
+        }
+        (Segment::Wildcard, Value::Array(items)) => out.extend(items.iter().map(Cow::Borrowed)),
+        (Segment::Wildcard, Value::Object(map)) => out.extend(map.values().map(Cow::Borrowed)),
+        (Segment::Wildcard, other) if strict => {
+            return Err(format!("Cannot iterate over {}", type_name(other)));
+        }
+        (Segment::Descendants(key), _) => descendants(value, key.as_deref(), out),
+        (Segment::Filter(condition), Value::Array(items)) => {
+            for item in items {
+                if matches(item, condition)? {
+                    out.push(Cow::Borrowed(item));
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn descendants<'a>(value: &'a Value, key: Option<&str>, out: &mut Vec<Cow<'a, Value>>) {
+    let children: Vec<(Option<&String>, &Value)> = match value {
+        Value::Object(map) => map.iter().map(|(k, v)| (Some(k), v)).collect(),
+        Value::Array(items) => items.iter().map(|v| (None, v)).collect(),
+        _ => return,
+    };
+    for (name, child) in children {
+        if key.is_none() || name.map(String::as_str) == key {
+            out.push(Cow::Borrowed(child));
+        }
+        descendants(child, key, out);
+    }
+}
+
+fn matches(item: &Value, condition: &Condition) -> Result<bool, String> {
+    let found = select(vec![Cow::Borrowed(item)], &condition.path, false)?;
+    let Some(actual) = found.first().map(Cow::as_ref) else {
+        return Ok(false);
+    };
+    let Some((op, expected)) = &condition.comparison else {
+        return Ok(true);
+    };
+    let ordering = match (actual, expected) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64().partial_cmp(&b.as_f64()),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        _ => None,
+    };
+    Ok(match op.as_str() {
+        "==" => actual == expected,
+        "!=" => actual != expected,
+        "<" => ordering.is_some_and(|o| o.is_lt()),
+        "<=" => ordering.is_some_and(|o| o.is_le()),
+        ">" => ordering.is_some_and(|o| o.is_gt()),
+        ">=" => ordering.is_some_and(|o| o.is_ge()),
+        _ => false,
+    })
+}
+
+fn resolve_index(index: i64, len: usize) -> Option<usize> {
+    if index < 0 {
+        len.checked_sub(index.unsigned_abs() as usize)
+    } else {
+        Some(index as usize)
+    }
+}
+
+fn resolve_bound(bound: i64, len: usize) -> usize {
+    resolve_index(bound, len).unwrap_or(0).min(len)
+}
+
+fn keys(value: &Value) -> Result<Value, String> {
+    match value {
+        Value::Object(map) => Ok(Value::from(map.keys().cloned().collect::<Vec<_>>())),
+        Value::Array(items) => Ok(Value::from((0..items.len()).collect::<Vec<_>>())),
+        other => Err(format!("{} has no keys", type_name(other))),
+    }
+}
+
+fn length(value: &Value) -> Result<Value, String> {
+    match value {
+        Value::Object(map) => Ok(Value::from(map.len())),
+        Value::Array(items) => Ok(Value::from(items.len())),
+        Value::String(text) => Ok(Value::from(text.chars().count())),
+        Value::Null => Ok(Value::from(0)),
+        Value::Number(number) => Ok(Value::from(number.as_f64().unwrap_or_default().abs())),
+        Value::Bool(_) => Err("boolean has no length".to_string()),
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests;
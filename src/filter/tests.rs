@@ -0,0 +1,137 @@
+use serde_json::json;
+
+use super::*;
+
+fn sample() -> Value {
+    json!({
+        "name": "snorlax",
+        "abilities": [
+            {"ability": {"name": "immunity"}, "slot": 1, "hidden": false},
+            {"ability": {"name": "thick-fat"}, "slot": 2, "hidden": false},
+            {"ability": {"name": "gluttony"}, "slot": 3, "hidden": true}
+        ],
+        "stats": {"hp": 160, "speed": 30},
+        "odd key": 1
+    })
+}
+
+fn eval(expression: &str) -> Vec<Value> {
+    evaluate(&sample(), expression)
+        .unwrap()
+        .into_iter()
+        .map(Cow::into_owned)
+        .collect()
+}
+
+#[test]
+fn test_identity() {
+    assert_eq!(eval("."), vec![sample()]);
+    assert_eq!(eval(""), vec![sample()]);
+    assert_eq!(eval("$"), vec![sample()]);
+}
+
+#[test]
+fn test_jq_paths() {
+    assert_eq!(eval(".name"), vec![json!("snorlax")]);
+    assert_eq!(eval(".stats.hp"), vec![json!(160)]);
+    assert_eq!(eval(".abilities[-1].slot"), vec![json!(3)]);
+    assert_eq!(eval(".[\"odd key\"]"), vec![json!(1)]);
+    assert_eq!(
+        eval(".abilities[].ability.name"),
+        vec![json!("immunity"), json!("thick-fat"), json!("gluttony")]
+    );
+    assert_eq!(eval(".missing"), vec![Value::Null]);
+}
+
+#[test]
+fn test_jq_pipes_and_builtins() {
+    assert_eq!(eval(".abilities | length"), vec![json!(3)]);
+    assert_eq!(eval(".stats | keys"), vec![json!(["hp", "speed"])]);
+    assert_eq!(eval(".abilities[0] | .slot"), vec![json!(1)]);
+}
+
+#[test]
+fn test_jsonpath() {
+    assert_eq!(eval("$.stats.speed"), vec![json!(30)]);
+    assert_eq!(
+        eval("$..name"),
+        vec![
+            json!("immunity"),
+            json!("thick-fat"),
+            json!("gluttony"),
+            json!("snorlax")
+        ]
+    );
+    assert_eq!(
+        eval("$.abilities[*].slot"),
+        vec![json!(1), json!(2), json!(3)]
+    );
+    assert_eq!(eval("$['odd key']"), vec![json!(1)]);
+    assert!(eval("$.missing").is_empty());
+}
+
+#[test]
+fn test_slices() {
+    assert_eq!(eval(".abilities[1:] | length"), vec![json!(2)]);
+    assert_eq!(eval("$.abilities[:1][0].slot"), vec![json!(1)]);
+}
+
+#[test]
+fn test_filters() {
+    assert_eq!(
+        eval("$.abilities[?(@.hidden == true)].ability.name"),
+        vec![json!("gluttony")]
+    );
+    assert_eq!(
+        eval(".abilities[?(@.slot >= 2)] | .slot"),
+        vec![json!(2), json!(3)]
+    );
+    assert_eq!(
+        eval("$.abilities[?(@.ability.name == 'immunity')].slot"),
+        vec![json!(1)]
+    );
+    assert_eq!(eval("$.abilities[?(@.slot)]").len(), 3);
+}
+
+#[test]
+fn test_errors() {
+    let value = sample();
+
+    assert_eq!(
+        evaluate(&value, ".name.first").unwrap_err(),
+        "Cannot index string with \"first\""
+    );
+    assert_eq!(evaluate(&value, ".abilities[").unwrap_err(), "Missing ']'");
+    assert_eq!(
+        evaluate(&value, "nope").unwrap_err(),
+        "Unknown filter 'nope'"
+    );
+    assert_eq!(
+        evaluate(&value, ".name | keys").unwrap_err(),
+        "string has no keys"
+    );
+}
+
+#[test]
+fn test_run_formats_output() {
+    let value = sample();
+
+    assert_eq!(run(&value, ".stats.hp").unwrap(), "160");
+    assert_eq!(run(&value, ".abilities[].slot").unwrap(), "1\n2\n3");
+    assert_eq!(run(&value, "$.stats.hp").unwrap(), "[\n  160\n]");
+}
+
+#[test]
+fn test_results_borrow_from_the_response() {
+    let value = sample();
+
+    let results = evaluate(&value, ".abilities[] | .ability").unwrap();
+    let sliced = evaluate(&value, ".abilities[1:] | .[0].slot").unwrap();
+
+    assert!(matches!(
+        &results[0],
+        Cow::Borrowed(ability) if std::ptr::eq(*ability, &value["abilities"][0]["ability"])
+    ));
+    // Parts of values the filter built are owned
+    assert!(matches!(&sliced[..], [Cow::Owned(slot)] if *slot == json!(2)));
+}
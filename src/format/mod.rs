@@ -0,0 +1,293 @@
+use crate::params;
+use crate::sse;
+
+/// HTML elements that never have a closing tag
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// HTML elements whose content is kept as-is
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style", "pre", "textarea"];
+
+const INDENT: &str = "  ";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Json,
+    NdJson,
+    Xml,
+    Html,
+    Form,
+    EventStream,
+    Plain,
+}
+
+impl Format {
+    /// Pick a formatter from the response Content-Type
+    pub fn detect(content_type: Option<&str>) -> Format {
+        let content_type = content_type.unwrap_or_default().to_ascii_lowercase();
+        let essence = content_type.split(';').next().unwrap_or_default().trim();
+        if essence.contains("ndjson") || essence.contains("jsonl") || essence.contains("json-seq") {
+            Format::NdJson
+        } else if essence == "text/event-stream" {
+            Format::EventStream
+        } else if essence.contains("json") {
+            Format::Json
+        } else if essence.contains("html") {
+            Format::Html
+        } else if essence.contains("xml") {
+            Format::Xml
+        } else if essence == "application/x-www-form-urlencoded" {
+            Format::Form
+        } else {
+            Format::Plain
+        }
+    }
+}
+
+/// Pretty-print a response body for its Content-Type.
+///
+/// Bodies that fail to format are returned unchanged. JSON is also detected
+/// without a matching Content-Type, as many APIs send it as `text/plain`.
+pub fn pretty(content_type: Option<&str>, body: &str) -> String {
+    let formatted = match Format::detect(content_type) {
+        Format::NdJson => ndjson(body),
+        Format::Xml => Some(markup(body, false)),
+        Format::Html => Some(markup(body, true)),
+        Format::Form => Some(form_table(body)),
+        Format::EventStream => event_stream(body),
+        Format::Json | Format::Plain => json(body),
+    };
+    formatted.unwrap_or_else(|| body.to_string())
+}
+
+fn json(body: &str) -> Option<String> {
+    let value = serde_json::from_str::<serde_json::Value>(body).ok()?;
+    serde_json::to_string_pretty(&value).ok()
+}
+
+/// One pretty-printed JSON value per record
+fn ndjson(body: &str) -> Option<String> {
+    body.lines()
+        .map(|line| line.trim().trim_start_matches('\u{1e}'))
+        .filter(|line| !line.is_empty())
+        .map(json)
+        .collect::<Option<Vec<_>>>()
+        .map(|records| records.join("\n"))
+}
+
+/// Parsed events, or `None` until the first one is complete
+fn event_stream(body: &str) -> Option<String> {
+    let events = sse::parse(body);
+    (!events.is_empty()).then(|| sse::format_events(&events))
+}
+
+/// Decoded `key = value` table with the keys aligned
+fn form_table(body: &str) -> String {
+    let pairs = params::parse_query(&format!("?{}", body.trim()));
+    let width = pairs
+        .iter()
+        .map(|pair| pair.key.chars().count())
+        .max()
+        .unwrap_or(0);
+    pairs
+        .iter()
+        .map(|pair| {
+            let padding = width - pair.key.chars().count();
+            format!("{}{} = {}", pair.key, " ".repeat(padding), pair.value)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Open {
+        name: String,
+        text: &'a str,
+    },
+    Close {
+        name: String,
+        text: &'a str,
+    },
+    /// Self-closing tags, comments, doctypes, CDATA and processing instructions
+    Standalone(&'a str),
+    Text(&'a str),
+}
+
+/// Split markup into tags and text. In HTML the content of raw-text
+/// elements such as `<script>` is a single text token, even when it holds `<`.
+fn tokenize(input: &str, html: bool) -> Vec<Token<'_>> {
+    let mut tokens: Vec<Token<'_>> = Vec::new();
+    let mut rest = input;
+
+    while !rest.is_empty() {
+        if let Some(Token::Open { name, .. }) = tokens.last() {
+            let lower = name.to_ascii_lowercase();
+            if html && RAW_TEXT_ELEMENTS.contains(&lower.as_str()) {
+                let end = rest
+                    .to_ascii_lowercase()
+                    .find(&format!("</{}", lower))
+                    .unwrap_or(rest.len());
+                if end > 0 {
+                    tokens.push(Token::Text(&rest[..end]));
+                    rest = &rest[end..];
+                    continue;
+                }
+            }
+        }
+
+        if !rest.starts_with('<') {
+            let end = rest.find('<').unwrap_or(rest.len());
+            tokens.push(Token::Text(&rest[..end]));
+            rest = &rest[end..];
+            continue;
+        }
+
+        let terminator = if rest.starts_with("<!--") {
+            "-->"
+        } else if rest.starts_with("<![CDATA[") {
+            "]]>"
+        } else if rest.starts_with("<?") {
+            "?>"
+        } else {
+            ">"
+        };
+        let end = tag_end(rest, terminator);
+        let text = &rest[..end];
+        rest = &rest[end..];
+
+        let is_special = text.starts_with("<!") || text.starts_with("<?");
+        if is_special || text.ends_with("/>") {
+            tokens.push(Token::Standalone(text));
+        } else if let Some(name) = text.strip_prefix("</") {
+            tokens.push(Token::Close {
+                name: tag_name(name),
+                text,
+            });
+        } else {
+            tokens.push(Token::Open {
+                name: tag_name(&text[1..]),
+                text,
+            });
+        }
+    }
+    tokens
+}
+
+/// End of a tag starting at the beginning of `text`, skipping quoted attribute values
+fn tag_end(text: &str, terminator: &str) -> usize {
+    if terminator != ">" {
+        return text
+            .find(terminator)
+            .map(|i| i + terminator.len())
+            .unwrap_or(text.len());
+    }
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return i + 1,
+            _ => {}
+        }
+    }
+    text.len()
+}
+
+fn tag_name(text: &str) -> String {
+    text.split(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Re-indent XML or HTML with one element per line. Elements that only hold
+/// a short text stay on one line, and HTML void and raw-text elements are
+/// handled the way browsers do.
+fn markup(body: &str, html: bool) -> String {
+    let tokens = tokenize(body.trim(), html);
+    let mut lines: Vec<String> = Vec::new();
+    let mut depth = 0usize;
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let indent = INDENT.repeat(depth);
+        match &tokens[i] {
+            Token::Open { name, text } => {
+                let lower = name.to_ascii_lowercase();
+                if html && VOID_ELEMENTS.contains(&lower.as_str()) {
+                    lines.push(format!("{}{}", indent, text));
+                } else if html && RAW_TEXT_ELEMENTS.contains(&lower.as_str()) {
+                    // Keep the content untouched, up to and including the closing tag
+                    let mut element = text.to_string();
+                    let mut j = i + 1;
+                    while let Some(token) = tokens.get(j) {
+                        element.push_str(text_of(token));
+                        j += 1;
+                        if matches!(token, Token::Close { .. }) {
+                            break;
+                        }
+                    }
+                    lines.push(format!("{}{}", indent, element));
+                    i = j;
+                    continue;
+                } else if let Some((inline, skip)) = inline_element(&tokens[i..]) {
+                    lines.push(format!("{}{}", indent, inline));
+                    i += skip;
+                    continue;
+                } else {
+                    lines.push(format!("{}{}", indent, text));
+                    depth += 1;
+                }
+            }
+            Token::Close { text, .. } => {
+                depth = depth.saturating_sub(1);
+                lines.push(format!("{}{}", INDENT.repeat(depth), text));
+            }
+            Token::Standalone(text) => lines.push(format!("{}{}", indent, text)),
+            Token::Text(text) => {
+                let text = collapse_whitespace(text);
+                if !text.is_empty() {
+                    lines.push(format!("{}{}", indent, text));
+                }
+            }
+        }
+        i += 1;
+    }
+    lines.join("\n")
+}
+
+fn text_of<'a>(token: &Token<'a>) -> &'a str {
+    match token {
+        Token::Open { text, .. } | Token::Close { text, .. } => text,
+        Token::Standalone(text) | Token::Text(text) => text,
+    }
+}
+
+/// `<a>text</a>` or `<a></a>` as a single line, with the number of tokens used
+fn inline_element(tokens: &[Token<'_>]) -> Option<(String, usize)> {
+    let Token::Open { name, text: open } = &tokens[0] else {
+        return None;
+    };
+    match (tokens.get(1), tokens.get(2)) {
+        (Some(Token::Close { name: close, text }), _) if close == name => {
+            Some((format!("{}{}", open, text), 2))
+        }
+        (Some(Token::Text(content)), Some(Token::Close { name: close, text }))
+            if close == name && !content.contains('\n') =>
+        {
+            Some((format!("{}{}{}", open, content.trim(), text), 3))
+        }
+        _ => None,
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests;
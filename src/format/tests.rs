@@ -0,0 +1,117 @@
+use super::*;
+
+#[test]
+fn test_detect_by_content_type() {
+    assert_eq!(
+        Format::detect(Some("application/json; charset=utf-8")),
+        Format::Json
+    );
+    assert_eq!(Format::detect(Some("application/x-ndjson")), Format::NdJson);
+    assert_eq!(Format::detect(Some("application/jsonl")), Format::NdJson);
+    assert_eq!(Format::detect(Some("text/html")), Format::Html);
+    assert_eq!(Format::detect(Some("application/atom+xml")), Format::Xml);
+    assert_eq!(
+        Format::detect(Some("application/x-www-form-urlencoded")),
+        Format::Form
+    );
+    assert_eq!(
+        Format::detect(Some("text/event-stream")),
+        Format::EventStream
+    );
+    assert_eq!(Format::detect(Some("text/plain")), Format::Plain);
+    assert_eq!(Format::detect(None), Format::Plain);
+}
+
+#[test]
+fn test_json_is_pretty_printed_without_content_type() {
+    assert_eq!(pretty(None, r#"{"a":1}"#), "{\n  \"a\": 1\n}");
+    assert_eq!(pretty(Some("text/plain"), "not json"), "not json");
+}
+
+#[test]
+fn test_ndjson_prints_each_record() {
+    let body = "{\"a\":1}\n\n{\"b\":[2]}\n";
+    assert_eq!(
+        pretty(Some("application/x-ndjson"), body),
+        "{\n  \"a\": 1\n}\n{\n  \"b\": [\n    2\n  ]\n}"
+    );
+}
+
+#[test]
+fn test_invalid_ndjson_is_left_unchanged() {
+    let body = "{\"a\":1}\nnope";
+    assert_eq!(pretty(Some("application/x-ndjson"), body), body);
+}
+
+#[test]
+fn test_form_body_is_decoded_into_table() {
+    assert_eq!(
+        pretty(
+            Some("application/x-www-form-urlencoded"),
+            "name=Jane+Doe&email=jane%40example.com&x="
+        ),
+        "name  = Jane Doe\nemail = jane@example.com\nx     = "
+    );
+}
+
+#[test]
+fn test_xml_is_indented() {
+    let body = r#"<?xml version="1.0"?><feed><entry id="1"><title>Hi</title><empty/></entry><!-- note --></feed>"#;
+    assert_eq!(
+        pretty(Some("application/xml"), body),
+        [
+            r#"<?xml version="1.0"?>"#,
+            "<feed>",
+            r#"  <entry id="1">"#,
+            "    <title>Hi</title>",
+            "    <empty/>",
+            "  </entry>",
+            "  <!-- note -->",
+            "</feed>",
+        ]
+        .join("\n")
+    );
+}
+
+#[test]
+fn test_xml_attribute_with_angle_bracket() {
+    assert_eq!(
+        pretty(Some("text/xml"), r#"<a x="1>2"><b/></a>"#),
+        "<a x=\"1>2\">\n  <b/>\n</a>"
+    );
+}
+
+#[test]
+fn test_html_void_and_raw_text_elements() {
+    let body = "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><script>if (a<b) {}</script></head><body><p>Hello <br> world</p></body></html>";
+    assert_eq!(
+        pretty(Some("text/html"), body),
+        [
+            "<!DOCTYPE html>",
+            "<html>",
+            "  <head>",
+            "    <meta charset=\"utf-8\">",
+            "    <script>if (a<b) {}</script>",
+            "  </head>",
+            "  <body>",
+            "    <p>",
+            "      Hello",
+            "      <br>",
+            "      world",
+            "    </p>",
+            "  </body>",
+            "</html>",
+        ]
+        .join("\n")
+    );
+}
+
+#[test]
+fn test_event_stream_is_split_into_events() {
+    let body = "id: 1\ndata: hello\n\ndata: wor";
+    assert_eq!(
+        pretty(Some("text/event-stream"), body),
+        "[message] id: 1\nhello"
+    );
+    assert_eq!(pretty(Some("text/event-stream"), "data: wor"), "data: wor");
+}
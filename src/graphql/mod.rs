@@ -0,0 +1,316 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+
+/// Query sent to fetch the schema. Only what field completion needs is
+/// requested: root types and the fields of every type with their types.
+pub const INTROSPECTION_QUERY: &str = "query IntrospectionQuery { __schema { \
+queryType { name } mutationType { name } subscriptionType { name } \
+types { name fields(includeDeprecated: true) { name type { ...TypeRef } } } } } \
+fragment TypeRef on __Type { name ofType { name ofType { name ofType { name ofType { name } } } } }";
+
+/// JSON request body for a query and its variables. The variables editor
+/// may be left empty; otherwise it has to hold a JSON object.
+pub fn encode(query: &str, variables: &str) -> Result<String, String> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Err("GraphQL query is empty".to_string());
+    }
+    let mut request = json!({ "query": query });
+    let variables = variables.trim();
+    if !variables.is_empty() {
+        let value: Value = serde_json::from_str(variables)
+            .map_err(|e| format!("Invalid JSON in variables: {}", e))?;
+        if !value.is_object() {
+            return Err("Variables must be a JSON object".to_string());
+        }
+        request["variables"] = value;
+    }
+    Ok(request.to_string())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: String,
+    /// Named type of the field with lists and non-null wrappers removed
+    pub type_name: String,
+}
+
+/// Types and fields of a GraphQL schema, as far as completion needs them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Schema {
+    pub query_type: String,
+    pub mutation_type: Option<String>,
+    pub subscription_type: Option<String>,
+    types: HashMap<String, Vec<Field>>,
+}
+
+impl Schema {
+    /// Read the response to `INTROSPECTION_QUERY`
+    pub fn from_introspection(response: &Value) -> Result<Schema, String> {
+        if let Some(message) = response["errors"][0]["message"].as_str() {
+            return Err(format!("Introspection failed: {}", message));
+        }
+        let schema = &response["data"]["__schema"];
+        let query_type = schema["queryType"]["name"]
+            .as_str()
+            .ok_or("Response is not an introspection result")?
+            .to_string();
+        let root = |key: &str| schema[key]["name"].as_str().map(str::to_string);
+
+        let mut types = HashMap::new();
+        for item in schema["types"].as_array().into_iter().flatten() {
+            let Some(name) = item["name"].as_str() else {
+                continue;
+            };
+            let fields: Vec<Field> = item["fields"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|field| {
+                    Some(Field {
+                        name: field["name"].as_str()?.to_string(),
+                        type_name: named_type(&field["type"])?,
+                    })
+                })
+                .collect();
+            if !fields.is_empty() {
+                types.insert(name.to_string(), fields);
+            }
+        }
+
+        Ok(Schema {
+            query_type,
+            mutation_type: root("mutationType"),
+            subscription_type: root("subscriptionType"),
+            types,
+        })
+    }
+
+    pub fn fields(&self, type_name: &str) -> &[Field] {
+        self.types.get(type_name).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Named type of `field` on `type_name`
+    pub fn field_type(&self, type_name: &str, field: &str) -> Option<&str> {
+        self.fields(type_name)
+            .iter()
+            .find(|f| f.name == field)
+            .map(|f| f.type_name.as_str())
+    }
+
+    /// Number of types that have fields
+    pub fn type_count(&self) -> usize {
+        self.types.len()
+    }
+}
+
+/// Follow `ofType` through list and non-null wrappers to the named type
+fn named_type(type_ref: &Value) -> Option<String> {
+    let mut current = type_ref;
+    loop {
+        if let Some(name) = current["name"].as_str() {
+            return Some(name.to_string());
+        }
+        current = &current["ofType"];
+        if current.is_null() {
+            return None;
+        }
+    }
+}
+
+/// Field names that complete the word at the end of `before_cursor`, the
+/// query text up to the cursor. Returns the partly typed word and the
+/// matching fields of the selection set the cursor is in.
+pub fn complete<'a>(schema: &Schema, before_cursor: &'a str) -> (&'a str, Vec<String>) {
+    let word_start = before_cursor
+        .rfind(|c: char| !is_name_char(c))
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    let (text, prefix) = before_cursor.split_at(word_start);
+    // Variables and directives are not fields
+    if text.ends_with(['$', '@']) || prefix.starts_with(|c: char| c.is_ascii_digit()) {
+        return (prefix, Vec::new());
+    }
+
+    let Some(Some(type_name)) = selection_types(schema, text).pop() else {
+        return (prefix, Vec::new());
+    };
+    let mut names: Vec<String> = schema
+        .fields(&type_name)
+        .iter()
+        .map(|field| field.name.clone())
+        .chain(std::iter::once("__typename".to_string()))
+        .filter(|name| name.starts_with(prefix))
+        .collect();
+    names.sort();
+    (prefix, names)
+}
+
+/// Type of each selection set open at the end of `text`, outermost first;
+/// `None` where the type is unknown
+fn selection_types(schema: &Schema, text: &str) -> Vec<Option<String>> {
+    let mut stack: Vec<Option<String>> = Vec::new();
+    // Names since the last brace, e.g. `query Name` or `alias: field`
+    let mut names: Vec<String> = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '#' => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '"' => skip_string(&mut chars),
+            // No fields to complete inside an argument list
+            '(' if !skip_arguments(&mut chars) => return Vec::new(),
+            '$' | '@' => while chars.next_if(|c| is_name_char(*c)).is_some() {},
+            '{' => {
+                let type_name = selection_type(schema, stack.last(), &names);
+                stack.push(type_name);
+                names.clear();
+            }
+            '}' => {
+                stack.pop();
+                names.clear();
+            }
+            c if is_name_char(c) => {
+                let mut name = c.to_string();
+                while let Some(c) = chars.next_if(|c| is_name_char(*c)) {
+                    name.push(c);
+                }
+                names.push(name);
+            }
+            _ => {}
+        }
+    }
+    stack
+}
+
+/// Type of a selection set opened after `names`, inside a selection set of
+/// type `parent`, or at the top level when there is none
+fn selection_type(
+    schema: &Schema,
+    parent: Option<&Option<String>>,
+    names: &[String],
+) -> Option<String> {
+    // Fragments name their type: `fragment F on User {` or `... on User {`
+    if let Some(on) = names.iter().rposition(|name| name == "on") {
+        return names.get(on + 1).cloned();
+    }
+    match parent {
+        None => match names.first().map(String::as_str) {
+            Some("mutation") => schema.mutation_type.clone(),
+            Some("subscription") => schema.subscription_type.clone(),
+            _ => Some(schema.query_type.clone()),
+        },
+        Some(parent) => {
+            let field = names.last()?;
+            schema
+                .field_type(parent.as_deref()?, field)
+                .map(str::to_string)
+        }
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn skip_string(chars: &mut std::iter::Peekable<std::str::Chars>) {
+    let mut escaped = false;
+    for c in chars.by_ref() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => return,
+            _ => escaped = false,
+        }
+    }
+}
+
+/// Skip to the `)` closing an argument list, which may nest objects,
+/// lists and strings. Returns false when the list is not closed.
+fn skip_arguments(chars: &mut std::iter::Peekable<std::str::Chars>) -> bool {
+    let mut depth = 1;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => skip_string(chars),
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return true;
+                }
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Response with `errors` listed above `data`. Returns `None` for bodies
+/// that are not GraphQL responses.
+pub fn format_response(body: &str) -> Option<String> {
+    let value: Value = serde_json::from_str(body).ok()?;
+    let object = value.as_object()?;
+    if !object.contains_key("data") && !object.contains_key("errors") {
+        return None;
+    }
+
+    let mut sections = Vec::new();
+    if let Some(errors) = object.get("errors").and_then(Value::as_array) {
+        let mut lines = vec![format!("errors ({}):", errors.len())];
+        lines.extend(
+            errors
+                .iter()
+                .map(|error| format!("  • {}", describe_error(error))),
+        );
+        sections.push(lines.join("\n"));
+    }
+    if let Some(data) = object.get("data") {
+        let pretty = serde_json::to_string_pretty(data).unwrap_or_default();
+        sections.push(format!("data:\n{}", pretty));
+    }
+    if let Some(extensions) = object.get("extensions") {
+        let pretty = serde_json::to_string_pretty(extensions).unwrap_or_default();
+        sections.push(format!("extensions:\n{}", pretty));
+    }
+    Some(sections.join("\n\n"))
+}
+
+/// Message of an error with its path and first location, e.g.
+/// `Not found (at user.posts[0], line 2:5)`
+fn describe_error(error: &Value) -> String {
+    let message = match error["message"].as_str() {
+        Some(message) => message.to_string(),
+        None => error.to_string(),
+    };
+    let mut details = Vec::new();
+    if let Some(path) = error["path"].as_array() {
+        let mut text = String::new();
+        for segment in path {
+            match segment {
+                Value::Number(index) => text.push_str(&format!("[{}]", index)),
+                Value::String(name) if text.is_empty() => text.push_str(name),
+                Value::String(name) => text.push_str(&format!(".{}", name)),
+                _ => {}
+            }
+        }
+        details.push(format!("at {}", text));
+    }
+    let location = &error["locations"][0];
+    if let (Some(line), Some(column)) = (location["line"].as_u64(), location["column"].as_u64()) {
+        details.push(format!("line {}:{}", line, column));
+    }
+    if details.is_empty() {
+        message
+    } else {
+        format!("{} ({})", message, details.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,146 @@
+use super::*;
+
+fn schema() -> Schema {
+    let response = json!({
+        "data": {
+            "__schema": {
+                "queryType": { "name": "Query" },
+                "mutationType": { "name": "Mutation" },
+                "subscriptionType": null,
+                "types": [
+                    {
+                        "name": "Query",
+                        "fields": [
+                            { "name": "user", "type": { "name": "User", "ofType": null } },
+                            { "name": "users", "type": { "name": null, "ofType": {
+                                "name": null, "ofType": { "name": "User", "ofType": null } } } }
+                        ]
+                    },
+                    {
+                        "name": "Mutation",
+                        "fields": [
+                            { "name": "createUser", "type": { "name": "User", "ofType": null } }
+                        ]
+                    },
+                    {
+                        "name": "User",
+                        "fields": [
+                            { "name": "id", "type": { "name": "ID", "ofType": null } },
+                            { "name": "name", "type": { "name": "String", "ofType": null } },
+                            { "name": "friends", "type": { "name": null, "ofType": { "name": "User", "ofType": null } } }
+                        ]
+                    },
+                    { "name": "String", "fields": null }
+                ]
+            }
+        }
+    });
+    Schema::from_introspection(&response).unwrap()
+}
+
+#[test]
+fn test_encode_query_and_variables() {
+    let body = encode("{ user(id: $id) { name } }", "{\"id\": 1}").unwrap();
+    let value: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        value,
+        json!({ "query": "{ user(id: $id) { name } }", "variables": { "id": 1 } })
+    );
+
+    let body = encode("{ users { id } }\n", "  ").unwrap();
+    assert_eq!(body, r#"{"query":"{ users { id } }"}"#);
+}
+
+#[test]
+fn test_encode_rejects_bad_variables() {
+    assert_eq!(encode(" ", "{}"), Err("GraphQL query is empty".to_string()));
+    assert_eq!(
+        encode("{ a }", "[1]"),
+        Err("Variables must be a JSON object".to_string())
+    );
+    assert!(encode("{ a }", "{")
+        .unwrap_err()
+        .starts_with("Invalid JSON in variables"));
+}
+
+#[test]
+fn test_schema_from_introspection() {
+    let schema = schema();
+    assert_eq!(schema.query_type, "Query");
+    assert_eq!(schema.mutation_type.as_deref(), Some("Mutation"));
+    assert_eq!(schema.subscription_type, None);
+    assert_eq!(schema.type_count(), 3);
+    // Lists and non-null wrappers are unwrapped
+    assert_eq!(schema.field_type("Query", "users"), Some("User"));
+    assert!(schema.fields("String").is_empty());
+
+    let error = json!({ "errors": [{ "message": "introspection disabled" }] });
+    assert_eq!(
+        Schema::from_introspection(&error),
+        Err("Introspection failed: introspection disabled".to_string())
+    );
+    assert!(Schema::from_introspection(&json!({ "data": {} })).is_err());
+}
+
+#[test]
+fn test_complete_root_fields() {
+    let schema = schema();
+    assert_eq!(
+        complete(&schema, "{ us"),
+        ("us", vec!["user".to_string(), "users".to_string()])
+    );
+    assert_eq!(
+        complete(&schema, "mutation Add {\n  cr"),
+        ("cr", vec!["createUser".to_string()])
+    );
+}
+
+#[test]
+fn test_complete_nested_fields() {
+    let schema = schema();
+    let query = "query Q($id: ID) {\n  me: user(id: $id, filter: { name: \"a)\" }) {\n    id\n    friends { n";
+    assert_eq!(complete(&schema, query), ("n", vec!["name".to_string()]));
+
+    // Back in the user selection after the friends block closed
+    let (prefix, names) = complete(&schema, "{ user { friends { id } ");
+    assert_eq!(prefix, "");
+    assert_eq!(names, vec!["__typename", "friends", "id", "name"]);
+}
+
+#[test]
+fn test_complete_fragments() {
+    let schema = schema();
+    assert_eq!(
+        complete(&schema, "fragment F on User { fr"),
+        ("fr", vec!["friends".to_string()])
+    );
+    assert_eq!(
+        complete(&schema, "{ users { ... on User { na"),
+        ("na", vec!["name".to_string()])
+    );
+}
+
+#[test]
+fn test_complete_nothing_outside_selections() {
+    let schema = schema();
+    assert!(complete(&schema, "quer").1.is_empty());
+    assert!(complete(&schema, "{ user(id: $i").1.is_empty());
+    assert!(complete(&schema, "{ unknown { i").1.is_empty());
+    // Comments are skipped
+    assert!(complete(&schema, "# { user {\nquer").1.is_empty());
+}
+
+#[test]
+fn test_format_response_separates_errors() {
+    let body = r#"{"errors":[{"message":"Not found","path":["user","friends",0],"locations":[{"line":2,"column":5}]}],"data":{"user":null}}"#;
+    assert_eq!(
+        format_response(body).unwrap(),
+        "errors (1):\n  • Not found (at user.friends[0], line 2:5)\n\ndata:\n{\n  \"user\": null\n}"
+    );
+    assert_eq!(
+        format_response(r#"{"data":{"a":1}}"#).unwrap(),
+        "data:\n{\n  \"a\": 1\n}"
+    );
+    assert_eq!(format_response(r#"{"a":1}"#), None);
+    assert_eq!(format_response("not json"), None);
+}
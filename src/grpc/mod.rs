@@ -0,0 +1,379 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context};
+use bytes::Bytes;
+use percent_encoding::percent_decode_str;
+use reqwest::blocking::Client;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::http::ClientSettings;
+use crate::proto::{Descriptors, MethodDesc};
+use crate::tls;
+
+/// Flag bit of a message frame whose payload is compressed
+const COMPRESSED_FLAG: u8 = 0x01;
+/// Flag bit of the gRPC-Web frame that carries the trailers
+const TRAILERS_FLAG: u8 = 0x80;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transport {
+    /// HTTP/2, cleartext for `http://` URLs and TLS for `https://`
+    Grpc,
+    /// gRPC-Web over HTTP/1.1, through the regular HTTP client
+    GrpcWeb,
+}
+
+impl Transport {
+    /// Transport of a method selector entry
+    pub fn for_method(method: &str) -> Option<Transport> {
+        match method {
+            "GRPC" => Some(Transport::Grpc),
+            "GRPC-WEB" => Some(Transport::GrpcWeb),
+            _ => None,
+        }
+    }
+}
+
+/// Method path of a gRPC URL, e.g. `/shop.v1.Orders/Get`
+pub fn method_path(url: &str) -> Result<String, String> {
+    reqwest::Url::parse(url)
+        .map(|url| url.path().to_string())
+        .map_err(|e| format!("Invalid URL {}: {}", url, e))
+}
+
+/// `url` with its path replaced by the path of a method
+pub fn with_method_path(url: &str, path: &str) -> Result<String, String> {
+    let mut url = reqwest::Url::parse(url)
+        .ok()
+        .filter(reqwest::Url::has_host)
+        .ok_or("Enter the server URL first, e.g. http://localhost:50051")?;
+    url.set_path(path);
+    url.set_query(None);
+    Ok(url.to_string())
+}
+
+/// Framed request messages for the body editor text. Client streaming
+/// methods take a JSON array with one element per message.
+pub fn encode_request(
+    descriptors: &Descriptors,
+    method: &MethodDesc,
+    text: &str,
+) -> Result<Vec<u8>, String> {
+    let text = text.trim();
+    let value: serde_json::Value = if text.is_empty() {
+        serde_json::json!({})
+    } else {
+        serde_json::from_str(text).map_err(|e| format!("Invalid JSON in body: {}", e))?
+    };
+    let messages = match value {
+        serde_json::Value::Array(items) if method.client_streaming => items,
+        _ if method.client_streaming => {
+            return Err(format!(
+                "{} is client streaming, send a JSON array of messages",
+                method.path()
+            ))
+        }
+        value => vec![value],
+    };
+    let mut body = Vec::new();
+    for message in &messages {
+        body.extend(frame(&descriptors.encode(&method.input, message)?));
+    }
+    Ok(body)
+}
+
+/// Header or trailer name/value pairs, names lowercased
+pub type Metadata = Vec<(String, String)>;
+
+/// Length-prefixed message as sent in a gRPC body
+pub fn frame(message: &[u8]) -> Vec<u8> {
+    let mut framed = Vec::with_capacity(message.len() + 5);
+    framed.push(0);
+    framed.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    framed.extend_from_slice(message);
+    framed
+}
+
+/// Split a response body into its messages and, for gRPC-Web, the trailers
+/// sent in the last frame
+pub fn unframe(data: &[u8]) -> Result<(Vec<Vec<u8>>, Metadata), String> {
+    let mut messages = Vec::new();
+    let mut trailers = Vec::new();
+    let mut rest = data;
+    while !rest.is_empty() {
+        if rest.len() < 5 {
+            return Err("Response ends in the middle of a message".to_string());
+        }
+        let flags = rest[0];
+        let len = u32::from_be_bytes([rest[1], rest[2], rest[3], rest[4]]) as usize;
+        let payload = rest
+            .get(5..5 + len)
+            .ok_or("Response ends in the middle of a message")?;
+        if flags & TRAILERS_FLAG != 0 {
+            trailers = parse_trailers(payload);
+        } else if flags & COMPRESSED_FLAG != 0 {
+            return Err("Compressed messages are not supported".to_string());
+        } else {
+            messages.push(payload.to_vec());
+        }
+        rest = &rest[5 + len..];
+    }
+    Ok((messages, trailers))
+}
+
+/// `name: value` lines of a gRPC-Web trailers frame
+fn parse_trailers(payload: &[u8]) -> Metadata {
+    String::from_utf8_lossy(payload)
+        .split("\r\n")
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GrpcResponse {
+    pub http_status: u16,
+    pub headers: Metadata,
+    /// Undecoded response messages, more than one for server streaming
+    pub messages: Vec<Vec<u8>>,
+    pub trailers: Metadata,
+    pub elapsed: Duration,
+}
+
+impl GrpcResponse {
+    fn metadata(&self, name: &str) -> Option<&str> {
+        // Errors without a body come back as trailers-only responses,
+        // with the status in the headers
+        self.trailers
+            .iter()
+            .chain(&self.headers)
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The `grpc-status` code
+    pub fn status(&self) -> Option<u32> {
+        self.metadata("grpc-status")?.parse().ok()
+    }
+
+    /// The `grpc-message` error description, percent-decoded
+    pub fn status_message(&self) -> Option<String> {
+        self.metadata("grpc-message")
+            .filter(|message| !message.is_empty())
+            .map(|message| percent_decode_str(message).decode_utf8_lossy().into_owned())
+    }
+
+    /// Status code and name, e.g. `5 NOT_FOUND`
+    pub fn status_label(&self) -> String {
+        match self.status() {
+            Some(code) => format!("{} {}", code, status_name(code)),
+            None => format!("missing (HTTP {})", self.http_status),
+        }
+    }
+}
+
+pub fn status_name(code: u32) -> &'static str {
+    match code {
+        0 => "OK",
+        1 => "CANCELLED",
+        2 => "UNKNOWN",
+        3 => "INVALID_ARGUMENT",
+        4 => "DEADLINE_EXCEEDED",
+        5 => "NOT_FOUND",
+        6 => "ALREADY_EXISTS",
+        7 => "PERMISSION_DENIED",
+        8 => "RESOURCE_EXHAUSTED",
+        9 => "FAILED_PRECONDITION",
+        10 => "ABORTED",
+        11 => "OUT_OF_RANGE",
+        12 => "UNIMPLEMENTED",
+        13 => "INTERNAL",
+        14 => "UNAVAILABLE",
+        15 => "DATA_LOSS",
+        16 => "UNAUTHENTICATED",
+        _ => "UNKNOWN_CODE",
+    }
+}
+
+/// Status, decoded messages and trailers as shown in the response pane
+pub fn render(response: &GrpcResponse, descriptors: &Descriptors, output: &str) -> String {
+    let mut sections = vec![format!("status: {}", response.status_label())];
+    if let Some(message) = response.status_message() {
+        sections[0].push_str(&format!("\nmessage: {}", message));
+    }
+    for bytes in &response.messages {
+        sections.push(match descriptors.decode(output, bytes) {
+            Ok(value) => serde_json::to_string_pretty(&value).unwrap_or_default(),
+            Err(e) => format!("Failed to decode {}: {}", output, e),
+        });
+    }
+    if !response.trailers.is_empty() {
+        let lines: Vec<String> = response
+            .trailers
+            .iter()
+            .map(|(name, value)| format!("  {}: {}", name, value))
+            .collect();
+        sections.push(format!("trailers:\n{}", lines.join("\n")));
+    }
+    sections.join("\n\n")
+}
+
+/// Call the method at `url`, whose path names it, with a body of framed
+/// messages. Proxy settings only apply to gRPC-Web.
+pub fn call(
+    transport: Transport,
+    client: &Client,
+    settings: &ClientSettings,
+    url: &str,
+    headers: &[(String, String)],
+    body: Vec<u8>,
+) -> Result<GrpcResponse, String> {
+    match transport {
+        Transport::Grpc => call_h2(settings, url, headers, body),
+        Transport::GrpcWeb => call_web(client, url, headers, body),
+    }
+    .map_err(|e| format!("{:#}", e))
+}
+
+fn call_web(
+    client: &Client,
+    url: &str,
+    headers: &[(String, String)],
+    body: Vec<u8>,
+) -> anyhow::Result<GrpcResponse> {
+    let start = Instant::now();
+    let mut request = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/grpc-web+proto")
+        .header(reqwest::header::ACCEPT, "application/grpc-web+proto")
+        .header("x-grpc-web", "1");
+    for (key, value) in headers {
+        request = request.header(key, value);
+    }
+    let response = request.body(body).send()?;
+    let http_status = response.status().as_u16();
+    let headers = header_pairs(response.headers());
+    let data = response.bytes()?;
+    let (messages, trailers) = unframe(&data).map_err(|e| anyhow!(e))?;
+    Ok(GrpcResponse {
+        http_status,
+        headers,
+        messages,
+        trailers,
+        elapsed: start.elapsed(),
+    })
+}
+
+fn call_h2(
+    settings: &ClientSettings,
+    url: &str,
+    headers: &[(String, String)],
+    body: Vec<u8>,
+) -> anyhow::Result<GrpcResponse> {
+    let uri: http::Uri = url
+        .parse()
+        .with_context(|| format!("Invalid URL {}", url))?;
+    let secure = match uri.scheme_str() {
+        Some("http") => false,
+        Some("https") => true,
+        _ => return Err(anyhow!("gRPC URLs start with http:// or https://")),
+    };
+    let host = uri
+        .host()
+        .ok_or_else(|| anyhow!("Missing host in {}", url))?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string();
+    let port = uri.port_u16().unwrap_or(if secure { 443 } else { 80 });
+
+    let mut request = http::Request::post(uri)
+        .header("content-type", "application/grpc")
+        .header("te", "trailers");
+    for (key, value) in headers {
+        request = request.header(key.as_str(), value.as_str());
+    }
+    let request = request.body(()).context("Invalid request headers")?;
+
+    let start = Instant::now();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let call = async {
+        let tcp = tokio::net::TcpStream::connect((host.as_str(), port))
+            .await
+            .with_context(|| format!("Failed to connect to {}:{}", host, port))?;
+        if secure {
+            let connector = tls::connector_with_alpn(&settings.tls, &["h2"])?;
+            let stream = tokio_native_tls::TlsConnector::from(connector)
+                .connect(&host, tcp)
+                .await
+                .context("TLS handshake failed")?;
+            exchange(stream, request, body).await
+        } else {
+            exchange(tcp, request, body).await
+        }
+    };
+    let mut response = runtime.block_on(async {
+        match settings.timeout_secs {
+            0 => call.await,
+            secs => tokio::time::timeout(Duration::from_secs(secs), call)
+                .await
+                .map_err(|_| anyhow!("Request timed out after {}s", secs))?,
+        }
+    })?;
+    response.elapsed = start.elapsed();
+    Ok(response)
+}
+
+/// Send one request over a new HTTP/2 connection and read the whole
+/// response including its trailers
+async fn exchange<S>(
+    io: S,
+    request: http::Request<()>,
+    body: Vec<u8>,
+) -> anyhow::Result<GrpcResponse>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let (client, connection) = h2::client::handshake(io)
+        .await
+        .context("HTTP/2 handshake failed")?;
+    tokio::spawn(async move {
+        let _ = connection.await;
+    });
+    let mut client = client.ready().await?;
+    let (response, mut stream) = client.send_request(request, false)?;
+    stream.send_data(Bytes::from(body), true)?;
+
+    let (parts, mut body) = response.await?.into_parts();
+    let mut data = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk?;
+        let _ = body.flow_control().release_capacity(chunk.len());
+        data.extend_from_slice(&chunk);
+    }
+    let trailers = body.trailers().await?;
+    let (messages, _) = unframe(&data).map_err(|e| anyhow!(e))?;
+    Ok(GrpcResponse {
+        http_status: parts.status.as_u16(),
+        headers: header_pairs(&parts.headers),
+        messages,
+        trailers: trailers.as_ref().map(header_pairs).unwrap_or_default(),
+        elapsed: Duration::ZERO,
+    })
+}
+
+fn header_pairs(headers: &http::HeaderMap) -> Metadata {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            (
+                name.as_str().to_string(),
+                String::from_utf8_lossy(value.as_bytes()).into_owned(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests;
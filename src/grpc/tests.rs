@@ -0,0 +1,214 @@
+use super::*;
+
+fn response(trailers: &[(&str, &str)], headers: &[(&str, &str)]) -> GrpcResponse {
+    let pairs = |pairs: &[(&str, &str)]| {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    };
+    GrpcResponse {
+        http_status: 200,
+        headers: pairs(headers),
+        messages: Vec::new(),
+        trailers: pairs(trailers),
+        elapsed: Duration::ZERO,
+    }
+}
+
+fn trailers_frame(text: &str) -> Vec<u8> {
+    let mut framed = frame(text.as_bytes());
+    framed[0] = TRAILERS_FLAG;
+    framed
+}
+
+/// HTTP/2 server that answers every call with each request message twice,
+/// then trailers naming the path that was called
+fn serve_h2() -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.set_nonblocking(true).unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async move {
+            let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut connection = h2::server::handshake(socket).await.unwrap();
+            while let Some(request) = connection.accept().await {
+                let (request, mut respond) = request.unwrap();
+                let path = request.uri().path().to_string();
+                let mut body = request.into_body();
+                let mut data = Vec::new();
+                while let Some(chunk) = body.data().await {
+                    let chunk = chunk.unwrap();
+                    let _ = body.flow_control().release_capacity(chunk.len());
+                    data.extend_from_slice(&chunk);
+                }
+
+                let head = http::Response::builder()
+                    .header("content-type", "application/grpc")
+                    .body(())
+                    .unwrap();
+                let mut stream = respond.send_response(head, false).unwrap();
+                let (messages, _) = unframe(&data).unwrap();
+                let mut out = Vec::new();
+                for message in &messages {
+                    out.extend(frame(message));
+                    out.extend(frame(message));
+                }
+                stream.send_data(Bytes::from(out), false).unwrap();
+                let mut trailers = http::HeaderMap::new();
+                trailers.insert("grpc-status", "0".parse().unwrap());
+                trailers.insert("x-path", path.parse().unwrap());
+                stream.send_trailers(trailers).unwrap();
+            }
+        });
+    });
+    format!("http://{}", addr)
+}
+
+#[test]
+fn test_frame_and_unframe() {
+    assert_eq!(frame(b"hi"), vec![0, 0, 0, 0, 2, b'h', b'i']);
+
+    let mut data = frame(b"one");
+    data.extend(frame(b""));
+    data.extend(trailers_frame("grpc-status: 0\r\nX-Id: 7\r\n"));
+    let (messages, trailers) = unframe(&data).unwrap();
+    assert_eq!(messages, vec![b"one".to_vec(), Vec::new()]);
+    assert_eq!(
+        trailers,
+        vec![
+            ("grpc-status".to_string(), "0".to_string()),
+            ("x-id".to_string(), "7".to_string())
+        ]
+    );
+}
+
+#[test]
+fn test_unframe_errors() {
+    let truncated = &frame(b"hello")[..6];
+    assert_eq!(
+        unframe(truncated),
+        Err("Response ends in the middle of a message".to_string())
+    );
+    let mut compressed = frame(b"x");
+    compressed[0] = COMPRESSED_FLAG;
+    assert_eq!(
+        unframe(&compressed),
+        Err("Compressed messages are not supported".to_string())
+    );
+}
+
+#[test]
+fn test_status_from_trailers_or_headers() {
+    let ok = response(&[("grpc-status", "0")], &[]);
+    assert_eq!(ok.status_label(), "0 OK");
+    assert_eq!(ok.status_message(), None);
+
+    // Trailers-only error response
+    let error = response(
+        &[],
+        &[
+            ("grpc-status", "5"),
+            ("grpc-message", "user%207%20not found"),
+        ],
+    );
+    assert_eq!(error.status_label(), "5 NOT_FOUND");
+    assert_eq!(error.status_message().as_deref(), Some("user 7 not found"));
+
+    let mut missing = response(&[], &[]);
+    missing.http_status = 404;
+    assert_eq!(missing.status_label(), "missing (HTTP 404)");
+}
+
+#[test]
+fn test_render_decodes_messages() {
+    let mut descriptors = Descriptors::default();
+    descriptors
+        .parse(
+            "a.proto",
+            "syntax = \"proto3\"; message Reply { string text = 1; }",
+        )
+        .unwrap();
+    descriptors.resolve().unwrap();
+
+    let mut reply = response(&[("grpc-status", "0"), ("x-id", "7")], &[]);
+    reply.messages = vec![vec![0x0a, 0x02, b'h', b'i'], vec![0x0a, 0x05]];
+    assert_eq!(
+        render(&reply, &descriptors, "Reply"),
+        "status: 0 OK\n\n{\n  \"text\": \"hi\"\n}\n\n\
+         Failed to decode Reply: Message ends in the middle of a field\n\n\
+         trailers:\n  grpc-status: 0\n  x-id: 7"
+    );
+}
+
+#[test]
+fn test_call_over_http2() {
+    let url = format!("{}/echo.Echo/Say", serve_h2());
+    let response = call(
+        Transport::Grpc,
+        &Client::new(),
+        &ClientSettings::default(),
+        &url,
+        &[("authorization".to_string(), "Bearer x".to_string())],
+        frame(b"hi"),
+    )
+    .unwrap();
+
+    assert_eq!(response.http_status, 200);
+    assert_eq!(response.messages, vec![b"hi".to_vec(), b"hi".to_vec()]);
+    assert_eq!(response.status(), Some(0));
+    assert!(response
+        .trailers
+        .contains(&("x-path".to_string(), "/echo.Echo/Say".to_string())));
+}
+
+#[test]
+fn test_call_grpc_web() {
+    let url = crate::http::test_server::serve_raw(|request| {
+        let request = String::from_utf8_lossy(request).to_ascii_lowercase();
+        assert!(request.contains("content-type: application/grpc-web+proto"));
+        let mut body = frame(b"ok");
+        body.extend(trailers_frame(
+            "grpc-status: 5\r\ngrpc-message: not%20found\r\n",
+        ));
+        let mut response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/grpc-web+proto\r\nContent-Length: {}\r\n\r\n",
+            body.len()
+        )
+        .into_bytes();
+        response.extend(body);
+        response
+    });
+    let response = call(
+        Transport::GrpcWeb,
+        &Client::new(),
+        &ClientSettings::default(),
+        &url,
+        &[],
+        frame(b"hi"),
+    )
+    .unwrap();
+
+    assert_eq!(response.messages, vec![b"ok".to_vec()]);
+    assert_eq!(response.status_label(), "5 NOT_FOUND");
+    assert_eq!(response.status_message().as_deref(), Some("not found"));
+}
+
+#[test]
+fn test_call_rejects_other_schemes() {
+    let error = call(
+        Transport::Grpc,
+        &Client::new(),
+        &ClientSettings::default(),
+        "ftp://localhost/a.B/C",
+        &[],
+        Vec::new(),
+    )
+    .unwrap_err();
+    assert_eq!(error, "gRPC URLs start with http:// or https://");
+}
@@ -0,0 +1,168 @@
+use reqwest::header::{HeaderName, HeaderValue};
+
+/// Header names offered by completion, in their usual spelling
+pub const NAMES: &[&str] = &[
+    "Accept",
+    "Accept-Charset",
+    "Accept-Encoding",
+    "Accept-Language",
+    "Authorization",
+    "Cache-Control",
+    "Connection",
+    "Content-Disposition",
+    "Content-Encoding",
+    "Content-Language",
+    "Content-Length",
+    "Content-Type",
+    "Cookie",
+    "DNT",
+    "Expect",
+    "Forwarded",
+    "From",
+    "Host",
+    "If-Match",
+    "If-Modified-Since",
+    "If-None-Match",
+    "If-Range",
+    "If-Unmodified-Since",
+    "Origin",
+    "Pragma",
+    "Prefer",
+    "Range",
+    "Referer",
+    "TE",
+    "Upgrade",
+    "User-Agent",
+    "Via",
+    "X-API-Key",
+    "X-Correlation-ID",
+    "X-Forwarded-For",
+    "X-Forwarded-Host",
+    "X-Forwarded-Proto",
+    "X-Request-ID",
+    "X-Requested-With",
+];
+
+const MEDIA_TYPES: &[&str] = &[
+    "*/*",
+    "application/graphql",
+    "application/json",
+    "application/octet-stream",
+    "application/x-www-form-urlencoded",
+    "application/xml",
+    "multipart/form-data",
+    "text/csv",
+    "text/event-stream",
+    "text/html",
+    "text/plain",
+    "text/xml",
+];
+
+const CACHE_DIRECTIVES: &[&str] = &[
+    "max-age=",
+    "max-stale",
+    "min-fresh=",
+    "no-cache",
+    "no-store",
+    "no-transform",
+    "only-if-cached",
+];
+
+const ENCODINGS: &[&str] = &["br", "deflate", "gzip", "identity", "zstd"];
+
+/// Common values for a header, or for each comma-separated item of it
+pub fn values(name: &str) -> &'static [&'static str] {
+    match name.to_ascii_lowercase().as_str() {
+        "accept" | "content-type" => MEDIA_TYPES,
+        "accept-encoding" | "content-encoding" | "te" => ENCODINGS,
+        "authorization" => &["Basic ", "Bearer "],
+        "cache-control" | "pragma" => CACHE_DIRECTIVES,
+        "connection" => &["close", "keep-alive", "upgrade"],
+        "expect" => &["100-continue"],
+        "prefer" => &["respond-async", "return=minimal", "return=representation"],
+        "x-requested-with" => &["XMLHttpRequest"],
+        _ => &[],
+    }
+}
+
+/// Parse one line of the headers editor. Blank lines and lines starting
+/// with `#` are not headers. The space after the colon is optional.
+pub fn parse_line(line: &str) -> Result<Option<(String, String)>, String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+    let Some((name, value)) = line.split_once(':') else {
+        return Err("Missing ':' between name and value".to_string());
+    };
+    let (name, value) = (name.trim(), value.trim());
+    if name.is_empty() {
+        return Err("Missing header name".to_string());
+    }
+    if HeaderName::from_bytes(name.as_bytes()).is_err() {
+        return Err(format!("Invalid header name: {}", name));
+    }
+    if HeaderValue::from_str(value).is_err() {
+        return Err(format!("Invalid value for {}", name));
+    }
+    Ok(Some((name.to_string(), value.to_string())))
+}
+
+/// Headers to send. Malformed lines are left out; `errors` reports them.
+pub fn parse(lines: &[String]) -> Vec<(String, String)> {
+    lines
+        .iter()
+        .filter_map(|line| parse_line(line).ok().flatten())
+        .collect()
+}
+
+/// Index and message of every malformed line
+pub fn errors(lines: &[String]) -> Vec<(usize, String)> {
+    lines
+        .iter()
+        .enumerate()
+        .filter_map(|(i, line)| parse_line(line).err().map(|e| (i, e)))
+        .collect()
+}
+
+/// Completions for the end of `before_cursor`, the line up to the cursor.
+/// Returns the partly typed word and the candidates that start with it.
+/// Before the colon names are completed, and a single match is followed
+/// by `": "`; after it, the current comma-separated item of the value.
+pub fn complete(before_cursor: &str) -> (&str, Vec<String>) {
+    if before_cursor.trim_start().starts_with('#') {
+        return ("", Vec::new());
+    }
+    let Some((name, value)) = before_cursor.split_once(':') else {
+        let prefix = before_cursor.trim_start();
+        if prefix.is_empty() {
+            return (prefix, Vec::new());
+        }
+        let names: Vec<String> = matching(NAMES, prefix).collect();
+        if let [name] = names.as_slice() {
+            return (prefix, vec![format!("{}: ", name)]);
+        }
+        return (prefix, names);
+    };
+    let item_start = value.rfind(',').map(|i| i + 1).unwrap_or(0);
+    let prefix = value[item_start..].trim_start();
+    let values = matching(values(name.trim()), prefix)
+        // Nothing to add to a value that is already complete
+        .filter(|value| value.len() > prefix.len())
+        .collect();
+    (prefix, values)
+}
+
+fn matching<'a>(candidates: &'a [&'a str], prefix: &'a str) -> impl Iterator<Item = String> + 'a {
+    candidates
+        .iter()
+        .filter(move |candidate| {
+            candidate
+                .get(..prefix.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+        })
+        .map(|candidate| candidate.to_string())
+}
+
+#[cfg(test)]
+mod tests;
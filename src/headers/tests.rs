@@ -0,0 +1,103 @@
+use super::*;
+
+#[test]
+fn test_parse_line_is_tolerant() {
+    assert_eq!(
+        parse_line("Content-Type:application/json"),
+        Ok(Some((
+            "Content-Type".to_string(),
+            "application/json".to_string()
+        )))
+    );
+    assert_eq!(
+        parse_line("  X-Token :  a:b  "),
+        Ok(Some(("X-Token".to_string(), "a:b".to_string())))
+    );
+    assert_eq!(
+        parse_line("X-Empty:"),
+        Ok(Some(("X-Empty".to_string(), String::new())))
+    );
+}
+
+#[test]
+fn test_parse_line_skips_blank_and_comments() {
+    assert_eq!(parse_line(""), Ok(None));
+    assert_eq!(parse_line("   "), Ok(None));
+    assert_eq!(parse_line("# Authorization: Bearer old"), Ok(None));
+    assert_eq!(parse_line("  #Accept: */*"), Ok(None));
+}
+
+#[test]
+fn test_parse_line_errors() {
+    assert_eq!(
+        parse_line("Invalid Header Without Colon"),
+        Err("Missing ':' between name and value".to_string())
+    );
+    assert_eq!(
+        parse_line(": value"),
+        Err("Missing header name".to_string())
+    );
+    assert_eq!(
+        parse_line("Bad Name: value"),
+        Err("Invalid header name: Bad Name".to_string())
+    );
+    assert_eq!(
+        parse_line("X-Bell: a\u{7}b"),
+        Err("Invalid value for X-Bell".to_string())
+    );
+}
+
+#[test]
+fn test_parse_and_errors() {
+    let lines: Vec<String> = ["Accept: */*", "oops", "# X-Debug: 1", "X-Id:7"]
+        .iter()
+        .map(|line| line.to_string())
+        .collect();
+
+    assert_eq!(
+        parse(&lines),
+        vec![
+            ("Accept".to_string(), "*/*".to_string()),
+            ("X-Id".to_string(), "7".to_string()),
+        ]
+    );
+    assert_eq!(
+        errors(&lines),
+        vec![(1, "Missing ':' between name and value".to_string())]
+    );
+}
+
+#[test]
+fn test_complete_names() {
+    let (prefix, names) = complete("content-t");
+    assert_eq!(prefix, "content-t");
+    assert_eq!(names, vec!["Content-Type: "]);
+
+    let (prefix, names) = complete("If-M");
+    assert_eq!(prefix, "If-M");
+    assert_eq!(names, vec!["If-Match", "If-Modified-Since"]);
+
+    assert!(complete("").1.is_empty());
+    assert!(complete("X-Unknown-").1.is_empty());
+    assert!(complete("# Acc").1.is_empty());
+}
+
+#[test]
+fn test_complete_values() {
+    let (prefix, values) = complete("Content-Type: application/j");
+    assert_eq!(prefix, "application/j");
+    assert_eq!(values, vec!["application/json"]);
+
+    // Each comma-separated directive is completed on its own
+    let (prefix, values) = complete("Cache-Control: no-cache, no-s");
+    assert_eq!(prefix, "no-s");
+    assert_eq!(values, vec!["no-store"]);
+
+    let (prefix, values) = complete("accept-encoding:");
+    assert_eq!(prefix, "");
+    assert_eq!(values.len(), ENCODINGS.len());
+
+    // Complete values and unknown headers have nothing to offer
+    assert!(complete("Accept: text/html").1.is_empty());
+    assert!(complete("X-Custom: a").1.is_empty());
+}
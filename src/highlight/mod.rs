@@ -0,0 +1,390 @@
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Language {
+    Json,
+    Xml,
+    Html,
+    Yaml,
+    Plain,
+}
+
+impl Language {
+    /// Pick a language from the Content-Type, falling back to sniffing the text
+    pub fn detect(content_type: Option<&str>, text: &str) -> Language {
+        let content_type = content_type.unwrap_or_default().to_ascii_lowercase();
+        if content_type.contains("json") {
+            return Language::Json;
+        }
+        if content_type.contains("yaml") || content_type.contains("yml") {
+            return Language::Yaml;
+        }
+        if content_type.contains("html") {
+            return Language::Html;
+        }
+        if content_type.contains("xml") {
+            return Language::Xml;
+        }
+
+        let start = text.trim_start();
+        let lower: String = start
+            .chars()
+            .take(15)
+            .collect::<String>()
+            .to_ascii_lowercase();
+        if (start.starts_with('{') || start.starts_with('['))
+            && serde_json::from_str::<serde_json::Value>(text).is_ok()
+        {
+            Language::Json
+        } else if lower.starts_with("<!doctype html") || lower.starts_with("<html") {
+            Language::Html
+        } else if start.starts_with('<') {
+            Language::Xml
+        } else {
+            Language::Plain
+        }
+    }
+}
+
+/// Colors used for each kind of token
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette {
+    pub text: Style,
+    pub key: Style,
+    pub string: Style,
+    pub number: Style,
+    pub boolean: Style,
+    pub null: Style,
+    pub punctuation: Style,
+    pub tag: Style,
+    pub attribute: Style,
+    pub comment: Style,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Theme {
+    Dark,
+    Light,
+    Mono,
+}
+
+impl Theme {
+    pub const ALL: &'static [Theme] = &[Theme::Dark, Theme::Light, Theme::Mono];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Theme::Dark => "dark",
+            Theme::Light => "light",
+            Theme::Mono => "mono",
+        }
+    }
+
+    pub fn next(&self) -> Theme {
+        let index = Self::ALL.iter().position(|t| t == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn palette(&self) -> Palette {
+        let fg = |color| Style::default().fg(color);
+        match self {
+            Theme::Dark => Palette {
+                text: fg(Color::DarkGray),
+                key: fg(Color::Cyan),
+                string: fg(Color::Green),
+                number: fg(Color::Yellow),
+                boolean: fg(Color::Magenta),
+                null: fg(Color::Red),
+                punctuation: fg(Color::Gray),
+                tag: fg(Color::Blue),
+                attribute: fg(Color::Cyan),
+                comment: fg(Color::DarkGray).add_modifier(Modifier::ITALIC),
+            },
+            Theme::Light => Palette {
+                text: fg(Color::Black),
+                key: fg(Color::Blue),
+                string: fg(Color::Green),
+                number: fg(Color::Magenta),
+                boolean: fg(Color::Red),
+                null: fg(Color::Red),
+                punctuation: fg(Color::DarkGray),
+                tag: fg(Color::Blue),
+                attribute: fg(Color::Magenta),
+                comment: fg(Color::Gray).add_modifier(Modifier::ITALIC),
+            },
+            Theme::Mono => Palette {
+                text: Style::default(),
+                key: Style::default().add_modifier(Modifier::BOLD),
+                string: Style::default(),
+                number: Style::default(),
+                boolean: Style::default(),
+                null: Style::default(),
+                punctuation: Style::default(),
+                tag: Style::default().add_modifier(Modifier::BOLD),
+                attribute: Style::default(),
+                comment: Style::default().add_modifier(Modifier::DIM),
+            },
+        }
+    }
+}
+
+/// Line-by-line highlighter that carries state across lines, such as an open
+/// XML comment or a tag whose attributes span several lines
+pub struct Highlighter {
+    language: Language,
+    palette: Palette,
+    in_comment: bool,
+    in_tag: bool,
+}
+
+impl Highlighter {
+    pub fn new(language: Language, palette: Palette) -> Self {
+        Self {
+            language,
+            palette,
+            in_comment: false,
+            in_tag: false,
+        }
+    }
+
+    pub fn line(&mut self, line: &str) -> Vec<Span<'static>> {
+        match self.language {
+            Language::Json => json_line(line, &self.palette),
+            Language::Yaml => yaml_line(line, &self.palette),
+            Language::Xml | Language::Html => self.markup_line(line),
+            Language::Plain => vec![Span::styled(line.to_string(), self.palette.text)],
+        }
+    }
+
+    fn markup_line(&mut self, line: &str) -> Vec<Span<'static>> {
+        let palette = self.palette;
+        let mut spans = Vec::new();
+        let mut rest = line;
+
+        while !rest.is_empty() {
+            if self.in_comment {
+                let end = match rest.find("-->") {
+                    Some(i) => {
+                        self.in_comment = false;
+                        i + 3
+                    }
+                    None => rest.len(),
+                };
+                push(&mut spans, &rest[..end], palette.comment);
+                rest = &rest[end..];
+            } else if self.in_tag {
+                let c = rest.chars().next().unwrap_or_default();
+                let len = if rest.starts_with("/>") || rest.starts_with("?>") {
+                    self.in_tag = false;
+                    push(&mut spans, &rest[..2], palette.punctuation);
+                    2
+                } else if c == '>' {
+                    self.in_tag = false;
+                    push(&mut spans, ">", palette.punctuation);
+                    1
+                } else if c == '"' || c == '\'' {
+                    let len = rest[1..].find(c).map(|i| i + 2).unwrap_or(rest.len());
+                    push(&mut spans, &rest[..len], palette.string);
+                    len
+                } else if c == '=' {
+                    push(&mut spans, "=", palette.punctuation);
+                    1
+                } else if c.is_whitespace() {
+                    let len = rest
+                        .find(|c: char| !c.is_whitespace())
+                        .unwrap_or(rest.len());
+                    push(&mut spans, &rest[..len], palette.text);
+                    len
+                } else {
+                    let len = rest
+                        .find(|c: char| c.is_whitespace() || "=>/\"'".contains(c))
+                        .unwrap_or(rest.len())
+                        .max(c.len_utf8());
+                    push(&mut spans, &rest[..len], palette.attribute);
+                    len
+                };
+                rest = &rest[len..];
+            } else if rest.starts_with("<!--") {
+                self.in_comment = true;
+                push(&mut spans, "<!--", palette.comment);
+                rest = &rest[4..];
+            } else if rest.starts_with('<') {
+                let open = if rest[1..].starts_with(['/', '?', '!']) {
+                    2
+                } else {
+                    1
+                };
+                push(&mut spans, &rest[..open], palette.punctuation);
+                rest = &rest[open..];
+                let name = rest
+                    .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+                    .unwrap_or(rest.len());
+                push(&mut spans, &rest[..name], palette.tag);
+                rest = &rest[name..];
+                self.in_tag = true;
+            } else {
+                let len = rest.find('<').unwrap_or(rest.len());
+                push(&mut spans, &rest[..len], palette.text);
+                rest = &rest[len..];
+            }
+        }
+        spans
+    }
+}
+
+/// Highlighted lines of the last text shown, so text that has not changed is
+/// not detected and highlighted again on every frame
+#[derive(Debug, Default)]
+pub struct HighlightCache {
+    generation: Option<u64>,
+    content_type: Option<String>,
+    palette: Option<Palette>,
+    lines: Vec<Line<'static>>,
+}
+
+impl HighlightCache {
+    /// Lines of `text` highlighted in the language detected from
+    /// `content_type` and the text itself. `generation` identifies the text:
+    /// the lines are only worked out again when it changes.
+    pub fn lines(
+        &mut self,
+        generation: u64,
+        text: &str,
+        content_type: Option<&str>,
+        palette: Palette,
+    ) -> &[Line<'static>] {
+        let fresh = self.generation == Some(generation)
+            && self.palette == Some(palette)
+            && self.content_type.as_deref() == content_type;
+        if !fresh {
+            let language = Language::detect(content_type, text);
+            self.lines = highlight(text, language, palette);
+            self.generation = Some(generation);
+            self.content_type = content_type.map(str::to_string);
+            self.palette = Some(palette);
+        }
+        &self.lines
+    }
+}
+
+/// Highlight a whole text into owned lines
+pub fn highlight(text: &str, language: Language, palette: Palette) -> Vec<Line<'static>> {
+    let mut highlighter = Highlighter::new(language, palette);
+    text.lines()
+        .map(|line| Line::from(highlighter.line(line)))
+        .collect()
+}
+
+fn push(spans: &mut Vec<Span<'static>>, text: &str, style: Style) {
+    if !text.is_empty() {
+        spans.push(Span::styled(text.to_string(), style));
+    }
+}
+
+/// Byte length of a quoted string at the start of `text`, including quotes
+fn quoted_len(text: &str) -> usize {
+    let quote = text.chars().next().unwrap_or('"');
+    let mut escaped = false;
+    for (i, c) in text.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            c if c == quote => return i + 1,
+            _ => {}
+        }
+    }
+    text.len()
+}
+
+fn json_line(line: &str, palette: &Palette) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut rest = line;
+
+    while let Some(c) = rest.chars().next() {
+        let (len, style) = if c == '"' {
+            let len = quoted_len(rest);
+            let is_key = rest[len..].trim_start().starts_with(':');
+            (len, if is_key { palette.key } else { palette.string })
+        } else if c == '-' || c.is_ascii_digit() {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_digit() || "+-.eE".contains(c)))
+                .unwrap_or(rest.len());
+            (len, palette.number)
+        } else if c.is_ascii_alphabetic() {
+            let len = rest
+                .find(|c: char| !c.is_ascii_alphanumeric())
+                .unwrap_or(rest.len());
+            let style = match &rest[..len] {
+                "true" | "false" => palette.boolean,
+                "null" => palette.null,
+                _ => palette.text,
+            };
+            (len, style)
+        } else if "{}[],:".contains(c) {
+            (1, palette.punctuation)
+        } else {
+            let len = rest
+                .find(|c: char| !c.is_whitespace())
+                .unwrap_or(rest.len())
+                .max(c.len_utf8());
+            (len, palette.text)
+        };
+        push(&mut spans, &rest[..len], style);
+        rest = &rest[len..];
+    }
+    spans
+}
+
+fn yaml_line(line: &str, palette: &Palette) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let indent = line.len() - line.trim_start().len();
+    push(&mut spans, &line[..indent], palette.text);
+    let mut rest = &line[indent..];
+
+    if rest.starts_with('#') {
+        push(&mut spans, rest, palette.comment);
+        return spans;
+    }
+    if rest == "---" || rest == "..." {
+        push(&mut spans, rest, palette.punctuation);
+        return spans;
+    }
+    while rest.starts_with("- ") || rest == "-" {
+        let len = rest.len().min(2);
+        push(&mut spans, &rest[..len], palette.punctuation);
+        rest = &rest[len..];
+    }
+
+    let key_end = if rest.starts_with(['"', '\'']) {
+        let len = quoted_len(rest);
+        rest[len..].starts_with(':').then_some(len)
+    } else {
+        rest.find(": ")
+            .or_else(|| rest.strip_suffix(':').map(str::len))
+            .filter(|&end| !rest[..end].contains(" #"))
+    };
+    if let Some(end) = key_end {
+        push(&mut spans, &rest[..end], palette.key);
+        push(&mut spans, ":", palette.punctuation);
+        rest = &rest[end + 1..];
+    }
+
+    let (value, comment) = match rest.find(" #") {
+        Some(i) if !rest.trim_start().starts_with(['"', '\'']) => rest.split_at(i),
+        _ => (rest, ""),
+    };
+    let trimmed = value.trim();
+    let style = match trimmed {
+        "true" | "false" | "yes" | "no" | "on" | "off" | "True" | "False" => palette.boolean,
+        "null" | "~" | "Null" | "NULL" => palette.null,
+        _ if trimmed.parse::<f64>().is_ok() => palette.number,
+        _ if trimmed.starts_with(['{', '[', '|', '>', '&', '*']) => palette.text,
+        _ => palette.string,
+    };
+    push(&mut spans, value, style);
+    push(&mut spans, comment, palette.comment);
+    spans
+}
+
+#[cfg(test)]
+mod tests;
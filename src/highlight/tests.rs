@@ -0,0 +1,176 @@
+use super::*;
+
+fn tokens(line: &str, language: Language) -> Vec<(String, Style)> {
+    Highlighter::new(language, Theme::Dark.palette())
+        .line(line)
+        .into_iter()
+        .map(|span| (span.content.into_owned(), span.style))
+        .collect()
+}
+
+fn style_of(tokens: &[(String, Style)], text: &str) -> Style {
+    tokens
+        .iter()
+        .find(|(token, _)| token == text)
+        .unwrap_or_else(|| panic!("no token {:?} in {:?}", text, tokens))
+        .1
+}
+
+#[test]
+fn test_detect_from_content_type() {
+    assert_eq!(
+        Language::detect(Some("application/problem+json"), ""),
+        Language::Json
+    );
+    assert_eq!(
+        Language::detect(Some("text/html; charset=utf-8"), ""),
+        Language::Html
+    );
+    assert_eq!(Language::detect(Some("application/xml"), ""), Language::Xml);
+    assert_eq!(
+        Language::detect(Some("application/x-yaml"), ""),
+        Language::Yaml
+    );
+}
+
+#[test]
+fn test_detect_by_sniffing() {
+    assert_eq!(Language::detect(None, " {\"a\": 1}"), Language::Json);
+    assert_eq!(
+        Language::detect(Some("text/plain"), "[1, 2]"),
+        Language::Json
+    );
+    assert_eq!(
+        Language::detect(None, "<!DOCTYPE html><html>"),
+        Language::Html
+    );
+    assert_eq!(
+        Language::detect(None, "<?xml version=\"1.0\"?>"),
+        Language::Xml
+    );
+    assert_eq!(Language::detect(None, "{not json"), Language::Plain);
+    assert_eq!(Language::detect(None, "plain text"), Language::Plain);
+}
+
+#[test]
+fn test_json_tokens() {
+    let palette = Theme::Dark.palette();
+    let tokens = tokens(
+        r#"  "name": "snorlax", "id": -14.5e2, "ok": true, "x": null,"#,
+        Language::Json,
+    );
+
+    assert_eq!(style_of(&tokens, "\"name\""), palette.key);
+    assert_eq!(style_of(&tokens, "\"snorlax\""), palette.string);
+    assert_eq!(style_of(&tokens, "-14.5e2"), palette.number);
+    assert_eq!(style_of(&tokens, "true"), palette.boolean);
+    assert_eq!(style_of(&tokens, "null"), palette.null);
+    assert_eq!(style_of(&tokens, ":"), palette.punctuation);
+}
+
+#[test]
+fn test_json_string_with_escaped_quote() {
+    let palette = Theme::Dark.palette();
+    let tokens = tokens(r#""say \"hi\"": 1"#, Language::Json);
+
+    assert_eq!(style_of(&tokens, r#""say \"hi\"""#), palette.key);
+}
+
+#[test]
+fn test_highlight_keeps_text() {
+    let text = "{\n  \"ünï\": [1, \"→\"]\n}";
+
+    for language in [
+        Language::Json,
+        Language::Xml,
+        Language::Yaml,
+        Language::Plain,
+    ] {
+        let lines = highlight(text, language, Theme::Dark.palette());
+        let joined: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+        assert_eq!(joined.join("\n"), text);
+    }
+}
+
+#[test]
+fn test_xml_tokens() {
+    let palette = Theme::Dark.palette();
+    let tokens = tokens(r#"<item id="7" enabled>text</item>"#, Language::Xml);
+
+    assert_eq!(style_of(&tokens, "item"), palette.tag);
+    assert_eq!(style_of(&tokens, "id"), palette.attribute);
+    assert_eq!(style_of(&tokens, "\"7\""), palette.string);
+    assert_eq!(style_of(&tokens, "enabled"), palette.attribute);
+    assert_eq!(style_of(&tokens, "text"), palette.text);
+    assert_eq!(style_of(&tokens, "</"), palette.punctuation);
+}
+
+#[test]
+fn test_xml_state_spans_lines() {
+    let palette = Theme::Dark.palette();
+    let lines = highlight(
+        "<!-- a\nb -->\n<img\n  src=\"x.png\"/>",
+        Language::Html,
+        palette,
+    );
+
+    assert_eq!(lines[1].spans[0].style, palette.comment);
+    assert_eq!(lines[3].spans[1].content, "src");
+    assert_eq!(lines[3].spans[1].style, palette.attribute);
+}
+
+#[test]
+fn test_yaml_tokens() {
+    let palette = Theme::Dark.palette();
+
+    let tokens_a = tokens("  - name: snorlax # sleepy", Language::Yaml);
+    assert_eq!(style_of(&tokens_a, "- "), palette.punctuation);
+    assert_eq!(style_of(&tokens_a, "name"), palette.key);
+    assert_eq!(style_of(&tokens_a, " snorlax"), palette.string);
+    assert_eq!(style_of(&tokens_a, " # sleepy"), palette.comment);
+
+    let tokens_b = tokens("weight: 4600", Language::Yaml);
+    assert_eq!(style_of(&tokens_b, " 4600"), palette.number);
+
+    let tokens_c = tokens("legendary: false", Language::Yaml);
+    assert_eq!(style_of(&tokens_c, " false"), palette.boolean);
+
+    let tokens_d = tokens("# comment", Language::Yaml);
+    assert_eq!(style_of(&tokens_d, "# comment"), palette.comment);
+}
+
+#[test]
+fn test_theme_cycles() {
+    assert_eq!(Theme::Dark.next(), Theme::Light);
+    assert_eq!(Theme::Mono.next(), Theme::Dark);
+}
+
+#[test]
+fn test_highlight_cache_rehighlights_only_on_change() {
+    let mut cache = HighlightCache::default();
+    let dark = Theme::Dark.palette();
+
+    let lines = cache
+        .lines(0, "{\"a\": 1}", Some("application/json"), dark)
+        .to_vec();
+    assert_eq!(lines, highlight("{\"a\": 1}", Language::Json, dark));
+    // The same generation is the same text, whatever is passed
+    assert_eq!(
+        cache.lines(0, "ignored", Some("application/json"), dark),
+        lines
+    );
+
+    let light = Theme::Light.palette();
+    assert_eq!(
+        cache.lines(0, "{\"a\": 1}", Some("application/json"), light),
+        highlight("{\"a\": 1}", Language::Json, light)
+    );
+    assert_eq!(
+        cache.lines(1, "a: 1", Some("application/yaml"), light),
+        highlight("a: 1", Language::Yaml, light)
+    );
+    assert_eq!(
+        cache.lines(2, "b: 2", Some("application/yaml"), light),
+        highlight("b: 2", Language::Yaml, light)
+    );
+}
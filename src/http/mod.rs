@@ -0,0 +1,729 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::{ErrorKind, Read};
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use hyper_util::client::legacy::connect::{Connection, HttpInfo};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::tls::TlsInfo;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::download;
+use crate::proxy::{self, ProxyMode, ProxySettings};
+use crate::tls::{self, CertificateInfo, MinTlsVersion, TlsSettings};
+
+/// Largest chunk passed on at a time when streaming a response body
+const STREAM_CHUNK_SIZE: usize = 8192;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientSettings {
+    /// Total request timeout in seconds, 0 disables it
+    pub timeout_secs: u64,
+    /// Keep idle connections in the pool for reuse
+    pub keep_alive: bool,
+    /// How long an idle pooled connection is kept, in seconds
+    pub pool_idle_secs: u64,
+    /// TCP keep-alive probe interval in seconds, 0 disables it
+    pub tcp_keepalive_secs: u64,
+    pub tls: TlsSettings,
+    pub proxy: ProxySettings,
+    /// Comma-separated `.proto` files describing gRPC services
+    pub proto_files: String,
+}
+
+impl Default for ClientSettings {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 30,
+            keep_alive: true,
+            pool_idle_secs: 90,
+            tcp_keepalive_secs: 60,
+            tls: TlsSettings::default(),
+            proxy: ProxySettings::default(),
+            proto_files: String::new(),
+        }
+    }
+}
+
+impl ClientSettings {
+    /// Paths listed in `proto_files`
+    pub fn proto_paths(&self) -> Vec<PathBuf> {
+        self.proto_files
+            .split(',')
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SettingsField {
+    Timeout,
+    KeepAlive,
+    PoolIdle,
+    TcpKeepalive,
+    CaBundle,
+    ClientCert,
+    ClientKey,
+    ClientCertPassword,
+    MinTlsVersion,
+    Insecure,
+    ProxyMode,
+    HttpProxy,
+    HttpsProxy,
+    ProxyUser,
+    ProxyPassword,
+    NoProxy,
+    ProtoFiles,
+}
+
+impl SettingsField {
+    pub const ALL: &'static [SettingsField] = &[
+        SettingsField::Timeout,
+        SettingsField::KeepAlive,
+        SettingsField::PoolIdle,
+        SettingsField::TcpKeepalive,
+        SettingsField::CaBundle,
+        SettingsField::ClientCert,
+        SettingsField::ClientKey,
+        SettingsField::ClientCertPassword,
+        SettingsField::MinTlsVersion,
+        SettingsField::Insecure,
+        SettingsField::ProxyMode,
+        SettingsField::HttpProxy,
+        SettingsField::HttpsProxy,
+        SettingsField::ProxyUser,
+        SettingsField::ProxyPassword,
+        SettingsField::NoProxy,
+        SettingsField::ProtoFiles,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SettingsField::Timeout => "Timeout (s, 0 = none)",
+            SettingsField::KeepAlive => "Keep-alive",
+            SettingsField::PoolIdle => "Pool idle timeout (s)",
+            SettingsField::TcpKeepalive => "TCP keep-alive (s, 0 = off)",
+            SettingsField::CaBundle => "CA bundle (PEM path)",
+            SettingsField::ClientCert => "Client cert (PEM/.p12 path)",
+            SettingsField::ClientKey => "Client key (PKCS#8 PEM path)",
+            SettingsField::ClientCertPassword => "Client cert password",
+            SettingsField::MinTlsVersion => "Min TLS version",
+            SettingsField::Insecure => "⚠ Skip TLS verification",
+            SettingsField::ProxyMode => "Proxy (system/manual/off)",
+            SettingsField::HttpProxy => "HTTP proxy URL",
+            SettingsField::HttpsProxy => "HTTPS proxy URL",
+            SettingsField::ProxyUser => "Proxy username",
+            SettingsField::ProxyPassword => "Proxy password",
+            SettingsField::NoProxy => "No proxy for (comma list)",
+            SettingsField::ProtoFiles => "gRPC .proto files (comma list)",
+        }
+    }
+
+    /// Toggle fields flip (or cycle) on Enter instead of opening a text edit
+    pub fn is_toggle(&self) -> bool {
+        matches!(
+            self,
+            SettingsField::KeepAlive
+                | SettingsField::MinTlsVersion
+                | SettingsField::Insecure
+                | SettingsField::ProxyMode
+        )
+    }
+
+    /// Secret fields are masked when displayed
+    pub fn is_secret(&self) -> bool {
+        matches!(
+            self,
+            SettingsField::ClientCertPassword | SettingsField::ProxyPassword
+        )
+    }
+
+    pub fn value(&self, settings: &ClientSettings) -> String {
+        match self {
+            SettingsField::Timeout => settings.timeout_secs.to_string(),
+            SettingsField::KeepAlive => on_off(settings.keep_alive).to_string(),
+            SettingsField::PoolIdle => settings.pool_idle_secs.to_string(),
+            SettingsField::TcpKeepalive => settings.tcp_keepalive_secs.to_string(),
+            SettingsField::CaBundle => settings.tls.ca_bundle.clone(),
+            SettingsField::ClientCert => settings.tls.client_cert.clone(),
+            SettingsField::ClientKey => settings.tls.client_key.clone(),
+            SettingsField::ClientCertPassword => settings.tls.client_cert_password.clone(),
+            SettingsField::MinTlsVersion => settings.tls.min_version.label().to_string(),
+            SettingsField::Insecure => on_off(settings.tls.insecure).to_string(),
+            SettingsField::ProxyMode => settings.proxy.mode.label().to_string(),
+            SettingsField::HttpProxy => settings.proxy.http.clone(),
+            SettingsField::HttpsProxy => settings.proxy.https.clone(),
+            SettingsField::ProxyUser => settings.proxy.username.clone(),
+            SettingsField::ProxyPassword => settings.proxy.password.clone(),
+            SettingsField::NoProxy => settings.proxy.no_proxy.clone(),
+            SettingsField::ProtoFiles => settings.proto_files.clone(),
+        }
+    }
+
+    /// Value as shown in the settings list, with secrets masked
+    pub fn display_value(&self, settings: &ClientSettings) -> String {
+        let value = self.value(settings);
+        if self.is_secret() {
+            "*".repeat(value.chars().count())
+        } else {
+            value
+        }
+    }
+
+    pub fn toggle(&self, settings: &mut ClientSettings) {
+        match self {
+            SettingsField::KeepAlive => settings.keep_alive = !settings.keep_alive,
+            SettingsField::MinTlsVersion => {
+                settings.tls.min_version = settings.tls.min_version.next()
+            }
+            SettingsField::Insecure => settings.tls.insecure = !settings.tls.insecure,
+            SettingsField::ProxyMode => settings.proxy.mode = settings.proxy.mode.next(),
+            _ => {}
+        }
+    }
+
+    pub fn set(&self, settings: &mut ClientSettings, value: &str) -> Result<(), String> {
+        let value = value.trim();
+        match self {
+            SettingsField::Timeout => settings.timeout_secs = parse_secs(value)?,
+            SettingsField::KeepAlive => settings.keep_alive = parse_bool(value)?,
+            SettingsField::PoolIdle => settings.pool_idle_secs = parse_secs(value)?,
+            SettingsField::TcpKeepalive => settings.tcp_keepalive_secs = parse_secs(value)?,
+            SettingsField::CaBundle => settings.tls.ca_bundle = value.to_string(),
+            SettingsField::ClientCert => settings.tls.client_cert = value.to_string(),
+            SettingsField::ClientKey => settings.tls.client_key = value.to_string(),
+            SettingsField::ClientCertPassword => {
+                settings.tls.client_cert_password = value.to_string()
+            }
+            SettingsField::MinTlsVersion => settings.tls.min_version = MinTlsVersion::parse(value)?,
+            SettingsField::Insecure => settings.tls.insecure = parse_bool(value)?,
+            SettingsField::ProxyMode => settings.proxy.mode = ProxyMode::parse(value)?,
+            SettingsField::HttpProxy => settings.proxy.http = value.to_string(),
+            SettingsField::HttpsProxy => settings.proxy.https = value.to_string(),
+            SettingsField::ProxyUser => settings.proxy.username = value.to_string(),
+            SettingsField::ProxyPassword => settings.proxy.password = value.to_string(),
+            SettingsField::NoProxy => settings.proxy.no_proxy = value.to_string(),
+            SettingsField::ProtoFiles => settings.proto_files = value.to_string(),
+        }
+        Ok(())
+    }
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+fn parse_secs(value: &str) -> Result<u64, String> {
+    value
+        .parse::<u64>()
+        .map_err(|_| format!("Expected a number of seconds, got '{}'", value))
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "on" | "true" | "yes" | "1" => Ok(true),
+        "off" | "false" | "no" | "0" => Ok(false),
+        _ => Err(format!("Expected on/off, got '{}'", value)),
+    }
+}
+
+/// Per-phase timings of a single request, similar to curl's `-w` variables.
+///
+/// `dns` and `connect` are only set when the request had to open a new
+/// connection; a reused pooled connection skips both.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RequestTimings {
+    pub dns: Option<Duration>,
+    /// From the end of the lookup until the connection was ready: the TCP
+    /// connect, any proxy tunnel and, for https, the TLS handshake. The
+    /// connector runs them all, so they cannot be told apart.
+    pub connect: Option<Duration>,
+    /// Time from sending the request until the response headers arrived
+    pub ttfb: Duration,
+    pub download: Duration,
+}
+
+impl RequestTimings {
+    pub fn total(&self) -> Duration {
+        self.dns.unwrap_or_default() + self.connect.unwrap_or_default() + self.ttfb + self.download
+    }
+
+    /// Phases in waterfall order, skipping the ones that did not happen
+    pub fn phases(&self) -> Vec<(&'static str, Duration)> {
+        let mut phases = Vec::new();
+        if let Some(dns) = self.dns {
+            phases.push(("DNS", dns));
+        }
+        if let Some(connect) = self.connect {
+            phases.push(("Connect", connect));
+        }
+        phases.push(("TTFB", self.ttfb));
+        phases.push(("Download", self.download));
+        phases
+    }
+}
+
+/// How long opening one connection took
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ConnectionSetup {
+    dns: Option<Duration>,
+    connect: Duration,
+    /// When the connection was ready
+    finished: Instant,
+}
+
+/// Connections the pool opened, shared between the client and its hooks.
+///
+/// Each new connection is recorded under its local address. The request
+/// whose response came over that connection claims the entry, so requests
+/// running at the same time on other threads never see each other's times.
+#[derive(Default)]
+struct ConnectionTracker {
+    opened: AtomicUsize,
+    /// Connections no response has claimed yet, by local address
+    unclaimed: Mutex<HashMap<SocketAddr, ConnectionSetup>>,
+}
+
+impl ConnectionTracker {
+    /// Setup of the connection at `local_addr` when it was opened for a
+    /// request sent at `sent`; `None` when the request reused a connection
+    fn claim(&self, local_addr: SocketAddr, sent: Instant) -> Option<ConnectionSetup> {
+        let setup = self.unclaimed.lock().unwrap().remove(&local_addr)?;
+        // Opened for an earlier request that found another connection first
+        (setup.finished >= sent).then_some(setup)
+    }
+}
+
+/// When the name lookup of a connection being opened started and ended
+#[derive(Debug, Default)]
+struct Lookup {
+    started: Option<Instant>,
+    ended: Option<Instant>,
+}
+
+impl Lookup {
+    fn duration(&self) -> Option<Duration> {
+        Some(self.ended? - self.started?)
+    }
+}
+
+tokio::task_local! {
+    /// Lookup of the connection the connector is opening, filled in by the
+    /// resolver
+    static LOOKUP: Arc<Mutex<Lookup>>;
+}
+
+/// Long-lived HTTP client that keeps its connection pool between requests.
+///
+/// The underlying `reqwest` client is only rebuilt when the settings change.
+#[derive(Clone)]
+pub struct HttpClient {
+    client: reqwest::blocking::Client,
+    settings: ClientSettings,
+    tracker: Arc<ConnectionTracker>,
+}
+
+impl HttpClient {
+    pub fn new(settings: ClientSettings) -> anyhow::Result<Self> {
+        let tracker = Arc::new(ConnectionTracker::default());
+        let client = build_client(&settings, &tracker)?;
+        Ok(Self {
+            client,
+            settings,
+            tracker,
+        })
+    }
+
+    pub fn client(&self) -> &reqwest::blocking::Client {
+        &self.client
+    }
+
+    pub fn settings(&self) -> &ClientSettings {
+        &self.settings
+    }
+
+    /// Number of connections opened by this client so far
+    #[cfg(test)]
+    pub fn connections_opened(&self) -> usize {
+        self.tracker.opened.load(Ordering::SeqCst)
+    }
+
+    /// Send a request and read its full body, timing each phase.
+    ///
+    /// Download progress is reported to `progress`. With a `save_dir` the body
+    /// is streamed to a new file there instead of being kept in memory.
+    pub fn execute(
+        &self,
+        request: reqwest::blocking::RequestBuilder,
+        progress: &Arc<TransferProgress>,
+        sink: BodySink<'_>,
+    ) -> reqwest::Result<TimedResponse> {
+        let start = Instant::now();
+        let response = request.send()?;
+        let setup = response
+            .extensions()
+            .get::<HttpInfo>()
+            .and_then(|info| self.tracker.claim(info.local_addr(), start));
+        let headers_at = start.elapsed();
+        let url = response.url().to_string();
+        let certificate = response
+            .extensions()
+            .get::<TlsInfo>()
+            .and_then(|info| info.peer_certificate())
+            .and_then(|der| CertificateInfo::from_der(der).ok());
+        let status = response.status().as_u16();
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let content_type = header(reqwest::header::CONTENT_TYPE);
+        let filename = download::filename(
+            response.url().as_str(),
+            header(reqwest::header::CONTENT_DISPOSITION).as_deref(),
+        );
+
+        progress.start(response.content_length().unwrap_or_default());
+        let mut reader = ProgressReader::new(response, Arc::clone(progress));
+        let body = match sink {
+            BodySink::Memory => {
+                let mut bytes = Vec::new();
+                reader
+                    .read_to_end(&mut bytes)
+                    .map(|_| ResponseBody::Bytes(bytes))
+            }
+            BodySink::Download(dir) => save_body(&mut reader, dir, &filename),
+            BodySink::Stream(updates, stop) => {
+                let _ = updates.send(StreamUpdate::Started {
+                    status,
+                    content_type: content_type.clone(),
+                });
+                stream_body(&mut reader, updates, stop)
+            }
+        };
+        let download = start.elapsed() - headers_at;
+
+        let dns = setup.and_then(|setup| setup.dns);
+        let connect = setup.map(|setup| setup.connect);
+        let setup_time = dns.unwrap_or_default() + connect.unwrap_or_default();
+
+        Ok(TimedResponse {
+            status,
+            content_type,
+            filename,
+            body,
+            connection_reused: setup.is_none(),
+            url,
+            certificate,
+            timings: RequestTimings {
+                dns,
+                connect,
+                ttfb: headers_at.saturating_sub(setup_time),
+                download,
+            },
+        })
+    }
+}
+
+/// Certificate chain the server at `url` presents, leaf first, from a new
+/// connection through the same proxy and with the same TLS settings as
+/// requests. Slow, so run it off the UI thread.
+pub fn certificate_chain(
+    settings: &ClientSettings,
+    url: &str,
+) -> anyhow::Result<Vec<CertificateInfo>> {
+    let parsed = reqwest::Url::parse(url)?;
+    let host = parsed
+        .host_str()
+        .ok_or_else(|| anyhow::anyhow!("Missing host in {}", url))?
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+    let port = parsed.port_or_known_default().unwrap_or(443);
+    let stream = match settings.proxy.proxy_for(url) {
+        Some(through) => {
+            let (proxy_host, proxy_port) = proxy::http_proxy_address(&through)?;
+            let mut stream = tls::connect(&proxy_host, proxy_port)?;
+            stream.set_read_timeout(Some(tls::CHAIN_TIMEOUT))?;
+            proxy::tunnel(&mut stream, &through, &settings.proxy, host, port)?;
+            stream
+        }
+        None => tls::connect(host, port)?,
+    };
+    tls::fetch_chain(stream, host, &settings.tls)
+}
+
+/// Stream a response body to a new file in `dir`, removing the partial file
+/// on failure
+fn save_body(reader: &mut impl Read, dir: &Path, name: &str) -> std::io::Result<ResponseBody> {
+    let (path, mut file) = download::create_unique(dir, name)?;
+    match std::io::copy(reader, &mut file) {
+        Ok(size) => Ok(ResponseBody::Saved { path, size }),
+        Err(e) => {
+            let _ = std::fs::remove_file(&path);
+            Err(e)
+        }
+    }
+}
+
+/// Read chunks as they arrive and pass each one on, stopping early once
+/// `stop` is set or the receiver is gone. The caller then drops the response,
+/// which closes the connection instead of returning it to the pool.
+fn stream_body(
+    reader: &mut impl Read,
+    updates: &Sender<StreamUpdate>,
+    stop: &AtomicBool,
+) -> std::io::Result<ResponseBody> {
+    let mut bytes = Vec::new();
+    let mut chunk = [0; STREAM_CHUNK_SIZE];
+    loop {
+        if stop.load(Ordering::SeqCst) {
+            return Ok(ResponseBody::Bytes(bytes));
+        }
+        let read = match reader.read(&mut chunk) {
+            Ok(0) => return Ok(ResponseBody::Bytes(bytes)),
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        bytes.extend_from_slice(&chunk[..read]);
+        if updates
+            .send(StreamUpdate::Chunk(chunk[..read].to_vec()))
+            .is_err()
+        {
+            return Ok(ResponseBody::Bytes(bytes));
+        }
+    }
+}
+
+/// Where `HttpClient::execute` puts the response body
+#[derive(Debug, Clone, Copy)]
+pub enum BodySink<'a> {
+    /// Read the whole body into memory
+    Memory,
+    /// Write the body straight to a new file in this directory
+    Download(&'a Path),
+    /// Read the whole body into memory, passing each chunk on as it arrives,
+    /// until the flag is set
+    Stream(&'a Sender<StreamUpdate>, &'a AtomicBool),
+}
+
+/// Progress of a streamed response, sent while the body is being read
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamUpdate {
+    Started {
+        status: u16,
+        content_type: Option<String>,
+    },
+    Chunk(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResponseBody {
+    Bytes(Vec<u8>),
+    /// Body written straight to disk in download mode
+    Saved {
+        path: PathBuf,
+        size: u64,
+    },
+}
+
+pub struct TimedResponse {
+    pub status: u16,
+    pub content_type: Option<String>,
+    /// Suggested file name from Content-Disposition or the URL
+    pub filename: String,
+    pub body: std::io::Result<ResponseBody>,
+    pub connection_reused: bool,
+    /// URL the response came from, after redirects
+    pub url: String,
+    /// Leaf certificate of the connection, for https requests
+    pub certificate: Option<CertificateInfo>,
+    pub timings: RequestTimings,
+}
+
+/// Byte counters for a transfer that is still in flight
+#[derive(Debug, Default)]
+pub struct TransferProgress {
+    done: AtomicU64,
+    total: AtomicU64,
+}
+
+impl TransferProgress {
+    pub fn start(&self, total: u64) {
+        self.done.store(0, Ordering::SeqCst);
+        self.total.store(total, Ordering::SeqCst);
+    }
+
+    pub fn reset(&self) {
+        self.start(0);
+    }
+
+    /// Bytes transferred and the expected total, `None` when no transfer is tracked
+    pub fn get(&self) -> Option<(u64, u64)> {
+        let total = self.total.load(Ordering::SeqCst);
+        (total > 0).then(|| (self.done.load(Ordering::SeqCst), total))
+    }
+}
+
+/// Reader that counts the bytes read through it into a [`TransferProgress`]
+pub struct ProgressReader<R> {
+    inner: R,
+    progress: Arc<TransferProgress>,
+}
+
+impl<R> ProgressReader<R> {
+    pub fn new(inner: R, progress: Arc<TransferProgress>) -> Self {
+        Self { inner, progress }
+    }
+}
+
+impl<R: Read> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.progress.done.fetch_add(read as u64, Ordering::SeqCst);
+        Ok(read)
+    }
+}
+
+fn build_client(
+    settings: &ClientSettings,
+    tracker: &Arc<ConnectionTracker>,
+) -> anyhow::Result<reqwest::blocking::Client> {
+    let mut builder = reqwest::blocking::Client::builder()
+        .pool_idle_timeout(Duration::from_secs(settings.pool_idle_secs))
+        .dns_resolver(Arc::new(TimingResolver))
+        .connector_layer(ConnectionTrackerLayer {
+            tracker: Arc::clone(tracker),
+        });
+
+    builder = if settings.timeout_secs > 0 {
+        builder.timeout(Duration::from_secs(settings.timeout_secs))
+    } else {
+        builder.timeout(None)
+    };
+
+    if !settings.keep_alive {
+        builder = builder.pool_max_idle_per_host(0);
+    }
+
+    builder = if settings.tcp_keepalive_secs > 0 {
+        builder.tcp_keepalive(Duration::from_secs(settings.tcp_keepalive_secs))
+    } else {
+        builder.tcp_keepalive(None)
+    };
+
+    builder = tls::configure(builder, &settings.tls)?;
+    builder = proxy::configure(builder, &settings.proxy)?;
+
+    Ok(builder.build()?)
+}
+
+/// System resolver that records how long the lookup for the connection
+/// being opened took
+struct TimingResolver;
+
+impl Resolve for TimingResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        let lookup = LOOKUP.try_with(Arc::clone).ok();
+        Box::pin(async move {
+            if let Some(lookup) = &lookup {
+                lookup.lock().unwrap().started = Some(Instant::now());
+            }
+            let addrs =
+                tokio::task::spawn_blocking(move || (host.as_str(), 0).to_socket_addrs()).await??;
+            if let Some(lookup) = &lookup {
+                lookup.lock().unwrap().ended = Some(Instant::now());
+            }
+            Ok(Box::new(addrs) as Addrs)
+        })
+    }
+}
+
+/// Connector layer that counts and times every new connection the pool opens
+#[derive(Clone)]
+struct ConnectionTrackerLayer {
+    tracker: Arc<ConnectionTracker>,
+}
+
+impl<S> Layer<S> for ConnectionTrackerLayer {
+    type Service = ConnectionTrackerService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConnectionTrackerService {
+            inner,
+            tracker: Arc::clone(&self.tracker),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct ConnectionTrackerService<S> {
+    inner: S,
+    tracker: Arc<ConnectionTracker>,
+}
+
+impl<S, R> Service<R> for ConnectionTrackerService<S>
+where
+    S: Service<R>,
+    S::Response: Connection,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        self.tracker.opened.fetch_add(1, Ordering::SeqCst);
+        let tracker = Arc::clone(&self.tracker);
+        let lookup = Arc::new(Mutex::new(Lookup::default()));
+        let connecting = LOOKUP.scope(Arc::clone(&lookup), self.inner.call(request));
+        let started = Instant::now();
+        Box::pin(async move {
+            let connection = connecting.await?;
+            let finished = Instant::now();
+            let (dns, looked_up) = {
+                let lookup = lookup.lock().unwrap();
+                (lookup.duration(), lookup.ended)
+            };
+            let mut extras = http::Extensions::new();
+            connection.connected().get_extras(&mut extras);
+            if let Some(info) = extras.get::<HttpInfo>() {
+                tracker.unclaimed.lock().unwrap().insert(
+                    info.local_addr(),
+                    ConnectionSetup {
+                        dns,
+                        connect: finished - looked_up.unwrap_or(started),
+                        finished,
+                    },
+                );
+            }
+            Ok(connection)
+        })
+    }
+}
+
+#[cfg(test)]
+pub mod test_server;
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,116 @@
+//! Minimal keep-alive HTTP server for exercising the client in tests.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+/// Serve `body` with the given content type to every request on a local port.
+///
+/// Connections are kept alive, so sequential requests can reuse them. Returns
+/// the base URL, e.g. `http://127.0.0.1:12345`.
+pub fn serve(content_type: &'static str, body: &'static [u8]) -> String {
+    serve_raw(move |_request| {
+        let mut response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\n\r\n",
+            content_type,
+            body.len()
+        )
+        .into_bytes();
+        response.extend_from_slice(body);
+        response
+    })
+}
+
+/// Serve whatever `respond` returns for each raw request (head and body).
+pub fn serve_raw<F>(respond: F) -> String
+where
+    F: Fn(&[u8]) -> Vec<u8> + Send + Sync + Clone + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { break };
+            let respond = respond.clone();
+            thread::spawn(move || {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    let mut request = Vec::new();
+                    let mut content_length = 0;
+                    loop {
+                        let mut line = String::new();
+                        match reader.read_line(&mut line) {
+                            Ok(0) | Err(_) => return,
+                            Ok(_) => {}
+                        }
+                        if let Some((name, value)) = line.split_once(':') {
+                            if name.eq_ignore_ascii_case("content-length") {
+                                content_length = value.trim().parse().unwrap_or(0);
+                            }
+                        }
+                        request.extend_from_slice(line.as_bytes());
+                        if line == "\r\n" {
+                            break;
+                        }
+                    }
+                    let mut body = vec![0; content_length];
+                    if reader.read_exact(&mut body).is_err() {
+                        return;
+                    }
+                    request.extend_from_slice(&body);
+                    if stream.write_all(&respond(&request)).is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+    });
+
+    format!("http://{}", addr)
+}
+
+/// Like [`serve`], but over TLS with the self-signed certificate in
+/// `src/tls/testdata`. Returns an `https://localhost:<port>` base URL.
+pub fn serve_tls(body: &'static [u8]) -> String {
+    serve_tls_with(include_bytes!("../tls/testdata/identity.p12"), body)
+}
+
+/// Like `serve_tls`, presenting the certificates in a PKCS#12 file with the
+/// password `secret`
+pub fn serve_tls_with(pkcs12: &[u8], body: &'static [u8]) -> String {
+    let identity = native_tls::Identity::from_pkcs12(pkcs12, "secret").unwrap();
+    let acceptor = native_tls::TlsAcceptor::new(identity).unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else { break };
+            let acceptor = acceptor.clone();
+            thread::spawn(move || {
+                let Ok(mut stream) = acceptor.accept(stream) else {
+                    return;
+                };
+                let mut head = Vec::new();
+                let mut byte = [0u8; 1];
+                while !head.ends_with(b"\r\n\r\n") {
+                    if stream.read(&mut byte).unwrap_or(0) == 0 {
+                        return;
+                    }
+                    head.push(byte[0]);
+                }
+                let mut response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                )
+                .into_bytes();
+                response.extend_from_slice(body);
+                let _ = stream.write_all(&response);
+                let _ = stream.shutdown();
+            });
+        }
+    });
+
+    format!("https://localhost:{}", addr.port())
+}
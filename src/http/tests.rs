@@ -0,0 +1,589 @@
+use super::test_server::serve;
+use super::*;
+
+fn body_text(body: std::io::Result<ResponseBody>) -> String {
+    match body.unwrap() {
+        ResponseBody::Bytes(bytes) => String::from_utf8(bytes).unwrap(),
+        saved => panic!("expected an in-memory body, got {:?}", saved),
+    }
+}
+
+#[test]
+fn test_default_settings() {
+    let settings = ClientSettings::default();
+
+    assert_eq!(settings.timeout_secs, 30);
+    assert!(settings.keep_alive);
+    assert_eq!(settings.pool_idle_secs, 90);
+    assert_eq!(settings.tcp_keepalive_secs, 60);
+}
+
+#[test]
+fn test_http_client_builds_with_defaults() {
+    let http = HttpClient::new(ClientSettings::default()).unwrap();
+
+    assert_eq!(http.settings(), &ClientSettings::default());
+    assert_eq!(http.connections_opened(), 0);
+}
+
+#[test]
+fn test_http_client_builds_with_everything_disabled() {
+    let settings = ClientSettings {
+        timeout_secs: 0,
+        keep_alive: false,
+        pool_idle_secs: 0,
+        tcp_keepalive_secs: 0,
+        tls: TlsSettings::default(),
+        proxy: ProxySettings::default(),
+        proto_files: String::new(),
+    };
+
+    assert!(HttpClient::new(settings).is_ok());
+}
+
+#[test]
+fn test_clones_share_connection_counter() {
+    let http = HttpClient::new(ClientSettings::default()).unwrap();
+    let clone = http.clone();
+
+    http.tracker.opened.fetch_add(1, Ordering::SeqCst);
+
+    assert_eq!(clone.connections_opened(), 1);
+}
+
+#[test]
+fn test_failed_connection_is_counted() {
+    let http = HttpClient::new(ClientSettings::default()).unwrap();
+
+    // Nothing listens on port 9 of localhost, but the pool still has to dial
+    let _ = http.client().get("http://127.0.0.1:9/").send();
+
+    assert_eq!(http.connections_opened(), 1);
+}
+
+#[test]
+fn test_settings_field_values() {
+    let settings = ClientSettings::default();
+
+    assert_eq!(SettingsField::Timeout.value(&settings), "30");
+    assert_eq!(SettingsField::KeepAlive.value(&settings), "on");
+    assert_eq!(SettingsField::PoolIdle.value(&settings), "90");
+    assert_eq!(SettingsField::TcpKeepalive.value(&settings), "60");
+}
+
+#[test]
+fn test_settings_field_set_number() {
+    let mut settings = ClientSettings::default();
+
+    SettingsField::Timeout.set(&mut settings, " 5 ").unwrap();
+
+    assert_eq!(settings.timeout_secs, 5);
+}
+
+#[test]
+fn test_settings_field_set_invalid_number() {
+    let mut settings = ClientSettings::default();
+
+    let result = SettingsField::PoolIdle.set(&mut settings, "soon");
+
+    assert!(result.is_err());
+    assert_eq!(settings.pool_idle_secs, 90);
+}
+
+#[test]
+fn test_settings_field_toggle() {
+    let mut settings = ClientSettings::default();
+
+    SettingsField::KeepAlive.toggle(&mut settings);
+    assert!(!settings.keep_alive);
+
+    SettingsField::KeepAlive.set(&mut settings, "on").unwrap();
+    assert!(settings.keep_alive);
+}
+
+#[test]
+fn test_proto_files_split_on_commas() {
+    let mut settings = ClientSettings::default();
+    assert!(settings.proto_paths().is_empty());
+
+    SettingsField::ProtoFiles
+        .set(&mut settings, "api/a.proto, b.proto,")
+        .unwrap();
+
+    assert_eq!(
+        settings.proto_paths(),
+        vec![PathBuf::from("api/a.proto"), PathBuf::from("b.proto")]
+    );
+}
+
+#[test]
+fn test_toggle_fields() {
+    let toggles: Vec<_> = SettingsField::ALL
+        .iter()
+        .filter(|field| field.is_toggle())
+        .collect();
+
+    assert_eq!(
+        toggles,
+        vec![
+            &SettingsField::KeepAlive,
+            &SettingsField::MinTlsVersion,
+            &SettingsField::Insecure,
+            &SettingsField::ProxyMode
+        ]
+    );
+}
+
+#[test]
+fn test_timings_total_sums_phases() {
+    let timings = RequestTimings {
+        dns: Some(Duration::from_millis(5)),
+        connect: Some(Duration::from_millis(10)),
+        ttfb: Duration::from_millis(20),
+        download: Duration::from_millis(15),
+    };
+
+    assert_eq!(timings.total(), Duration::from_millis(50));
+}
+
+#[test]
+fn test_timings_phases_in_order() {
+    let timings = RequestTimings {
+        dns: Some(Duration::from_millis(5)),
+        connect: Some(Duration::from_millis(10)),
+        ttfb: Duration::from_millis(20),
+        download: Duration::from_millis(15),
+    };
+
+    let labels: Vec<_> = timings.phases().iter().map(|(label, _)| *label).collect();
+
+    assert_eq!(labels, vec!["DNS", "Connect", "TTFB", "Download"]);
+}
+
+#[test]
+fn test_timings_phases_skip_connection_setup_when_reused() {
+    let timings = RequestTimings {
+        ttfb: Duration::from_millis(20),
+        download: Duration::from_millis(15),
+        ..RequestTimings::default()
+    };
+
+    let labels: Vec<_> = timings.phases().iter().map(|(label, _)| *label).collect();
+
+    assert_eq!(labels, vec!["TTFB", "Download"]);
+}
+
+#[test]
+fn test_execute_reads_body_and_times_new_connection() {
+    let url = serve("text/plain", b"hello");
+    let http = HttpClient::new(ClientSettings::default()).unwrap();
+
+    let response = http
+        .execute(http.client().get(&url), &Arc::default(), BodySink::Memory)
+        .unwrap();
+
+    assert_eq!(response.status, 200);
+    assert_eq!(body_text(response.body), "hello");
+    assert!(!response.connection_reused);
+    // IP literal, so no lookup but a fresh TCP connect
+    assert!(response.timings.dns.is_none());
+    assert!(response.timings.connect.is_some());
+}
+
+#[test]
+fn test_execute_times_dns_lookup() {
+    let url = serve("text/plain", b"hello").replace("127.0.0.1", "localhost");
+    let http = HttpClient::new(ClientSettings::default()).unwrap();
+
+    let response = http
+        .execute(http.client().get(&url), &Arc::default(), BodySink::Memory)
+        .unwrap();
+
+    assert!(response.timings.dns.is_some());
+    assert!(response.timings.connect.is_some());
+}
+
+#[test]
+fn test_execute_reuses_pooled_connection() {
+    let url = serve("text/plain", b"hello");
+    let http = HttpClient::new(ClientSettings::default()).unwrap();
+
+    http.execute(http.client().get(&url), &Arc::default(), BodySink::Memory)
+        .unwrap();
+    let second = http
+        .execute(http.client().get(&url), &Arc::default(), BodySink::Memory)
+        .unwrap();
+
+    assert!(second.connection_reused);
+    assert!(second.timings.connect.is_none());
+    assert_eq!(http.connections_opened(), 1);
+}
+
+#[test]
+fn test_concurrent_requests_keep_their_own_timings() {
+    let slow = super::test_server::serve_raw(|_request| {
+        std::thread::sleep(Duration::from_millis(300));
+        b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\nslow".to_vec()
+    });
+    let fast = serve("text/plain", b"fast");
+    let http = HttpClient::new(ClientSettings::default()).unwrap();
+
+    let background = http.clone();
+    let slow_request = std::thread::spawn(move || {
+        background
+            .execute(
+                background.client().get(&slow),
+                &Arc::default(),
+                BodySink::Memory,
+            )
+            .unwrap()
+    });
+    std::thread::sleep(Duration::from_millis(100));
+    let first = http
+        .execute(http.client().get(&fast), &Arc::default(), BodySink::Memory)
+        .unwrap();
+    let second = http
+        .execute(http.client().get(&fast), &Arc::default(), BodySink::Memory)
+        .unwrap();
+    let slow = slow_request.join().unwrap();
+
+    assert!(!first.connection_reused);
+    assert!(second.connection_reused);
+    assert!(second.timings.connect.is_none());
+    assert_eq!(body_text(slow.body), "slow");
+    assert!(!slow.connection_reused);
+    assert!(slow.timings.connect.is_some());
+}
+
+#[test]
+fn test_connection_opened_before_the_request_counts_as_reused() {
+    let tracker = ConnectionTracker::default();
+    let addr: SocketAddr = "127.0.0.1:5000".parse().unwrap();
+    let setup = ConnectionSetup {
+        dns: None,
+        connect: Duration::from_millis(2),
+        finished: Instant::now(),
+    };
+    tracker.unclaimed.lock().unwrap().insert(addr, setup);
+    assert_eq!(tracker.claim(addr, setup.finished), Some(setup));
+    // Claimed once only
+    assert_eq!(tracker.claim(addr, setup.finished), None);
+
+    tracker.unclaimed.lock().unwrap().insert(addr, setup);
+    let later = setup.finished + Duration::from_millis(1);
+    assert_eq!(tracker.claim(addr, later), None);
+    assert!(tracker.unclaimed.lock().unwrap().is_empty());
+}
+
+#[test]
+fn test_execute_without_keep_alive_opens_new_connections() {
+    let url = serve("text/plain", b"hello");
+    let http = HttpClient::new(ClientSettings {
+        keep_alive: false,
+        ..ClientSettings::default()
+    })
+    .unwrap();
+
+    http.execute(http.client().get(&url), &Arc::default(), BodySink::Memory)
+        .unwrap();
+    let second = http
+        .execute(http.client().get(&url), &Arc::default(), BodySink::Memory)
+        .unwrap();
+
+    assert!(!second.connection_reused);
+    assert_eq!(http.connections_opened(), 2);
+}
+
+#[test]
+fn test_untrusted_certificate_is_rejected() {
+    let url = super::test_server::serve_tls(b"secure");
+    let http = HttpClient::new(ClientSettings::default()).unwrap();
+
+    assert!(http
+        .execute(http.client().get(&url), &Arc::default(), BodySink::Memory)
+        .is_err());
+}
+
+#[test]
+fn test_custom_ca_bundle_trusts_server_and_captures_certificate() {
+    let url = super::test_server::serve_tls(b"secure");
+    let http = HttpClient::new(ClientSettings {
+        tls: TlsSettings {
+            ca_bundle: concat!(env!("CARGO_MANIFEST_DIR"), "/src/tls/testdata/cert.pem")
+                .to_string(),
+            ..TlsSettings::default()
+        },
+        ..ClientSettings::default()
+    })
+    .unwrap();
+
+    let response = http
+        .execute(http.client().get(&url), &Arc::default(), BodySink::Memory)
+        .unwrap();
+
+    assert_eq!(body_text(response.body), "secure");
+    assert!(response.timings.connect.is_some());
+    assert!(response.certificate.unwrap().subject.contains("jorna.test"));
+}
+
+#[test]
+#[cfg(not(any(target_os = "windows", target_vendor = "apple")))]
+fn test_certificate_chain_uses_request_tls_settings() {
+    let url =
+        super::test_server::serve_tls_with(include_bytes!("../tls/testdata/chain.p12"), b"secure");
+    let settings = ClientSettings {
+        tls: TlsSettings {
+            ca_bundle: concat!(env!("CARGO_MANIFEST_DIR"), "/src/tls/testdata/ca.pem").to_string(),
+            ..TlsSettings::default()
+        },
+        ..ClientSettings::default()
+    };
+    let http = HttpClient::new(settings.clone()).unwrap();
+
+    let response = http
+        .execute(http.client().get(&url), &Arc::default(), BodySink::Memory)
+        .unwrap();
+    let chain = certificate_chain(&settings, &response.url).unwrap();
+
+    let subjects: Vec<_> = chain.iter().map(|c| c.subject.as_str()).collect();
+    assert_eq!(subjects.len(), 2);
+    assert!(subjects[0].contains("chain.jorna.test"));
+    assert!(subjects[1].contains("Jorna Test CA"));
+    assert!(chain[1].is_ca);
+    assert_eq!(Some(&chain[0]), response.certificate.as_ref());
+    assert!(certificate_chain(&ClientSettings::default(), &response.url).is_err());
+}
+
+#[test]
+fn test_insecure_mode_skips_verification() {
+    let url = super::test_server::serve_tls(b"secure");
+    let http = HttpClient::new(ClientSettings {
+        tls: TlsSettings {
+            insecure: true,
+            ..TlsSettings::default()
+        },
+        ..ClientSettings::default()
+    })
+    .unwrap();
+
+    let response = http
+        .execute(http.client().get(&url), &Arc::default(), BodySink::Memory)
+        .unwrap();
+
+    assert_eq!(body_text(response.body), "secure");
+}
+
+#[test]
+fn test_tls_settings_fields() {
+    let mut settings = ClientSettings::default();
+
+    SettingsField::CaBundle
+        .set(&mut settings, " /etc/ca.pem ")
+        .unwrap();
+    SettingsField::ClientCertPassword
+        .set(&mut settings, "hunter2")
+        .unwrap();
+    SettingsField::MinTlsVersion.toggle(&mut settings);
+    SettingsField::Insecure.toggle(&mut settings);
+
+    assert_eq!(settings.tls.ca_bundle, "/etc/ca.pem");
+    assert_eq!(
+        SettingsField::ClientCertPassword.display_value(&settings),
+        "*******"
+    );
+    assert_eq!(SettingsField::MinTlsVersion.value(&settings), "1.0");
+    assert!(settings.tls.insecure);
+}
+
+#[test]
+fn test_invalid_tls_settings_fail_to_build() {
+    let settings = ClientSettings {
+        tls: TlsSettings {
+            client_cert: "/nonexistent/client.pem".to_string(),
+            ..TlsSettings::default()
+        },
+        ..ClientSettings::default()
+    };
+
+    assert!(HttpClient::new(settings).is_err());
+}
+
+#[test]
+fn test_manual_proxy_receives_request_with_auth() {
+    let proxy = super::test_server::serve_raw(|request| {
+        let request = String::from_utf8_lossy(request).to_string();
+        let body = request.lines().next().unwrap_or_default().to_string()
+            + if request
+                .to_ascii_lowercase()
+                .contains("proxy-authorization: basic ")
+            {
+                " with auth"
+            } else {
+                ""
+            };
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .into_bytes()
+    });
+    let http = HttpClient::new(ClientSettings {
+        proxy: ProxySettings {
+            mode: ProxyMode::Manual,
+            http: proxy,
+            username: "me".to_string(),
+            password: "secret".to_string(),
+            ..ProxySettings::default()
+        },
+        ..ClientSettings::default()
+    })
+    .unwrap();
+
+    let response = http
+        .execute(
+            http.client().get("http://upstream.invalid/path"),
+            &Arc::default(),
+            BodySink::Memory,
+        )
+        .unwrap();
+
+    assert_eq!(
+        body_text(response.body),
+        "GET http://upstream.invalid/path HTTP/1.1 with auth"
+    );
+}
+
+#[test]
+fn test_proxy_settings_fields() {
+    let mut settings = ClientSettings::default();
+
+    SettingsField::ProxyMode.toggle(&mut settings);
+    SettingsField::HttpProxy
+        .set(&mut settings, "http://proxy:8080")
+        .unwrap();
+    SettingsField::ProxyPassword
+        .set(&mut settings, "pw")
+        .unwrap();
+    SettingsField::NoProxy
+        .set(&mut settings, "localhost, .corp")
+        .unwrap();
+
+    assert_eq!(settings.proxy.mode, ProxyMode::Manual);
+    assert_eq!(settings.proxy.http, "http://proxy:8080");
+    assert_eq!(SettingsField::ProxyPassword.display_value(&settings), "**");
+    assert_eq!(settings.proxy.no_proxy, "localhost, .corp");
+    assert!(SettingsField::ProxyMode
+        .set(&mut settings, "sometimes")
+        .is_err());
+}
+
+#[test]
+fn test_execute_reports_download_progress() {
+    let url = serve("application/octet-stream", b"\x00\x01\x02\x03");
+    let http = HttpClient::new(ClientSettings::default()).unwrap();
+    let progress = Arc::new(TransferProgress::default());
+
+    let response = http
+        .execute(http.client().get(&url), &progress, BodySink::Memory)
+        .unwrap();
+
+    assert_eq!(
+        response.body.unwrap(),
+        ResponseBody::Bytes(vec![0, 1, 2, 3])
+    );
+    assert_eq!(
+        response.content_type.as_deref(),
+        Some("application/octet-stream")
+    );
+    assert_eq!(progress.get(), Some((4, 4)));
+}
+
+#[test]
+fn test_execute_saves_body_to_directory() {
+    let dir = std::env::temp_dir().join(format!("jorna-download-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let url = serve("image/png", b"\x89PNG");
+    let http = HttpClient::new(ClientSettings::default()).unwrap();
+
+    let response = http
+        .execute(
+            http.client().get(format!("{}/images/logo.png", url)),
+            &Arc::default(),
+            BodySink::Download(&dir),
+        )
+        .unwrap();
+    let saved = std::fs::read(dir.join("logo.png"));
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(response.filename, "logo.png");
+    assert_eq!(
+        response.body.unwrap(),
+        ResponseBody::Saved {
+            path: dir.join("logo.png"),
+            size: 4,
+        }
+    );
+    assert_eq!(saved.unwrap(), b"\x89PNG");
+}
+
+#[test]
+fn test_execute_streams_chunks() {
+    let url = super::test_server::serve_raw(|_| {
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nTransfer-Encoding: chunked\r\n\r\n\
+          7\r\ndata: 1\r\n6\r\n\n\ndata\r\n4\r\n: 2\n\r\n0\r\n\r\n"
+            .to_vec()
+    });
+    let http = HttpClient::new(ClientSettings::default()).unwrap();
+    let (sender, receiver) = std::sync::mpsc::channel();
+
+    let response = http
+        .execute(
+            http.client().get(&url),
+            &Arc::default(),
+            BodySink::Stream(&sender, &AtomicBool::new(false)),
+        )
+        .unwrap();
+    drop(sender);
+    let updates: Vec<StreamUpdate> = receiver.iter().collect();
+
+    assert_eq!(body_text(response.body), "data: 1\n\ndata: 2\n");
+    assert_eq!(
+        updates[0],
+        StreamUpdate::Started {
+            status: 200,
+            content_type: Some("text/event-stream".to_string()),
+        }
+    );
+    let streamed: Vec<u8> = updates[1..]
+        .iter()
+        .flat_map(|update| match update {
+            StreamUpdate::Chunk(bytes) => bytes.clone(),
+            other => panic!("unexpected update {:?}", other),
+        })
+        .collect();
+    assert_eq!(streamed, b"data: 1\n\ndata: 2\n");
+}
+
+#[test]
+fn test_execute_stream_stops_when_asked() {
+    let url = super::test_server::serve_raw(|_| {
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nTransfer-Encoding: chunked\r\n\r\n\
+          7\r\ndata: 1\r\n"
+            .to_vec()
+    });
+    let http = HttpClient::new(ClientSettings::default()).unwrap();
+    let (sender, receiver) = std::sync::mpsc::channel();
+
+    let response = http
+        .execute(
+            http.client().get(&url),
+            &Arc::default(),
+            BodySink::Stream(&sender, &AtomicBool::new(true)),
+        )
+        .unwrap();
+    drop(sender);
+
+    assert_eq!(body_text(response.body), "");
+    assert_eq!(receiver.iter().count(), 1);
+}
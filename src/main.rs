@@ -1,5 +1,8 @@
 use crossterm::{
-    event::{self as crossterm_event, Event, KeyEventKind},
+    clipboard::CopyToClipboard,
+    event::{
+        self as crossterm_event, DisableBracketedPaste, EnableBracketedPaste, Event, KeyEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -7,8 +10,31 @@ use ratatui::{backend::CrosstermBackend, Terminal};
 use std::io::{self, stdout};
 
 mod app;
+mod body;
+mod clipboard;
+mod codegen;
+mod diff;
+mod download;
+mod editor;
 mod event;
+mod external;
+mod filter;
+mod format;
+mod graphql;
+mod grpc;
+mod headers;
+mod highlight;
+mod http;
+mod params;
+mod proto;
+mod proxy;
+mod search;
+mod sse;
+mod text;
+mod tls;
+mod tree;
 mod ui;
+mod ws;
 
 use app::App;
 use event::handle_key_event;
@@ -25,15 +51,47 @@ fn run_app(
             break;
         }
 
+        app.poll_request();
+
         // Poll for events with timeout
         if crossterm_event::poll(std::time::Duration::from_millis(100))? {
-            if let Event::Key(key) = crossterm_event::read()? {
+            match crossterm_event::read()? {
                 // Only handle KeyPress events (ignore KeyRelease)
-                if key.kind != KeyEventKind::Press {
-                    continue;
+                Event::Key(key) if key.kind == KeyEventKind::Press => {
+                    handle_key_event(&mut app, key);
                 }
+                Event::Paste(text) => app.paste(&text),
+                _ => continue,
+            }
+
+            if let Some(text) = app.clipboard_request.take() {
+                // OSC 52 reaches the local clipboard over SSH and without a display
+                if !clipboard::copy(&text) {
+                    execute!(
+                        terminal.backend_mut(),
+                        CopyToClipboard::to_clipboard_from(text)
+                    )?;
+                }
+            }
 
-                handle_key_event(&mut app, key);
+            if let Some(target) = app.external_edit.take() {
+                let (text, extension) = app.external_edit_content(target);
+                // Hand the terminal to the editor, then take it back
+                disable_raw_mode()?;
+                execute!(
+                    terminal.backend_mut(),
+                    DisableBracketedPaste,
+                    LeaveAlternateScreen
+                )?;
+                let result = external::edit(&text, extension);
+                enable_raw_mode()?;
+                execute!(
+                    terminal.backend_mut(),
+                    EnterAlternateScreen,
+                    EnableBracketedPaste
+                )?;
+                terminal.clear()?;
+                app.finish_external_edit(target, result);
             }
         }
     }
@@ -62,13 +120,13 @@ fn main() -> anyhow::Result<()> {
     let original_hook = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |panic_info| {
         let _ = disable_raw_mode();
-        let _ = execute!(stdout(), LeaveAlternateScreen);
+        let _ = execute!(stdout(), DisableBracketedPaste, LeaveAlternateScreen);
         original_hook(panic_info);
     }));
 
     // Setup terminal
     enable_raw_mode()?;
-    execute!(stdout(), EnterAlternateScreen)?;
+    execute!(stdout(), EnterAlternateScreen, EnableBracketedPaste)?;
 
     let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
 
@@ -80,7 +138,7 @@ fn main() -> anyhow::Result<()> {
 
     // Cleanup terminal (even on error)
     disable_raw_mode()?;
-    execute!(stdout(), LeaveAlternateScreen)?;
+    execute!(stdout(), DisableBracketedPaste, LeaveAlternateScreen)?;
 
     // Return error after cleanup
     result?;
@@ -0,0 +1,341 @@
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+
+use crate::text;
+
+/// Characters escaped in query keys and values. Everything else that is legal
+/// in a query component is left readable.
+const QUERY_COMPONENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'&')
+    .add(b'+')
+    .add(b'<')
+    .add(b'=')
+    .add(b'>')
+    .add(b'[')
+    .add(b']')
+    .add(b'`')
+    .add(b'{')
+    .add(b'|')
+    .add(b'}');
+
+#[derive(Debug, Clone)]
+pub struct QueryParam {
+    pub key: String,
+    pub value: String,
+    pub enabled: bool,
+    /// The pair as written in the URL, kept while the row still decodes to it
+    raw: Option<String>,
+}
+
+/// Rows are compared by what they mean, not how the URL spelled them
+impl PartialEq for QueryParam {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key && self.value == other.value && self.enabled == other.enabled
+    }
+}
+
+impl QueryParam {
+    pub fn new(key: &str, value: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            value: value.to_string(),
+            enabled: true,
+            raw: None,
+        }
+    }
+
+    /// Query component for this row, reusing the original spelling when the
+    /// row was not edited
+    fn encoded(&self) -> String {
+        if let Some(raw) = &self.raw {
+            let (key, value) = decode_pair(raw);
+            if key == self.key && value == self.value {
+                return raw.clone();
+            }
+        }
+        if self.value.is_empty() {
+            encode(&self.key)
+        } else {
+            format!("{}={}", encode(&self.key), encode(&self.value))
+        }
+    }
+
+    pub fn is_blank(&self) -> bool {
+        self.key.is_empty() && self.value.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ParamColumn {
+    #[default]
+    Key,
+    Value,
+}
+
+/// Key/value rows edited as a table, for query params and form bodies
+#[derive(Debug, Clone, Default)]
+pub struct ParamTable {
+    pub rows: Vec<QueryParam>,
+    pub index: usize,
+    pub column: ParamColumn,
+    /// Byte offset in the cell being edited
+    pub cursor: usize,
+    pub scroll: u16,
+}
+
+impl ParamTable {
+    pub fn new(rows: Vec<QueryParam>) -> Self {
+        Self {
+            rows,
+            ..Self::default()
+        }
+    }
+
+    /// Replace the rows, keeping the selection within them
+    pub fn set_rows(&mut self, rows: Vec<QueryParam>) {
+        self.rows = rows;
+        if self.index >= self.rows.len() {
+            self.index = self.rows.len().saturating_sub(1);
+        }
+        self.cursor = self.cursor.min(self.cell_len());
+    }
+
+    fn cell_len(&self) -> usize {
+        self.rows
+            .get(self.index)
+            .map(|row| match self.column {
+                ParamColumn::Key => row.key.len(),
+                ParamColumn::Value => row.value.len(),
+            })
+            .unwrap_or(0)
+    }
+
+    /// Cell being edited, adding a first row if the table is empty
+    fn cell(&mut self) -> &mut String {
+        if self.rows.is_empty() {
+            self.rows.push(QueryParam::new("", ""));
+            self.index = 0;
+        }
+        let row = &mut self.rows[self.index];
+        match self.column {
+            ParamColumn::Key => &mut row.key,
+            ParamColumn::Value => &mut row.value,
+        }
+    }
+
+    pub fn insert_str(&mut self, value: &str) {
+        let mut cursor = self.cursor;
+        text::insert(self.cell(), &mut cursor, value);
+        self.cursor = cursor;
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        self.insert_str(c.encode_utf8(&mut [0; 4]));
+    }
+
+    /// Remove the character before the cursor, returning whether anything changed
+    pub fn backspace(&mut self) -> bool {
+        let mut cursor = self.cursor;
+        let removed = text::remove_previous(self.cell(), &mut cursor);
+        self.cursor = cursor;
+        removed
+    }
+
+    /// Remove the character under the cursor, returning whether anything changed
+    pub fn delete(&mut self) -> bool {
+        let cursor = self.cursor;
+        text::remove_next(self.cell(), cursor)
+    }
+
+    pub fn left(&mut self) {
+        if self.cursor > 0 {
+            let cursor = self.cursor;
+            self.cursor = text::previous_boundary(self.cell(), cursor);
+        } else if self.column == ParamColumn::Value {
+            self.column = ParamColumn::Key;
+            self.cursor = self.cell_len();
+        }
+    }
+
+    pub fn right(&mut self) {
+        let len = self.cell_len();
+        if self.cursor < len {
+            let cursor = self.cursor;
+            self.cursor = text::next_boundary(self.cell(), cursor);
+        } else if self.column == ParamColumn::Key {
+            self.column = ParamColumn::Value;
+            self.cursor = 0;
+        }
+    }
+
+    pub fn up(&mut self) {
+        if self.index > 0 {
+            self.index -= 1;
+            self.clamp_cursor();
+        }
+    }
+
+    pub fn down(&mut self) {
+        if self.index + 1 < self.rows.len() {
+            self.index += 1;
+            self.clamp_cursor();
+        }
+    }
+
+    fn clamp_cursor(&mut self) {
+        let cursor = self.cursor.min(self.cell_len());
+        self.cursor = text::snap(self.cell(), cursor);
+    }
+
+    /// Move from key to value, or from value to a new row below
+    pub fn enter(&mut self) {
+        match self.column {
+            ParamColumn::Key => {
+                self.cell();
+                self.column = ParamColumn::Value;
+            }
+            ParamColumn::Value => {
+                let index = if self.rows.is_empty() {
+                    0
+                } else {
+                    self.index + 1
+                };
+                self.rows.insert(index, QueryParam::new("", ""));
+                self.index = index;
+                self.column = ParamColumn::Key;
+            }
+        }
+        self.cursor = 0;
+    }
+
+    /// Flip the selected row on or off, returning whether there was one
+    pub fn toggle_enabled(&mut self) -> bool {
+        match self.rows.get_mut(self.index) {
+            Some(row) => {
+                row.enabled = !row.enabled;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove the selected row, returning whether there was one
+    pub fn delete_row(&mut self) -> bool {
+        if self.index >= self.rows.len() {
+            return false;
+        }
+        self.rows.remove(self.index);
+        if self.index >= self.rows.len() {
+            self.index = self.rows.len().saturating_sub(1);
+        }
+        self.cursor = 0;
+        true
+    }
+
+    pub fn ensure_cursor_visible(&mut self, visible_lines: usize) {
+        if visible_lines == 0 {
+            return;
+        }
+        let scroll = self.scroll as usize;
+        if self.index < scroll {
+            self.scroll = self.index as u16;
+        } else if self.index >= scroll + visible_lines {
+            self.scroll = (self.index - visible_lines + 1) as u16;
+        }
+    }
+}
+
+/// Split a URL into the part before the query, the raw query and the fragment
+fn split_url(url: &str) -> (&str, Option<&str>, Option<&str>) {
+    let (rest, fragment) = match url.split_once('#') {
+        Some((rest, fragment)) => (rest, Some(fragment)),
+        None => (url, None),
+    };
+    match rest.split_once('?') {
+        Some((base, query)) => (base, Some(query), fragment),
+        None => (rest, None, fragment),
+    }
+}
+
+fn decode(component: &str) -> String {
+    let component = component.replace('+', " ");
+    percent_decode_str(&component)
+        .decode_utf8_lossy()
+        .into_owned()
+}
+
+fn encode(component: &str) -> String {
+    utf8_percent_encode(component, QUERY_COMPONENT).to_string()
+}
+
+fn decode_pair(pair: &str) -> (String, String) {
+    match pair.split_once('=') {
+        Some((key, value)) => (decode(key), decode(value)),
+        None => (decode(pair), String::new()),
+    }
+}
+
+/// Decode the query string of `url` into enabled params
+pub fn parse_query(url: &str) -> Vec<QueryParam> {
+    let (_, query, _) = split_url(url);
+    query
+        .unwrap_or_default()
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = decode_pair(pair);
+            QueryParam {
+                raw: Some(pair.to_string()),
+                ..QueryParam::new(&key, &value)
+            }
+        })
+        .collect()
+}
+
+/// Replace the query string of `url` with the enabled, non-blank params.
+/// Rows parsed from the URL and left alone keep their original encoding.
+pub fn with_query(url: &str, params: &[QueryParam]) -> String {
+    let (base, _, fragment) = split_url(url);
+    let query = params
+        .iter()
+        .filter(|param| param.enabled && !param.is_blank())
+        .map(QueryParam::encoded)
+        .collect::<Vec<_>>()
+        .join("&");
+
+    let mut url = base.to_string();
+    if !query.is_empty() {
+        url.push('?');
+        url.push_str(&query);
+    }
+    if let Some(fragment) = fragment {
+        url.push('#');
+        url.push_str(fragment);
+    }
+    url
+}
+
+/// Merge params freshly parsed from the URL into the existing table.
+///
+/// Disabled and blank rows are not part of the URL, so they keep their place
+/// while the enabled rows are replaced in order by the parsed ones.
+pub fn merge(existing: &[QueryParam], parsed: Vec<QueryParam>) -> Vec<QueryParam> {
+    let mut parsed = parsed.into_iter();
+    let mut merged = Vec::new();
+
+    for param in existing {
+        if !param.enabled || param.is_blank() {
+            merged.push(param.clone());
+        } else if let Some(next) = parsed.next() {
+            merged.push(next);
+        }
+    }
+    merged.extend(parsed);
+    merged
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,144 @@
+use super::*;
+
+#[test]
+fn test_parse_query_without_query() {
+    assert!(parse_query("https://example.com/path").is_empty());
+}
+
+#[test]
+fn test_parse_query_pairs() {
+    let params = parse_query("https://example.com/search?q=rust&page=2&flag");
+
+    assert_eq!(
+        params,
+        vec![
+            QueryParam::new("q", "rust"),
+            QueryParam::new("page", "2"),
+            QueryParam::new("flag", ""),
+        ]
+    );
+}
+
+#[test]
+fn test_parse_query_decodes() {
+    let params = parse_query("http://x/?name=J%C3%B6rn+Doe&a%26b=c%3Dd");
+
+    assert_eq!(params[0].value, "Jörn Doe");
+    assert_eq!(params[1].key, "a&b");
+    assert_eq!(params[1].value, "c=d");
+}
+
+#[test]
+fn test_parse_query_ignores_fragment() {
+    let params = parse_query("http://x/?a=1#section?b=2");
+
+    assert_eq!(params, vec![QueryParam::new("a", "1")]);
+}
+
+#[test]
+fn test_with_query_encodes() {
+    let url = with_query(
+        "https://example.com/search?old=1",
+        &[
+            QueryParam::new("q", "hello world"),
+            QueryParam::new("filter", "a&b=c"),
+            QueryParam::new("path", "/x/y?z"),
+        ],
+    );
+
+    assert_eq!(
+        url,
+        "https://example.com/search?q=hello%20world&filter=a%26b%3Dc&path=/x/y?z"
+    );
+}
+
+#[test]
+fn test_with_query_skips_disabled_and_blank() {
+    let mut disabled = QueryParam::new("debug", "true");
+    disabled.enabled = false;
+
+    let url = with_query(
+        "http://x/",
+        &[QueryParam::new("a", "1"), disabled, QueryParam::new("", "")],
+    );
+
+    assert_eq!(url, "http://x/?a=1");
+}
+
+#[test]
+fn test_with_query_removes_empty_query_and_keeps_fragment() {
+    let url = with_query("http://x/page?a=1#top", &[]);
+
+    assert_eq!(url, "http://x/page#top");
+}
+
+#[test]
+fn test_round_trip() {
+    let url = "https://example.com/?q=caf%C3%A9%20au%20lait&n=1";
+
+    assert_eq!(with_query(url, &parse_query(url)), url);
+}
+
+#[test]
+fn test_round_trip_keeps_empty_values_and_original_encoding() {
+    let url = "https://example.com/?a=&b&q=hello+world&x=%7e%2F";
+
+    assert_eq!(with_query(url, &parse_query(url)), url);
+}
+
+#[test]
+fn test_with_query_reencodes_only_edited_pairs() {
+    let url = "https://example.com/?q=hello+world&a=";
+    let mut params = parse_query(url);
+    params[1].value = "1 2".to_string();
+
+    assert_eq!(
+        with_query(url, &params),
+        "https://example.com/?q=hello+world&a=1%202"
+    );
+}
+
+#[test]
+fn test_merge_keeps_disabled_rows_in_place() {
+    let mut disabled = QueryParam::new("debug", "true");
+    disabled.enabled = false;
+    let existing = vec![
+        QueryParam::new("a", "1"),
+        disabled.clone(),
+        QueryParam::new("b", "2"),
+    ];
+
+    let merged = merge(
+        &existing,
+        vec![QueryParam::new("a", "10"), QueryParam::new("b", "20")],
+    );
+
+    assert_eq!(
+        merged,
+        vec![
+            QueryParam::new("a", "10"),
+            disabled,
+            QueryParam::new("b", "20"),
+        ]
+    );
+}
+
+#[test]
+fn test_merge_appends_and_drops_enabled_rows() {
+    let existing = vec![QueryParam::new("a", "1"), QueryParam::new("b", "2")];
+
+    assert_eq!(
+        merge(&existing, vec![QueryParam::new("a", "1")]),
+        vec![QueryParam::new("a", "1")]
+    );
+    assert_eq!(merge(&existing[..1], existing.clone()), existing);
+}
+
+#[test]
+fn test_merge_keeps_blank_rows() {
+    let existing = vec![QueryParam::new("a", "1"), QueryParam::new("", "")];
+
+    let merged = merge(&existing, vec![QueryParam::new("a", "2")]);
+
+    assert_eq!(merged[1], QueryParam::new("", ""));
+}
@@ -0,0 +1,1257 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use serde_json::{json, Map, Value};
+
+/// Well-known types, used when an import of `google/protobuf/...` is not
+/// found next to the user's files
+const WELL_KNOWN: &[(&str, &str)] = &[
+    (
+        "google/protobuf/any.proto",
+        "syntax = \"proto3\"; package google.protobuf;
+         message Any { string type_url = 1; bytes value = 2; }",
+    ),
+    (
+        "google/protobuf/duration.proto",
+        "syntax = \"proto3\"; package google.protobuf;
+         message Duration { int64 seconds = 1; int32 nanos = 2; }",
+    ),
+    (
+        "google/protobuf/empty.proto",
+        "syntax = \"proto3\"; package google.protobuf; message Empty {}",
+    ),
+    (
+        "google/protobuf/field_mask.proto",
+        "syntax = \"proto3\"; package google.protobuf;
+         message FieldMask { repeated string paths = 1; }",
+    ),
+    (
+        "google/protobuf/struct.proto",
+        "syntax = \"proto3\"; package google.protobuf;
+         message Struct { map<string, Value> fields = 1; }
+         message Value {
+           oneof kind {
+             NullValue null_value = 1; double number_value = 2; string string_value = 3;
+             bool bool_value = 4; Struct struct_value = 5; ListValue list_value = 6;
+           }
+         }
+         enum NullValue { NULL_VALUE = 0; }
+         message ListValue { repeated Value values = 1; }",
+    ),
+    (
+        "google/protobuf/timestamp.proto",
+        "syntax = \"proto3\"; package google.protobuf;
+         message Timestamp { int64 seconds = 1; int32 nanos = 2; }",
+    ),
+    (
+        "google/protobuf/wrappers.proto",
+        "syntax = \"proto3\"; package google.protobuf;
+         message DoubleValue { double value = 1; } message FloatValue { float value = 1; }
+         message Int64Value { int64 value = 1; } message UInt64Value { uint64 value = 1; }
+         message Int32Value { int32 value = 1; } message UInt32Value { uint32 value = 1; }
+         message BoolValue { bool value = 1; } message StringValue { string value = 1; }
+         message BytesValue { bytes value = 1; }",
+    ),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldKind {
+    Double,
+    Float,
+    Int64,
+    Uint64,
+    Int32,
+    Uint32,
+    Sint32,
+    Sint64,
+    Fixed32,
+    Fixed64,
+    Sfixed32,
+    Sfixed64,
+    Bool,
+    String,
+    Bytes,
+    /// Full name of a message type
+    Message(String),
+    /// Full name of an enum type
+    Enum(String),
+    Map(Box<FieldKind>, Box<FieldKind>),
+    /// Type name as written, before `resolve` finds what it refers to
+    Named(String),
+}
+
+impl FieldKind {
+    fn scalar(name: &str) -> Option<FieldKind> {
+        Some(match name {
+            "double" => FieldKind::Double,
+            "float" => FieldKind::Float,
+            "int64" => FieldKind::Int64,
+            "uint64" => FieldKind::Uint64,
+            "int32" => FieldKind::Int32,
+            "uint32" => FieldKind::Uint32,
+            "sint32" => FieldKind::Sint32,
+            "sint64" => FieldKind::Sint64,
+            "fixed32" => FieldKind::Fixed32,
+            "fixed64" => FieldKind::Fixed64,
+            "sfixed32" => FieldKind::Sfixed32,
+            "sfixed64" => FieldKind::Sfixed64,
+            "bool" => FieldKind::Bool,
+            "string" => FieldKind::String,
+            "bytes" => FieldKind::Bytes,
+            _ => return None,
+        })
+    }
+
+    fn wire_type(&self) -> u8 {
+        match self {
+            FieldKind::Double | FieldKind::Fixed64 | FieldKind::Sfixed64 => WIRE_FIXED64,
+            FieldKind::Float | FieldKind::Fixed32 | FieldKind::Sfixed32 => WIRE_FIXED32,
+            FieldKind::String
+            | FieldKind::Bytes
+            | FieldKind::Message(_)
+            | FieldKind::Map(..)
+            | FieldKind::Named(_) => WIRE_LEN,
+            _ => WIRE_VARINT,
+        }
+    }
+
+    /// Numeric types, which repeated fields may pack into one record
+    fn is_packable(&self) -> bool {
+        self.wire_type() != WIRE_LEN
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDesc {
+    pub name: String,
+    /// lowerCamelCase name used in JSON
+    pub json_name: String,
+    pub number: u32,
+    pub kind: FieldKind,
+    pub repeated: bool,
+    /// Repeated numbers are written as one packed record
+    pub packed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageDesc {
+    pub name: String,
+    pub fields: Vec<FieldDesc>,
+}
+
+impl MessageDesc {
+    fn field(&self, key: &str) -> Option<&FieldDesc> {
+        self.fields
+            .iter()
+            .find(|field| field.json_name == key || field.name == key)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MethodDesc {
+    /// Full name of the service, e.g. `shop.v1.Orders`
+    pub service: String,
+    pub name: String,
+    /// Full names of the request and response messages
+    pub input: String,
+    pub output: String,
+    pub client_streaming: bool,
+    pub server_streaming: bool,
+}
+
+impl MethodDesc {
+    /// Request path of the method, e.g. `/shop.v1.Orders/Get`
+    pub fn path(&self) -> String {
+        format!("/{}/{}", self.service, self.name)
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match (self.client_streaming, self.server_streaming) {
+            (false, false) => "unary",
+            (false, true) => "server streaming",
+            (true, false) => "client streaming",
+            (true, true) => "bidirectional streaming",
+        }
+    }
+}
+
+/// Messages, enums and services of a set of `.proto` files
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Descriptors {
+    messages: HashMap<String, MessageDesc>,
+    /// Value names and numbers of each enum, in declaration order
+    enums: HashMap<String, Vec<(String, i32)>>,
+    methods: Vec<MethodDesc>,
+}
+
+impl Descriptors {
+    /// Parse `paths` and everything they import. Imports are looked up in
+    /// the directories of the given files.
+    pub fn load(paths: &[PathBuf]) -> Result<Descriptors, String> {
+        let include_dirs: Vec<PathBuf> = paths
+            .iter()
+            .map(|path| path.parent().unwrap_or(Path::new(".")).to_path_buf())
+            .collect();
+        let mut descriptors = Descriptors::default();
+        let mut loaded = HashSet::new();
+        let mut queue: Vec<(String, PathBuf)> = paths
+            .iter()
+            .map(|path| (path.display().to_string(), path.clone()))
+            .collect();
+        queue.reverse();
+
+        while let Some((name, path)) = queue.pop() {
+            if !loaded.insert(std::fs::canonicalize(&path).unwrap_or_else(|_| path.clone())) {
+                continue;
+            }
+            let source = match std::fs::read_to_string(&path) {
+                Ok(source) => source,
+                Err(e) => match WELL_KNOWN.iter().find(|(known, _)| *known == name) {
+                    Some((_, source)) => source.to_string(),
+                    None => return Err(format!("Failed to read {}: {}", path.display(), e)),
+                },
+            };
+            let imports = descriptors.parse(&name, &source)?;
+            for import in imports {
+                let path = include_dirs
+                    .iter()
+                    .map(|dir| dir.join(&import))
+                    .find(|path| path.exists())
+                    .unwrap_or_else(|| PathBuf::from(&import));
+                queue.push((import, path));
+            }
+        }
+        descriptors.resolve()?;
+        Ok(descriptors)
+    }
+
+    /// Parse one file into the descriptors, returning its imports
+    pub fn parse(&mut self, file: &str, source: &str) -> Result<Vec<String>, String> {
+        let tokens = tokenize(source).map_err(|(line, e)| format!("{}:{}: {}", file, line, e))?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            package: String::new(),
+            proto3: false,
+            imports: Vec::new(),
+            descriptors: self,
+        };
+        parser
+            .file()
+            .map_err(|(line, e)| format!("{}:{}: {}", file, line, e))?;
+        Ok(parser.imports)
+    }
+
+    /// Replace the type names written in fields and methods with the full
+    /// names of the types they refer to
+    pub fn resolve(&mut self) -> Result<(), String> {
+        let names: Vec<String> = self.messages.keys().cloned().collect();
+        for name in names {
+            let mut message = self.messages[&name].clone();
+            for field in &mut message.fields {
+                field.kind = self
+                    .resolve_kind(&field.kind, &name)
+                    .map_err(|e| format!("{} in field {}.{}", e, name, field.name))?;
+                field.packed = field.packed && field.kind.is_packable();
+            }
+            self.messages.insert(name, message);
+        }
+
+        let mut methods = std::mem::take(&mut self.methods);
+        for method in &mut methods {
+            let path = method.path();
+            let scope = method.service.clone();
+            for name in [&mut method.input, &mut method.output] {
+                match self.resolve_kind(&FieldKind::Named(name.clone()), &scope) {
+                    Ok(FieldKind::Message(full)) => *name = full,
+                    _ => return Err(format!("Unknown message type {} in {}", name, path)),
+                }
+            }
+        }
+        self.methods = methods;
+        Ok(())
+    }
+
+    fn resolve_kind(&self, kind: &FieldKind, scope: &str) -> Result<FieldKind, String> {
+        match kind {
+            FieldKind::Named(name) => self.lookup(name, scope),
+            FieldKind::Map(key, value) => Ok(FieldKind::Map(
+                key.clone(),
+                Box::new(self.resolve_kind(value, scope)?),
+            )),
+            kind => Ok(kind.clone()),
+        }
+    }
+
+    /// Find a type name the way protoc does, from the innermost scope out
+    fn lookup(&self, name: &str, scope: &str) -> Result<FieldKind, String> {
+        let found = |full: &str| {
+            if self.messages.contains_key(full) {
+                Some(FieldKind::Message(full.to_string()))
+            } else if self.enums.contains_key(full) {
+                Some(FieldKind::Enum(full.to_string()))
+            } else {
+                None
+            }
+        };
+        if let Some(full) = name.strip_prefix('.') {
+            return found(full).ok_or_else(|| format!("Unknown type {}", name));
+        }
+        let mut scope = scope;
+        loop {
+            let full = if scope.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}.{}", scope, name)
+            };
+            if let Some(kind) = found(&full) {
+                return Ok(kind);
+            }
+            if scope.is_empty() {
+                return Err(format!("Unknown type {}", name));
+            }
+            scope = scope.rsplit_once('.').map(|(outer, _)| outer).unwrap_or("");
+        }
+    }
+
+    pub fn message(&self, name: &str) -> Option<&MessageDesc> {
+        self.messages.get(name)
+    }
+
+    /// Methods of all services, in the order they were declared
+    pub fn methods(&self) -> &[MethodDesc] {
+        &self.methods
+    }
+
+    /// The method a request path such as `/shop.v1.Orders/Get` calls
+    pub fn method(&self, path: &str) -> Option<&MethodDesc> {
+        self.methods.iter().find(|method| method.path() == path)
+    }
+
+    /// Encode a JSON object as the protobuf message `message`
+    pub fn encode(&self, message: &str, value: &Value) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        self.encode_message(message, value, &mut out)?;
+        Ok(out)
+    }
+
+    fn encode_message(
+        &self,
+        message: &str,
+        value: &Value,
+        out: &mut Vec<u8>,
+    ) -> Result<(), String> {
+        let desc = self
+            .message(message)
+            .ok_or_else(|| format!("Unknown message type {}", message))?;
+        let object = value
+            .as_object()
+            .ok_or_else(|| format!("Expected a JSON object for {}", message))?;
+        for (key, value) in object {
+            let field = desc
+                .field(key)
+                .ok_or_else(|| format!("Unknown field '{}' in {}", key, message))?;
+            if value.is_null() {
+                continue;
+            }
+            let context = |e: String| format!("{}.{}: {}", message, key, e);
+            match &field.kind {
+                FieldKind::Map(key_kind, value_kind) => {
+                    let entries = value
+                        .as_object()
+                        .ok_or_else(|| context("expected an object".into()))?;
+                    for (map_key, map_value) in entries {
+                        let key_value = match **key_kind {
+                            FieldKind::Bool => Value::Bool(map_key == "true"),
+                            _ => Value::String(map_key.clone()),
+                        };
+                        let mut entry = Vec::new();
+                        self.encode_field(1, key_kind, &key_value, &mut entry)
+                            .map_err(context)?;
+                        self.encode_field(2, value_kind, map_value, &mut entry)
+                            .map_err(context)?;
+                        write_tag(out, field.number, WIRE_LEN);
+                        write_bytes(out, &entry);
+                    }
+                }
+                kind if field.repeated => {
+                    let items = value
+                        .as_array()
+                        .ok_or_else(|| context("expected an array".into()))?;
+                    if field.packed {
+                        let mut packed = Vec::new();
+                        for item in items {
+                            encode_scalar(kind, item, &mut packed, self).map_err(context)?;
+                        }
+                        write_tag(out, field.number, WIRE_LEN);
+                        write_bytes(out, &packed);
+                    } else {
+                        for item in items {
+                            self.encode_field(field.number, kind, item, out)
+                                .map_err(context)?;
+                        }
+                    }
+                }
+                kind => self
+                    .encode_field(field.number, kind, value, out)
+                    .map_err(context)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn encode_field(
+        &self,
+        number: u32,
+        kind: &FieldKind,
+        value: &Value,
+        out: &mut Vec<u8>,
+    ) -> Result<(), String> {
+        write_tag(out, number, kind.wire_type());
+        match kind {
+            FieldKind::Message(name) => {
+                let mut nested = Vec::new();
+                self.encode_message(name, value, &mut nested)?;
+                write_bytes(out, &nested);
+            }
+            FieldKind::String => {
+                let text = value.as_str().ok_or("expected a string")?;
+                write_bytes(out, text.as_bytes());
+            }
+            FieldKind::Bytes => {
+                let text = value.as_str().ok_or("expected a base64 string")?;
+                write_bytes(out, &decode_base64(text)?);
+            }
+            kind => encode_scalar(kind, value, out, self)?,
+        }
+        Ok(())
+    }
+
+    /// Decode the protobuf message `message` into JSON. Fields use their
+    /// JSON names, 64-bit integers are strings and bytes are base64.
+    pub fn decode(&self, message: &str, bytes: &[u8]) -> Result<Value, String> {
+        let desc = self
+            .message(message)
+            .ok_or_else(|| format!("Unknown message type {}", message))?;
+        let mut object = Map::new();
+        let mut reader = Reader { bytes, pos: 0 };
+        while !reader.is_empty() {
+            let key = reader.varint()?;
+            let (number, wire) = ((key >> 3) as u32, (key & 7) as u8);
+            let Some(field) = desc.fields.iter().find(|field| field.number == number) else {
+                reader.skip(wire)?;
+                continue;
+            };
+            let name = field.json_name.clone();
+            match &field.kind {
+                FieldKind::Map(key_kind, value_kind) => {
+                    let entry = reader.len_delimited(wire)?;
+                    let (key, value) = self.decode_map_entry(key_kind, value_kind, entry)?;
+                    let map = object.entry(name).or_insert_with(|| json!({}));
+                    if let Value::Object(map) = map {
+                        map.insert(key, value);
+                    }
+                }
+                kind if field.repeated => {
+                    let mut values = Vec::new();
+                    if wire == WIRE_LEN && kind.is_packable() {
+                        let mut packed = Reader {
+                            bytes: reader.len_delimited(wire)?,
+                            pos: 0,
+                        };
+                        while !packed.is_empty() {
+                            values.push(self.decode_value(kind, kind.wire_type(), &mut packed)?);
+                        }
+                    } else {
+                        values.push(self.decode_value(kind, wire, &mut reader)?);
+                    }
+                    let list = object.entry(name).or_insert_with(|| json!([]));
+                    if let Value::Array(list) = list {
+                        list.extend(values);
+                    }
+                }
+                kind => {
+                    let value = self.decode_value(kind, wire, &mut reader)?;
+                    object.insert(name, value);
+                }
+            }
+        }
+        Ok(Value::Object(object))
+    }
+
+    fn decode_map_entry(
+        &self,
+        key_kind: &FieldKind,
+        value_kind: &FieldKind,
+        bytes: &[u8],
+    ) -> Result<(String, Value), String> {
+        let mut key = Value::Null;
+        let mut value = None;
+        let mut reader = Reader { bytes, pos: 0 };
+        while !reader.is_empty() {
+            let tag = reader.varint()?;
+            let wire = (tag & 7) as u8;
+            match tag >> 3 {
+                1 => key = self.decode_value(key_kind, wire, &mut reader)?,
+                2 => value = Some(self.decode_value(value_kind, wire, &mut reader)?),
+                _ => reader.skip(wire)?,
+            }
+        }
+        let key = match key {
+            Value::String(key) => key,
+            Value::Null => String::new(),
+            key => key.to_string(),
+        };
+        let value = value.unwrap_or_else(|| self.default_value(value_kind, &mut Vec::new()));
+        Ok((key, value))
+    }
+
+    fn decode_value(
+        &self,
+        kind: &FieldKind,
+        wire: u8,
+        reader: &mut Reader,
+    ) -> Result<Value, String> {
+        if wire != kind.wire_type() {
+            return Err(format!(
+                "Wire type {} does not match a {:?} field",
+                wire, kind
+            ));
+        }
+        Ok(match kind {
+            FieldKind::Int32 => json!(reader.varint()? as i32),
+            FieldKind::Int64 => json!((reader.varint()? as i64).to_string()),
+            FieldKind::Uint32 => json!(reader.varint()? as u32),
+            FieldKind::Uint64 => json!(reader.varint()?.to_string()),
+            FieldKind::Sint32 => json!(zigzag_decode(reader.varint()?) as i32),
+            FieldKind::Sint64 => json!(zigzag_decode(reader.varint()?).to_string()),
+            FieldKind::Bool => json!(reader.varint()? != 0),
+            FieldKind::Enum(name) => {
+                let number = reader.varint()? as i32;
+                match self.enums[name].iter().find(|(_, value)| *value == number) {
+                    Some((label, _)) => json!(label),
+                    None => json!(number),
+                }
+            }
+            FieldKind::Fixed64 => json!(u64::from_le_bytes(reader.array()?).to_string()),
+            FieldKind::Sfixed64 => json!(i64::from_le_bytes(reader.array()?).to_string()),
+            FieldKind::Double => float_json(f64::from_le_bytes(reader.array()?)),
+            FieldKind::Fixed32 => json!(u32::from_le_bytes(reader.array()?)),
+            FieldKind::Sfixed32 => json!(i32::from_le_bytes(reader.array()?)),
+            FieldKind::Float => float_json(f32::from_le_bytes(reader.array()?) as f64),
+            FieldKind::String => json!(String::from_utf8_lossy(reader.len_delimited(wire)?)),
+            FieldKind::Bytes => {
+                json!(base64::engine::general_purpose::STANDARD.encode(reader.len_delimited(wire)?))
+            }
+            FieldKind::Message(name) => self.decode(name, reader.len_delimited(wire)?)?,
+            FieldKind::Map(..) | FieldKind::Named(_) => {
+                return Err(format!("Unexpected field kind {:?}", kind))
+            }
+        })
+    }
+
+    /// Request body to start from: every field of the message with its
+    /// default value
+    pub fn template(&self, message: &str) -> Value {
+        self.default_value(&FieldKind::Message(message.to_string()), &mut Vec::new())
+    }
+
+    fn default_value(&self, kind: &FieldKind, open: &mut Vec<String>) -> Value {
+        match kind {
+            FieldKind::Message(name) => {
+                // Recursive messages are left empty the second time round
+                let Some(desc) = self.message(name).filter(|_| !open.contains(name)) else {
+                    return json!({});
+                };
+                open.push(name.clone());
+                let object = desc
+                    .fields
+                    .iter()
+                    .map(|field| {
+                        let value = match &field.kind {
+                            FieldKind::Map(..) => json!({}),
+                            _ if field.repeated => json!([]),
+                            kind => self.default_value(kind, open),
+                        };
+                        (field.json_name.clone(), value)
+                    })
+                    .collect();
+                open.pop();
+                Value::Object(object)
+            }
+            FieldKind::Enum(name) => self.enums[name]
+                .first()
+                .map(|(label, _)| json!(label))
+                .unwrap_or(json!(0)),
+            FieldKind::Int64
+            | FieldKind::Uint64
+            | FieldKind::Sint64
+            | FieldKind::Fixed64
+            | FieldKind::Sfixed64 => json!("0"),
+            FieldKind::Bool => json!(false),
+            FieldKind::String | FieldKind::Bytes => json!(""),
+            FieldKind::Map(..) => json!({}),
+            _ => json!(0),
+        }
+    }
+}
+
+const WIRE_VARINT: u8 = 0;
+const WIRE_FIXED64: u8 = 1;
+const WIRE_LEN: u8 = 2;
+const WIRE_START_GROUP: u8 = 3;
+const WIRE_FIXED32: u8 = 5;
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn write_tag(out: &mut Vec<u8>, number: u32, wire: u8) {
+    write_varint(out, ((number as u64) << 3) | wire as u64);
+}
+
+fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    write_varint(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+fn zigzag_encode(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn zigzag_decode(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+/// Write a number, bool or enum without its tag
+fn encode_scalar(
+    kind: &FieldKind,
+    value: &Value,
+    out: &mut Vec<u8>,
+    descriptors: &Descriptors,
+) -> Result<(), String> {
+    match kind {
+        FieldKind::Int32 | FieldKind::Int64 => write_varint(out, json_i64(value)? as u64),
+        FieldKind::Uint32 | FieldKind::Uint64 => write_varint(out, json_u64(value)?),
+        FieldKind::Sint32 | FieldKind::Sint64 => write_varint(out, zigzag_encode(json_i64(value)?)),
+        FieldKind::Bool => {
+            write_varint(out, value.as_bool().ok_or("expected true or false")? as u64)
+        }
+        FieldKind::Enum(name) => {
+            let values = &descriptors.enums[name];
+            let number = match value {
+                Value::String(label) => values
+                    .iter()
+                    .find(|(known, _)| known == label)
+                    .map(|(_, number)| *number)
+                    .ok_or_else(|| format!("unknown {} value '{}'", name, label))?,
+                value => json_i64(value)? as i32,
+            };
+            write_varint(out, number as i64 as u64);
+        }
+        FieldKind::Fixed64 => out.extend_from_slice(&json_u64(value)?.to_le_bytes()),
+        FieldKind::Sfixed64 => out.extend_from_slice(&json_i64(value)?.to_le_bytes()),
+        FieldKind::Double => out.extend_from_slice(&json_f64(value)?.to_le_bytes()),
+        FieldKind::Fixed32 => out.extend_from_slice(&(json_u64(value)? as u32).to_le_bytes()),
+        FieldKind::Sfixed32 => out.extend_from_slice(&(json_i64(value)? as i32).to_le_bytes()),
+        FieldKind::Float => out.extend_from_slice(&(json_f64(value)? as f32).to_le_bytes()),
+        kind => return Err(format!("{:?} is not a scalar", kind)),
+    }
+    Ok(())
+}
+
+/// Integers may be JSON numbers or strings, as 64-bit values usually are
+fn json_i64(value: &Value) -> Result<i64, String> {
+    match value {
+        Value::Number(number) => number.as_i64(),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| format!("expected an integer, got {}", value))
+}
+
+fn json_u64(value: &Value) -> Result<u64, String> {
+    match value {
+        Value::Number(number) => number.as_u64(),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| format!("expected an unsigned integer, got {}", value))
+}
+
+fn json_f64(value: &Value) -> Result<f64, String> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => match text.as_str() {
+            "NaN" => Some(f64::NAN),
+            "Infinity" => Some(f64::INFINITY),
+            "-Infinity" => Some(f64::NEG_INFINITY),
+            text => text.trim().parse().ok(),
+        },
+        _ => None,
+    }
+    .ok_or_else(|| format!("expected a number, got {}", value))
+}
+
+fn float_json(value: f64) -> Value {
+    match serde_json::Number::from_f64(value) {
+        Some(number) => Value::Number(number),
+        None if value.is_nan() => json!("NaN"),
+        None if value > 0.0 => json!("Infinity"),
+        None => json!("-Infinity"),
+    }
+}
+
+fn decode_base64(text: &str) -> Result<Vec<u8>, String> {
+    use base64::engine::general_purpose::{STANDARD, URL_SAFE};
+    let text = text.trim_end_matches('=');
+    let pad = "=".repeat((4 - text.len() % 4) % 4);
+    let padded = format!("{}{}", text, pad);
+    STANDARD
+        .decode(&padded)
+        .or_else(|_| URL_SAFE.decode(&padded))
+        .map_err(|e| format!("invalid base64: {}", e))
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn is_empty(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or("Message ends in the middle of a field")?;
+        let bytes = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("Varint is longer than 10 bytes".to_string())
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn len_delimited(&mut self, wire: u8) -> Result<&'a [u8], String> {
+        if wire != WIRE_LEN {
+            return Err(format!("Wire type {} is not length-delimited", wire));
+        }
+        let len = self.varint()? as usize;
+        self.take(len)
+    }
+
+    /// Skip a field the descriptors do not know about
+    fn skip(&mut self, wire: u8) -> Result<(), String> {
+        match wire {
+            WIRE_VARINT => self.varint().map(drop),
+            WIRE_FIXED64 => self.take(8).map(drop),
+            WIRE_LEN => self.len_delimited(wire).map(drop),
+            WIRE_FIXED32 => self.take(4).map(drop),
+            WIRE_START_GROUP => Err("Groups are not supported".to_string()),
+            wire => Err(format!("Invalid wire type {}", wire)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// Identifier, dotted name or number
+    Word(String),
+    Str(String),
+    Symbol(char),
+}
+
+type ParseError = (usize, String);
+
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    let mut line = 1;
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            c if c.is_whitespace() => {}
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        line += 1;
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last = ' ';
+                loop {
+                    match chars.next() {
+                        Some('/') if last == '*' => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            last = c;
+                        }
+                        None => return Err((line, "Unterminated comment".to_string())),
+                    }
+                }
+            }
+            '"' | '\'' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some('\\') => text.extend(chars.next()),
+                        Some(end) if end == c => break,
+                        Some('\n') | None => return Err((line, "Unterminated string".to_string())),
+                        Some(c) => text.push(c),
+                    }
+                }
+                tokens.push((Token::Str(text), line));
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '.' => {
+                let mut word = c.to_string();
+                while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || matches!(c, '_' | '.'))
+                {
+                    word.push(c);
+                }
+                tokens.push((Token::Word(word), line));
+            }
+            c => tokens.push((Token::Symbol(c), line)),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    package: String,
+    proto3: bool,
+    imports: Vec<String>,
+    descriptors: &'a mut Descriptors,
+}
+
+impl Parser<'_> {
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or(self.tokens.last())
+            .map(|(_, line)| *line)
+            .unwrap_or(1)
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, ParseError> {
+        Err((self.line(), message.into()))
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).map(|(token, _)| token.clone());
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, symbol: char) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), ParseError> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            self.error(format!("expected '{}'", symbol))
+        }
+    }
+
+    fn word(&mut self) -> Result<String, ParseError> {
+        match self.next() {
+            Some(Token::Word(word)) => Ok(word),
+            _ => {
+                self.pos -= 1;
+                self.error("expected a name")
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        match self.next() {
+            Some(Token::Str(text)) => Ok(text),
+            _ => {
+                self.pos -= 1;
+                self.error("expected a string")
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<i64, ParseError> {
+        let negative = self.eat('-');
+        let word = self.word()?;
+        let value = match word.strip_prefix("0x").or(word.strip_prefix("0X")) {
+            Some(hex) => i64::from_str_radix(hex, 16).ok(),
+            None => word.parse().ok(),
+        };
+        match value {
+            Some(value) if negative => Ok(-value),
+            Some(value) => Ok(value),
+            None => self.error(format!("expected a number, got '{}'", word)),
+        }
+    }
+
+    /// Skip to the end of a statement such as an option, including any
+    /// `{ ... }` aggregate value in it
+    fn skip_statement(&mut self) -> Result<(), ParseError> {
+        let mut depth = 0;
+        loop {
+            match self.next() {
+                Some(Token::Symbol('{')) => depth += 1,
+                Some(Token::Symbol('}')) if depth > 0 => {
+                    depth -= 1;
+                    if depth == 0 && self.peek() != Some(&Token::Symbol(';')) {
+                        return Ok(());
+                    }
+                }
+                Some(Token::Symbol(';')) if depth == 0 => return Ok(()),
+                Some(_) => {}
+                None => return self.error("unexpected end of file"),
+            }
+        }
+    }
+
+    /// Option name, either plain or a custom `(full.name)` with an optional
+    /// `.field` path after it
+    fn option_name(&mut self) -> Result<String, ParseError> {
+        if !self.eat('(') {
+            return self.word();
+        }
+        let mut name = format!("({})", self.word()?);
+        self.expect(')')?;
+        if let Some(Token::Word(path)) = self.peek() {
+            if path.starts_with('.') {
+                name.push_str(path);
+                self.pos += 1;
+            }
+        }
+        Ok(name)
+    }
+
+    /// Skip a field option value up to the `,` or `]` after it, including
+    /// negative numbers and `{ ... }` aggregates with lists inside
+    fn skip_option_value(&mut self) -> Result<(), ParseError> {
+        let mut depth = 0usize;
+        loop {
+            match self.peek() {
+                Some(Token::Symbol(',' | ']')) if depth == 0 => return Ok(()),
+                Some(Token::Symbol('{' | '[' | '(')) => depth += 1,
+                Some(Token::Symbol('}' | ']' | ')')) => match depth.checked_sub(1) {
+                    Some(outer) => depth = outer,
+                    None => return self.error("unbalanced brackets in option value"),
+                },
+                Some(Token::Symbol(';')) if depth == 0 => return self.error("expected ']'"),
+                Some(_) => {}
+                None => return self.error("unexpected end of file"),
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// Skip a `{ ... }` block such as an `extend`
+    fn skip_block(&mut self) -> Result<(), ParseError> {
+        while !self.eat('{') {
+            if self.next().is_none() {
+                return self.error("unexpected end of file");
+            }
+        }
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next() {
+                Some(Token::Symbol('{')) => depth += 1,
+                Some(Token::Symbol('}')) => depth -= 1,
+                Some(_) => {}
+                None => return self.error("unexpected end of file"),
+            }
+        }
+        Ok(())
+    }
+
+    fn file(&mut self) -> Result<(), ParseError> {
+        while let Some(token) = self.next() {
+            let Token::Word(keyword) = token else {
+                if token == Token::Symbol(';') {
+                    continue;
+                }
+                self.pos -= 1;
+                return self.error("expected a declaration");
+            };
+            match keyword.as_str() {
+                "syntax" | "edition" => {
+                    self.expect('=')?;
+                    self.proto3 = self.string()? != "proto2";
+                    self.expect(';')?;
+                }
+                "package" => {
+                    self.package = self.word()?;
+                    self.expect(';')?;
+                }
+                "import" => {
+                    if matches!(self.peek(), Some(Token::Word(word)) if word == "public" || word == "weak")
+                    {
+                        self.pos += 1;
+                    }
+                    let import = self.string()?;
+                    self.imports.push(import);
+                    self.expect(';')?;
+                }
+                "option" => self.skip_statement()?,
+                "message" => {
+                    let scope = self.package.clone();
+                    self.message(&scope)?;
+                }
+                "enum" => {
+                    let scope = self.package.clone();
+                    self.enumeration(&scope)?;
+                }
+                "service" => self.service()?,
+                "extend" => self.skip_block()?,
+                other => return self.error(format!("unexpected '{}'", other)),
+            }
+        }
+        Ok(())
+    }
+
+    fn message(&mut self, scope: &str) -> Result<(), ParseError> {
+        let name = qualify(scope, &self.word()?);
+        self.expect('{')?;
+        let mut fields = Vec::new();
+        self.message_body(&name, &mut fields)?;
+        self.descriptors
+            .messages
+            .insert(name.clone(), MessageDesc { name, fields });
+        Ok(())
+    }
+
+    /// Fields and nested declarations up to the closing brace; oneof bodies
+    /// add their fields to the enclosing message
+    fn message_body(&mut self, name: &str, fields: &mut Vec<FieldDesc>) -> Result<(), ParseError> {
+        loop {
+            let keyword = match self.next() {
+                Some(Token::Symbol('}')) => return Ok(()),
+                Some(Token::Symbol(';')) => continue,
+                Some(Token::Word(word)) => word,
+                _ => {
+                    self.pos = self.pos.saturating_sub(1);
+                    return self.error("expected a field or '}'");
+                }
+            };
+            match keyword.as_str() {
+                "message" => self.message(name)?,
+                "enum" => self.enumeration(name)?,
+                "oneof" => {
+                    self.word()?;
+                    self.expect('{')?;
+                    self.message_body(name, fields)?;
+                }
+                "option" | "reserved" | "extensions" => self.skip_statement()?,
+                "extend" => self.skip_block()?,
+                "group" => return self.error("groups are not supported"),
+                "repeated" => {
+                    let type_name = self.word()?;
+                    fields.push(self.field(&type_name, true)?);
+                }
+                "optional" | "required" => {
+                    let type_name = self.word()?;
+                    fields.push(self.field(&type_name, false)?);
+                }
+                "map" => {
+                    self.expect('<')?;
+                    let key = self.word()?;
+                    self.expect(',')?;
+                    let value = self.word()?;
+                    self.expect('>')?;
+                    let mut field = self.field(&value, false)?;
+                    let key = match FieldKind::scalar(&key) {
+                        Some(key) => key,
+                        None => return self.error(format!("invalid map key type '{}'", key)),
+                    };
+                    field.kind = FieldKind::Map(Box::new(key), Box::new(field.kind));
+                    fields.push(field);
+                }
+                type_name => fields.push(self.field(type_name, false)?),
+            }
+        }
+    }
+
+    /// `name = number [options];` after the field type
+    fn field(&mut self, type_name: &str, repeated: bool) -> Result<FieldDesc, ParseError> {
+        let name = self.word()?;
+        self.expect('=')?;
+        let number = self.number()?;
+        let mut json_name = lower_camel_case(&name);
+        let mut packed = None;
+        if self.eat('[') {
+            loop {
+                let option = self.option_name()?;
+                self.expect('=')?;
+                match (option.as_str(), self.peek()) {
+                    ("json_name", Some(Token::Str(value))) => json_name = value.clone(),
+                    ("packed", Some(Token::Word(value))) => packed = Some(value == "true"),
+                    _ => {}
+                }
+                self.skip_option_value()?;
+                if self.eat(']') {
+                    break;
+                }
+                self.expect(',')?;
+            }
+        }
+        self.expect(';')?;
+        let kind =
+            FieldKind::scalar(type_name).unwrap_or_else(|| FieldKind::Named(type_name.to_string()));
+        Ok(FieldDesc {
+            name,
+            json_name,
+            number: number as u32,
+            // Checked against the resolved type in `resolve`
+            packed: repeated && packed.unwrap_or(self.proto3),
+            kind,
+            repeated,
+        })
+    }
+
+    fn enumeration(&mut self, scope: &str) -> Result<(), ParseError> {
+        let name = qualify(scope, &self.word()?);
+        self.expect('{')?;
+        let mut values = Vec::new();
+        loop {
+            match self.next() {
+                Some(Token::Symbol('}')) => break,
+                Some(Token::Symbol(';')) => {}
+                Some(Token::Word(word)) if word == "option" || word == "reserved" => {
+                    self.skip_statement()?
+                }
+                Some(Token::Word(label)) => {
+                    self.expect('=')?;
+                    let number = self.number()?;
+                    values.push((label, number as i32));
+                    self.skip_statement()?;
+                }
+                _ => {
+                    self.pos -= 1;
+                    return self.error("expected an enum value or '}'");
+                }
+            }
+        }
+        self.descriptors.enums.insert(name, values);
+        Ok(())
+    }
+
+    fn service(&mut self) -> Result<(), ParseError> {
+        let name = self.word()?;
+        let service = qualify(&self.package, &name);
+        self.expect('{')?;
+        loop {
+            match self.next() {
+                Some(Token::Symbol('}')) => return Ok(()),
+                Some(Token::Symbol(';')) => {}
+                Some(Token::Word(word)) if word == "option" => self.skip_statement()?,
+                Some(Token::Word(word)) if word == "rpc" => {
+                    let name = self.word()?;
+                    let (client_streaming, input) = self.rpc_type()?;
+                    if self.word()? != "returns" {
+                        self.pos -= 1;
+                        return self.error("expected 'returns'");
+                    }
+                    let (server_streaming, output) = self.rpc_type()?;
+                    if self.peek() == Some(&Token::Symbol('{')) {
+                        self.skip_block()?;
+                    } else {
+                        self.expect(';')?;
+                    }
+                    self.descriptors.methods.push(MethodDesc {
+                        service: service.clone(),
+                        name,
+                        input,
+                        output,
+                        client_streaming,
+                        server_streaming,
+                    });
+                }
+                _ => {
+                    self.pos -= 1;
+                    return self.error("expected 'rpc' or '}'");
+                }
+            }
+        }
+    }
+
+    /// `(stream Type)` of an rpc
+    fn rpc_type(&mut self) -> Result<(bool, String), ParseError> {
+        self.expect('(')?;
+        let mut name = self.word()?;
+        let streaming = name == "stream" && self.peek() != Some(&Token::Symbol(')'));
+        if streaming {
+            name = self.word()?;
+        }
+        self.expect(')')?;
+        Ok((streaming, name))
+    }
+}
+
+fn qualify(scope: &str, name: &str) -> String {
+    if scope.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", scope, name)
+    }
+}
+
+/// JSON name protoc derives from a field name: `user_id` becomes `userId`
+fn lower_camel_case(name: &str) -> String {
+    let mut out = String::new();
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            out.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests;
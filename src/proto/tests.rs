@@ -0,0 +1,330 @@
+use super::*;
+
+const SHOP: &str = r#"
+syntax = "proto3";
+package shop.v1;
+
+import "google/protobuf/timestamp.proto";
+
+option go_package = "example.com/shop;shop";
+
+// An order
+message Order {
+  int32 id = 1;
+  string customer_name = 2;
+  repeated int32 quantities = 3;
+  Status status = 4;
+  map<string, Item> items = 5;
+  google.protobuf.Timestamp created = 6;
+  oneof payment {
+    string card = 7;
+    bytes token = 8 [json_name = "paymentToken"];
+  }
+  sint64 balance = 9;
+  repeated Note notes = 10;
+
+  message Item {
+    double price = 1;
+    reserved 2, 3;
+  }
+}
+
+message Note { string text = 1; Order parent = 2; }
+
+enum Status {
+  option allow_alias = true;
+  STATUS_UNKNOWN = 0;
+  STATUS_OPEN = 1 [deprecated = true];
+  STATUS_CLOSED = -1;
+}
+
+message GetOrderRequest { int32 id = 1; }
+
+service Orders {
+  option (google.api.default_host) = { host: "shop" };
+  rpc GetOrder(GetOrderRequest) returns (Order);
+  rpc Watch(GetOrderRequest) returns (stream Order) {
+    option idempotency_level = NO_SIDE_EFFECTS;
+  }
+  rpc Upload(stream Order) returns (.shop.v1.GetOrderRequest);
+}
+"#;
+
+fn descriptors() -> Descriptors {
+    let mut descriptors = Descriptors::default();
+    let imports = descriptors.parse("shop.proto", SHOP).unwrap();
+    assert_eq!(imports, vec!["google/protobuf/timestamp.proto".to_string()]);
+    let (_, timestamp) = WELL_KNOWN
+        .iter()
+        .find(|(name, _)| name.ends_with("timestamp.proto"))
+        .unwrap();
+    descriptors.parse("timestamp.proto", timestamp).unwrap();
+    descriptors.resolve().unwrap();
+    descriptors
+}
+
+#[test]
+fn test_parse_messages_and_services() {
+    let descriptors = descriptors();
+    let order = descriptors.message("shop.v1.Order").unwrap();
+    let kinds: Vec<(&str, &FieldKind)> = order
+        .fields
+        .iter()
+        .map(|field| (field.json_name.as_str(), &field.kind))
+        .collect();
+    assert_eq!(kinds[1], ("customerName", &FieldKind::String));
+    assert_eq!(
+        kinds[3],
+        ("status", &FieldKind::Enum("shop.v1.Status".into()))
+    );
+    assert_eq!(
+        kinds[4].1,
+        &FieldKind::Map(
+            Box::new(FieldKind::String),
+            Box::new(FieldKind::Message("shop.v1.Order.Item".into()))
+        )
+    );
+    assert_eq!(
+        kinds[5].1,
+        &FieldKind::Message("google.protobuf.Timestamp".into())
+    );
+    assert_eq!(kinds[7].0, "paymentToken");
+    assert!(order.fields[2].packed);
+    assert!(!order.fields[9].packed);
+
+    let paths: Vec<String> = descriptors.methods().iter().map(MethodDesc::path).collect();
+    assert_eq!(
+        paths,
+        vec![
+            "/shop.v1.Orders/GetOrder",
+            "/shop.v1.Orders/Watch",
+            "/shop.v1.Orders/Upload"
+        ]
+    );
+    let upload = descriptors.method("/shop.v1.Orders/Upload").unwrap();
+    assert_eq!(upload.input, "shop.v1.Order");
+    assert_eq!(upload.output, "shop.v1.GetOrderRequest");
+    assert_eq!(upload.kind(), "client streaming");
+    assert_eq!(descriptors.methods()[1].kind(), "server streaming");
+}
+
+#[test]
+fn test_parse_skips_field_option_values() {
+    let mut descriptors = Descriptors::default();
+    descriptors
+        .parse(
+            "options.proto",
+            r#"
+            syntax = "proto2";
+            message Options {
+              required string name = 1 [(google.api.field_behavior) = REQUIRED];
+              optional int32 limit = 2 [default = -1, json_name = "max"];
+              repeated int32 ids = 3 [
+                (validate.rules).repeated = { min_items: 1, items: { int32: { in: [1, 2] } } },
+                packed = true
+              ];
+              optional string note = 4 [(a.b).c = "x", deprecated = true];
+            }
+            "#,
+        )
+        .unwrap();
+
+    let fields = &descriptors.message("Options").unwrap().fields;
+    let summary: Vec<(&str, &str, u32, bool)> = fields
+        .iter()
+        .map(|field| {
+            (
+                field.name.as_str(),
+                field.json_name.as_str(),
+                field.number,
+                field.packed,
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("name", "name", 1, false),
+            ("limit", "max", 2, false),
+            ("ids", "ids", 3, true),
+            ("note", "note", 4, false),
+        ]
+    );
+}
+
+#[test]
+fn test_parse_rejects_unbalanced_option_value() {
+    let mut descriptors = Descriptors::default();
+    let error = descriptors
+        .parse(
+            "bad.proto",
+            "message A {\n  int32 id = 1 [(x) = { a: 1 ];\n}",
+        )
+        .unwrap_err();
+
+    assert_eq!(error, "bad.proto:2: expected ']'");
+}
+
+#[test]
+fn test_parse_errors_name_the_line() {
+    let mut descriptors = Descriptors::default();
+    let error = descriptors
+        .parse(
+            "bad.proto",
+            "syntax = \"proto3\";\nmessage A {\n  int32 id 1;\n}",
+        )
+        .unwrap_err();
+    assert_eq!(error, "bad.proto:3: expected '='");
+
+    let mut descriptors = Descriptors::default();
+    descriptors
+        .parse("a.proto", "message A { Missing m = 1; }")
+        .unwrap();
+    assert_eq!(
+        descriptors.resolve(),
+        Err("Unknown type Missing in field A.m".to_string())
+    );
+}
+
+#[test]
+fn test_encode_matches_wire_format() {
+    let descriptors = descriptors();
+    let bytes = descriptors
+        .encode("shop.v1.GetOrderRequest", &json!({ "id": 150 }))
+        .unwrap();
+    assert_eq!(bytes, vec![0x08, 0x96, 0x01]);
+
+    // Packed repeated field, a zigzag sint64 and a negative enum
+    let bytes = descriptors
+        .encode(
+            "shop.v1.Order",
+            &json!({ "quantities": [1, 2], "balance": "-2", "status": "STATUS_CLOSED" }),
+        )
+        .unwrap();
+    let mut expected = vec![0x48, 0x03, 0x1a, 0x02, 0x01, 0x02, 0x20];
+    expected.extend([0xff; 9]);
+    expected.push(0x01);
+    assert_eq!(bytes, expected);
+}
+
+#[test]
+fn test_round_trip() {
+    let descriptors = descriptors();
+    let order = json!({
+        "id": 7,
+        "customerName": "Ada",
+        "quantities": [3, 0, 1],
+        "status": "STATUS_OPEN",
+        "items": { "apple": { "price": 1.5 }, "pear": {} },
+        "created": { "seconds": "1700000000", "nanos": 5 },
+        "paymentToken": "AAEC/w==",
+        "balance": "-42",
+        "notes": [{ "text": "a" }, { "text": "b", "parent": { "id": 1 } }]
+    });
+    let bytes = descriptors.encode("shop.v1.Order", &order).unwrap();
+    assert_eq!(descriptors.decode("shop.v1.Order", &bytes).unwrap(), order);
+}
+
+#[test]
+fn test_encode_accepts_proto_names_and_numbers() {
+    let descriptors = descriptors();
+    let bytes = descriptors
+        .encode(
+            "shop.v1.Order",
+            &json!({ "customer_name": "x", "status": 1, "id": "3", "card": null }),
+        )
+        .unwrap();
+    assert_eq!(
+        descriptors.decode("shop.v1.Order", &bytes).unwrap(),
+        json!({ "customerName": "x", "status": "STATUS_OPEN", "id": 3 })
+    );
+}
+
+#[test]
+fn test_encode_errors() {
+    let descriptors = descriptors();
+    let encode = |value| descriptors.encode("shop.v1.Order", &value).unwrap_err();
+    assert_eq!(
+        encode(json!({ "nope": 1 })),
+        "Unknown field 'nope' in shop.v1.Order"
+    );
+    assert_eq!(
+        encode(json!({ "id": "seven" })),
+        "shop.v1.Order.id: expected an integer, got \"seven\""
+    );
+    assert_eq!(
+        encode(json!({ "status": "STATUS_LOST" })),
+        "shop.v1.Order.status: unknown shop.v1.Status value 'STATUS_LOST'"
+    );
+    assert_eq!(
+        encode(json!([1])),
+        "Expected a JSON object for shop.v1.Order"
+    );
+}
+
+#[test]
+fn test_decode_skips_unknown_fields_and_rejects_truncation() {
+    let descriptors = descriptors();
+    // Field 15 (varint) is not in the schema
+    let bytes = [0x78, 0x01, 0x08, 0x05];
+    assert_eq!(
+        descriptors
+            .decode("shop.v1.GetOrderRequest", &bytes)
+            .unwrap(),
+        json!({ "id": 5 })
+    );
+    assert_eq!(
+        descriptors.decode("shop.v1.Order", &[0x12, 0x05, b'a']),
+        Err("Message ends in the middle of a field".to_string())
+    );
+}
+
+#[test]
+fn test_template_has_every_field() {
+    let descriptors = descriptors();
+    assert_eq!(
+        descriptors.template("shop.v1.Note"),
+        json!({
+            "text": "",
+            "parent": {
+                "id": 0,
+                "customerName": "",
+                "quantities": [],
+                "status": "STATUS_UNKNOWN",
+                "items": {},
+                "created": { "seconds": "0", "nanos": 0 },
+                "card": "",
+                "paymentToken": "",
+                "balance": "0",
+                "notes": []
+            }
+        })
+    );
+}
+
+#[test]
+fn test_load_resolves_imports_from_disk() {
+    let dir = std::env::temp_dir().join(format!("jorna-proto-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("common")).unwrap();
+    std::fs::write(
+        dir.join("common/money.proto"),
+        "syntax = \"proto3\"; package common; message Money { int64 cents = 1; }",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("pay.proto"),
+        "syntax = \"proto3\"; package pay; import \"common/money.proto\";
+         import \"google/protobuf/empty.proto\";
+         service Pay { rpc Charge(common.Money) returns (google.protobuf.Empty); }",
+    )
+    .unwrap();
+
+    let descriptors = Descriptors::load(&[dir.join("pay.proto")]).unwrap();
+    let method = descriptors.method("/pay.Pay/Charge").unwrap();
+    assert_eq!(method.input, "common.Money");
+    assert_eq!(method.output, "google.protobuf.Empty");
+
+    let error = Descriptors::load(&[dir.join("missing.proto")]).unwrap_err();
+    assert!(error.starts_with("Failed to read"));
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,310 @@
+use std::io::{Read, Write};
+use std::net::IpAddr;
+
+use anyhow::{anyhow, Context};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use percent_encoding::percent_decode_str;
+use reqwest::blocking::ClientBuilder;
+use reqwest::{NoProxy, Proxy, Url};
+
+/// Longest reply to `CONNECT` read before giving up
+const MAX_CONNECT_REPLY: usize = 8 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProxyMode {
+    /// Honour `HTTP_PROXY`, `HTTPS_PROXY`, `ALL_PROXY` and `NO_PROXY`
+    System,
+    /// Use the proxies configured in the settings
+    Manual,
+    /// Always connect directly
+    Off,
+}
+
+impl ProxyMode {
+    pub const ALL: &'static [ProxyMode] = &[ProxyMode::System, ProxyMode::Manual, ProxyMode::Off];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ProxyMode::System => "system",
+            ProxyMode::Manual => "manual",
+            ProxyMode::Off => "off",
+        }
+    }
+
+    pub fn next(&self) -> ProxyMode {
+        let index = Self::ALL.iter().position(|m| m == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn parse(value: &str) -> Result<ProxyMode, String> {
+        Self::ALL
+            .iter()
+            .find(|m| m.label().eq_ignore_ascii_case(value.trim()))
+            .copied()
+            .ok_or_else(|| format!("Expected system, manual or off, got '{}'", value))
+    }
+}
+
+/// Proxy options applied when building the HTTP client.
+///
+/// Proxy URLs may use the `http`, `https`, `socks5` or `socks5h` scheme.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProxySettings {
+    pub mode: ProxyMode,
+    /// Proxy for plain http requests
+    pub http: String,
+    /// Proxy for https requests
+    pub https: String,
+    pub username: String,
+    pub password: String,
+    /// Comma-separated hosts, domains or CIDR ranges that bypass the proxy
+    pub no_proxy: String,
+}
+
+impl Default for ProxySettings {
+    fn default() -> Self {
+        Self {
+            mode: ProxyMode::System,
+            http: String::new(),
+            https: String::new(),
+            username: String::new(),
+            password: String::new(),
+            no_proxy: String::new(),
+        }
+    }
+}
+
+impl ProxySettings {
+    /// Proxy a request to `url` would go through, with credentials removed
+    pub fn active_proxy(&self, url: &str) -> Option<String> {
+        self.proxy_for(url).map(|proxy| redact(&proxy))
+    }
+
+    /// Proxy a connection to `url` would go through, as configured
+    pub fn proxy_for(&self, url: &str) -> Option<String> {
+        self.active_proxy_with(url, |name| std::env::var(name).ok())
+    }
+
+    fn active_proxy_with(&self, url: &str, env: impl Fn(&str) -> Option<String>) -> Option<String> {
+        let url = Url::parse(url).ok()?;
+        let host = url.host_str()?;
+        let env = |upper: &str| {
+            env(upper)
+                .or_else(|| env(&upper.to_ascii_lowercase()))
+                .filter(|value| !value.is_empty())
+        };
+
+        let (proxy, no_proxy) = match self.mode {
+            ProxyMode::Off => return None,
+            ProxyMode::Manual => {
+                let proxy = match url.scheme() {
+                    "https" | "wss" => &self.https,
+                    _ => &self.http,
+                };
+                (Some(proxy.clone()), self.no_proxy.clone())
+            }
+            ProxyMode::System => {
+                let proxy = match url.scheme() {
+                    "https" | "wss" => env("HTTPS_PROXY"),
+                    _ => env("HTTP_PROXY"),
+                }
+                .or_else(|| env("ALL_PROXY"));
+                (proxy, env("NO_PROXY").unwrap_or_default())
+            }
+        };
+
+        let proxy = proxy.filter(|proxy| !proxy.is_empty())?;
+        if bypasses_proxy(host, &no_proxy) {
+            return None;
+        }
+        Some(proxy)
+    }
+}
+
+/// Host and port of `proxy` for connections reqwest does not make, such as
+/// WebSockets, which are tunnelled with [`tunnel`]. Only `http://` proxies
+/// can do that.
+pub fn http_proxy_address(proxy: &str) -> anyhow::Result<(String, u16)> {
+    let url = parse_proxy(proxy)?;
+    if url.scheme() != "http" {
+        return Err(anyhow!(
+            "Only http:// proxies can tunnel this connection, not {}",
+            redact(proxy)
+        ));
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| anyhow!("Missing host in proxy {}", redact(proxy)))?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    Ok((host.to_string(), url.port_or_known_default().unwrap_or(80)))
+}
+
+/// Proxy URL, read as `http://` when it has no scheme like reqwest does
+fn parse_proxy(proxy: &str) -> anyhow::Result<Url> {
+    let proxy = if proxy.contains("://") {
+        proxy.to_string()
+    } else {
+        format!("http://{}", proxy)
+    };
+    Url::parse(&proxy).with_context(|| format!("Invalid proxy {}", redact(&proxy)))
+}
+
+/// Ask the HTTP proxy at the other end of `stream` to open a tunnel to
+/// `host:port` with `CONNECT`
+pub fn tunnel(
+    stream: &mut (impl Read + Write),
+    proxy: &str,
+    settings: &ProxySettings,
+    host: &str,
+    port: u16,
+) -> anyhow::Result<()> {
+    let url = parse_proxy(proxy)?;
+    let target = if host.contains(':') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    };
+
+    let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", target);
+    let credentials = if settings.mode == ProxyMode::Manual && !settings.username.is_empty() {
+        Some((settings.username.clone(), settings.password.clone()))
+    } else if !url.username().is_empty() {
+        let decode = |part: &str| percent_decode_str(part).decode_utf8_lossy().into_owned();
+        Some((
+            decode(url.username()),
+            decode(url.password().unwrap_or_default()),
+        ))
+    } else {
+        None
+    };
+    if let Some((username, password)) = credentials {
+        let token = STANDARD.encode(format!("{}:{}", username, password));
+        request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", token));
+    }
+    request.push_str("\r\n");
+    stream
+        .write_all(request.as_bytes())
+        .context("Failed to send CONNECT to the proxy")?;
+
+    // Read the reply byte by byte so nothing sent through the tunnel is lost
+    let mut head = Vec::new();
+    let mut byte = [0; 1];
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() > MAX_CONNECT_REPLY {
+            return Err(anyhow!("Proxy reply to CONNECT is too long"));
+        }
+        match stream.read(&mut byte) {
+            Ok(0) => return Err(anyhow!("Proxy closed the connection during CONNECT")),
+            Ok(_) => head.push(byte[0]),
+            Err(e) => return Err(e).context("Failed to read the proxy reply to CONNECT"),
+        }
+    }
+    let head = String::from_utf8_lossy(&head);
+    let status_line = head.lines().next().unwrap_or_default();
+    match status_line.split_whitespace().nth(1) {
+        Some(status) if status.starts_with('2') => Ok(()),
+        _ => Err(anyhow!(
+            "Proxy refused to connect to {}: {}",
+            target,
+            status_line
+        )),
+    }
+}
+
+/// Apply the proxy settings to a client builder
+pub fn configure(
+    mut builder: ClientBuilder,
+    settings: &ProxySettings,
+) -> anyhow::Result<ClientBuilder> {
+    match settings.mode {
+        ProxyMode::System => Ok(builder),
+        ProxyMode::Off => Ok(builder.no_proxy()),
+        ProxyMode::Manual => {
+            if settings.http.is_empty() && settings.https.is_empty() {
+                return Err(anyhow!(
+                    "Manual proxy mode needs an HTTP or HTTPS proxy URL"
+                ));
+            }
+            if !settings.http.is_empty() {
+                let proxy = Proxy::http(&settings.http)
+                    .with_context(|| format!("Invalid HTTP proxy {}", settings.http))?;
+                builder = builder.proxy(with_options(proxy, settings));
+            }
+            if !settings.https.is_empty() {
+                let proxy = Proxy::https(&settings.https)
+                    .with_context(|| format!("Invalid HTTPS proxy {}", settings.https))?;
+                builder = builder.proxy(with_options(proxy, settings));
+            }
+            Ok(builder)
+        }
+    }
+}
+
+fn with_options(mut proxy: Proxy, settings: &ProxySettings) -> Proxy {
+    if !settings.username.is_empty() {
+        proxy = proxy.basic_auth(&settings.username, &settings.password);
+    }
+    proxy.no_proxy(NoProxy::from_string(&settings.no_proxy))
+}
+
+/// Whether `host` matches a `NO_PROXY` style list, using the same rules as
+/// reqwest: `*` matches everything, IPs match exactly or by CIDR range, and
+/// domains match themselves and their subdomains.
+pub fn bypasses_proxy(host: &str, no_proxy: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let ip = host.parse::<IpAddr>().ok();
+
+    no_proxy
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| {
+            if entry == "*" {
+                return true;
+            }
+            if let Some(ip) = ip {
+                return match entry.split_once('/') {
+                    Some((network, prefix)) => in_cidr(ip, network, prefix),
+                    None => entry.parse::<IpAddr>().ok() == Some(ip),
+                };
+            }
+            let domain = entry.trim_start_matches('.');
+            host.eq_ignore_ascii_case(domain)
+                || host
+                    .to_ascii_lowercase()
+                    .ends_with(&format!(".{}", domain.to_ascii_lowercase()))
+        })
+}
+
+fn in_cidr(ip: IpAddr, network: &str, prefix: &str) -> bool {
+    let (Ok(network), Ok(prefix)) = (network.parse::<IpAddr>(), prefix.parse::<u32>()) else {
+        return false;
+    };
+    match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) if prefix <= 32 => {
+            let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(network) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(network)) if prefix <= 128 => {
+            let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(network) & mask
+        }
+        _ => false,
+    }
+}
+
+/// Strip any credentials from a proxy URL so it is safe to display
+fn redact(proxy: &str) -> String {
+    match Url::parse(proxy) {
+        Ok(mut url) if url.password().is_some() || !url.username().is_empty() => {
+            let _ = url.set_username("");
+            let _ = url.set_password(None);
+            url.to_string().trim_end_matches('/').to_string()
+        }
+        _ => proxy.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,85 @@
+use ratatui::text::{Line, Text};
+use ratatui::widgets::{Paragraph, Wrap};
+use regex::RegexBuilder;
+
+/// One match of the search pattern, as byte offsets within a line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchMatch {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Find every match of the regex `pattern` in `text`, line by line.
+///
+/// Matches never span lines and empty matches are skipped.
+pub fn find_matches(
+    text: &str,
+    pattern: &str,
+    case_sensitive: bool,
+) -> Result<Vec<SearchMatch>, String> {
+    if pattern.is_empty() {
+        return Ok(Vec::new());
+    }
+    let regex = RegexBuilder::new(pattern)
+        .case_insensitive(!case_sensitive)
+        .build()
+        .map_err(|e| match e {
+            regex::Error::Syntax(message) => message
+                .lines()
+                .last()
+                .unwrap_or("Invalid regex")
+                .trim_start_matches("error: ")
+                .to_string(),
+            other => other.to_string(),
+        })?;
+
+    Ok(text
+        .lines()
+        .enumerate()
+        .flat_map(|(line, content)| {
+            regex
+                .find_iter(content)
+                .filter(|found| !found.is_empty())
+                .map(move |found| SearchMatch {
+                    line,
+                    start: found.start(),
+                    end: found.end(),
+                })
+        })
+        .collect())
+}
+
+/// The response pane's paragraph: word wrapped, keeping leading whitespace
+pub fn wrapped<'a>(text: impl Into<Text<'a>>) -> Paragraph<'a> {
+    Paragraph::new(text).wrap(Wrap { trim: false })
+}
+
+/// Rows a line takes up in the response pane when it is `width` columns
+/// wide, at least one even before the pane has been laid out
+pub fn wrapped_rows(line: &str, width: u16) -> usize {
+    wrapped(Line::raw(line)).line_count(width).max(1)
+}
+
+/// Scroll offset, in rows, of the first row of line `index`
+pub fn row_of_line(text: &str, index: usize, width: u16) -> usize {
+    text.lines()
+        .take(index)
+        .map(|line| wrapped_rows(line, width))
+        .sum()
+}
+
+/// Index of the line shown at scroll offset `row`
+pub fn line_at_row(text: &str, row: usize, width: u16) -> usize {
+    let mut rows = 0;
+    for (index, line) in text.lines().enumerate() {
+        rows += wrapped_rows(line, width);
+        if rows > row {
+            return index;
+        }
+    }
+    text.lines().count()
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,93 @@
+use super::*;
+
+#[test]
+fn test_find_matches_per_line() {
+    let matches = find_matches("name: Snorlax\nsnorlax snorlax", "snorlax", false).unwrap();
+
+    assert_eq!(
+        matches,
+        vec![
+            SearchMatch {
+                line: 0,
+                start: 6,
+                end: 13
+            },
+            SearchMatch {
+                line: 1,
+                start: 0,
+                end: 7
+            },
+            SearchMatch {
+                line: 1,
+                start: 8,
+                end: 15
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_find_matches_case_sensitive() {
+    let matches = find_matches("Snorlax snorlax", "snorlax", true).unwrap();
+
+    assert_eq!(
+        matches,
+        vec![SearchMatch {
+            line: 0,
+            start: 8,
+            end: 15
+        }]
+    );
+}
+
+#[test]
+fn test_find_matches_regex() {
+    let matches = find_matches("\"hp\": 160,\n\"speed\": 30", r"\d+", false).unwrap();
+
+    assert_eq!(matches.len(), 2);
+    assert_eq!(
+        matches[1],
+        SearchMatch {
+            line: 1,
+            start: 9,
+            end: 11
+        }
+    );
+}
+
+#[test]
+fn test_find_matches_skips_empty_matches() {
+    assert!(find_matches("abc", "x*", false).unwrap().is_empty());
+    assert!(find_matches("abc", "", false).unwrap().is_empty());
+}
+
+#[test]
+fn test_invalid_regex() {
+    let error = find_matches("abc", "(", false).unwrap_err();
+
+    assert!(error.contains("unclosed group"), "{}", error);
+}
+
+#[test]
+fn test_row_mapping_accounts_for_wrapping() {
+    let text = "short\n0123456789abcdef\nend";
+
+    assert_eq!(wrapped_rows("0123456789abcdef", 10), 2);
+    assert_eq!(wrapped_rows("", 10), 1);
+    assert_eq!(row_of_line(text, 2, 10), 3);
+    assert_eq!(line_at_row(text, 2, 10), 1);
+    assert_eq!(line_at_row(text, 3, 10), 2);
+}
+
+#[test]
+fn test_wrapped_rows_uses_display_width_and_word_wrap() {
+    // Wide characters take two columns
+    assert_eq!(wrapped_rows("日本語日本語", 10), 2);
+    // Words that do not fit move to the next row whole
+    assert_eq!(wrapped_rows("aaaaaaa bbbbbbb ccc", 10), 3);
+    // Words longer than a row are broken
+    assert_eq!(wrapped_rows("aaaaaaaaaaaaaaaaaaaaaaaaa", 10), 3);
+    // Control characters are not drawn
+    assert_eq!(wrapped_rows("\t\t\t\t\t\t\t\t\t\tabc", 10), 1);
+    assert_eq!(wrapped_rows("abc", 0), 1);
+}
@@ -0,0 +1,113 @@
+/// One dispatched `text/event-stream` event
+#[derive(Debug, Clone, PartialEq)]
+pub struct SseEvent {
+    pub id: Option<String>,
+    /// Event type, `message` when the stream does not name one
+    pub event: String,
+    /// Data lines joined with `\n`
+    pub data: String,
+}
+
+/// Parse the complete events of a `text/event-stream` body. An event is only
+/// dispatched by the blank line that ends it, so a partly received last
+/// event is left out until the rest of it arrives.
+pub fn parse(text: &str) -> Vec<SseEvent> {
+    EventParser::default().push(text)
+}
+
+/// Parser for a `text/event-stream` body that arrives in pieces, keeping the
+/// unfinished last line and event between them
+#[derive(Debug, Default)]
+pub struct EventParser {
+    /// Text after the last line break
+    pending: String,
+    started: bool,
+    /// The last piece ended in CR, so a LF starting the next one is part of it
+    after_cr: bool,
+    id: Option<String>,
+    event: Option<String>,
+    data: Option<String>,
+}
+
+impl EventParser {
+    /// Feed the next piece of the body, returning the events it completed
+    pub fn push(&mut self, text: &str) -> Vec<SseEvent> {
+        let mut events = Vec::new();
+        let mut pending = std::mem::take(&mut self.pending);
+        pending.push_str(text);
+        let mut rest = pending.as_str();
+        if !self.started && !rest.is_empty() {
+            rest = rest.strip_prefix('\u{feff}').unwrap_or(rest);
+            self.started = true;
+        }
+        if self.after_cr && !rest.is_empty() {
+            rest = rest.strip_prefix('\n').unwrap_or(rest);
+            self.after_cr = false;
+        }
+
+        while let Some(end) = rest.find(['\r', '\n']) {
+            let line = &rest[..end];
+            let cr = rest[end..].starts_with('\r');
+            rest = &rest[end + 1..];
+            if cr {
+                match rest.strip_prefix('\n') {
+                    Some(after) => rest = after,
+                    None => self.after_cr = rest.is_empty(),
+                }
+            }
+            events.extend(self.line(line));
+        }
+        self.pending = rest.to_string();
+        events
+    }
+
+    fn line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            let event = self.event.take();
+            return self.data.take().map(|data| SseEvent {
+                id: self.id.clone(),
+                event: event.unwrap_or_else(|| "message".to_string()),
+                data,
+            });
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "data" => match &mut self.data {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => self.data = Some(value.to_string()),
+            },
+            "event" => self.event = Some(value.to_string()),
+            // The last event ID carries over to later events
+            "id" if !value.contains('\0') => self.id = Some(value.to_string()),
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Events as `[type] id` headers followed by their data, one blank line apart
+pub fn format_events(events: &[SseEvent]) -> String {
+    events
+        .iter()
+        .map(|event| {
+            let header = match &event.id {
+                Some(id) => format!("[{}] id: {}", event.event, id),
+                None => format!("[{}]", event.event),
+            };
+            format!("{}\n{}", header, event.data)
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,76 @@
+use super::*;
+
+fn event(id: Option<&str>, kind: &str, data: &str) -> SseEvent {
+    SseEvent {
+        id: id.map(str::to_string),
+        event: kind.to_string(),
+        data: data.to_string(),
+    }
+}
+
+#[test]
+fn test_parse_events_with_fields() {
+    let text = "id: 1\nevent: update\ndata: {\"a\":1}\n\ndata: first\ndata: second\n\n";
+    assert_eq!(
+        parse(text),
+        vec![
+            event(Some("1"), "update", "{\"a\":1}"),
+            event(Some("1"), "message", "first\nsecond"),
+        ]
+    );
+}
+
+#[test]
+fn test_parse_skips_comments_and_unknown_fields() {
+    let text = ": keep-alive\nretry: 1000\nfoo: bar\ndata:no space\n\n";
+    assert_eq!(parse(text), vec![event(None, "message", "no space")]);
+}
+
+#[test]
+fn test_parse_waits_for_blank_line() {
+    assert!(parse("data: partial\n").is_empty());
+    assert!(parse("data: par").is_empty());
+    assert_eq!(parse("data: done\n\ndata: next").len(), 1);
+}
+
+#[test]
+fn test_parse_handles_crlf() {
+    assert_eq!(
+        parse("event: ping\r\ndata: 1\r\n\r\n"),
+        vec![event(None, "ping", "1")]
+    );
+}
+
+#[test]
+fn test_event_without_data_is_not_dispatched() {
+    assert!(parse("event: empty\n\n").is_empty());
+    assert_eq!(
+        parse("event: empty\n\ndata: x\n\n"),
+        vec![event(None, "message", "x")]
+    );
+}
+
+#[test]
+fn test_format_events() {
+    let events = vec![
+        event(Some("7"), "update", "{\"a\":1}"),
+        event(None, "message", "hi"),
+    ];
+    assert_eq!(
+        format_events(&events),
+        "[update] id: 7\n{\"a\":1}\n\n[message]\nhi"
+    );
+}
+
+#[test]
+fn test_event_parser_across_pieces() {
+    let mut parser = EventParser::default();
+
+    assert!(parser.push("\u{feff}id: 4\r").is_empty());
+    assert!(parser.push("\ndata: a").is_empty());
+    assert_eq!(
+        parser.push("b\r\n\r\ndata: c\n"),
+        vec![event(Some("4"), "message", "ab")]
+    );
+    assert_eq!(parser.push("\n"), vec![event(Some("4"), "message", "c")]);
+}
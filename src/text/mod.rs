@@ -0,0 +1,154 @@
+//! Cursor movement and display widths for the text editors.
+//!
+//! Cursors are byte offsets into a line that always sit on a grapheme
+//! cluster boundary, so `é`, emoji and flags move and delete as one
+//! character and slicing a line at the cursor never splits a code point.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Start of the grapheme cluster before `cursor`
+pub fn previous_boundary(text: &str, cursor: usize) -> usize {
+    text[..cursor]
+        .grapheme_indices(true)
+        .next_back()
+        .map_or(0, |(index, _)| index)
+}
+
+/// End of the grapheme cluster after `cursor`
+pub fn next_boundary(text: &str, cursor: usize) -> usize {
+    text[cursor..]
+        .graphemes(true)
+        .next()
+        .map_or(cursor, |grapheme| cursor + grapheme.len())
+}
+
+/// Closest grapheme boundary at or before `cursor`
+pub fn snap(text: &str, cursor: usize) -> usize {
+    if cursor >= text.len() {
+        return text.len();
+    }
+    text.grapheme_indices(true)
+        .map(|(index, _)| index)
+        .take_while(|index| *index <= cursor)
+        .last()
+        .unwrap_or(0)
+}
+
+/// Terminal columns taken by `text`; wide characters such as CJK and most
+/// emoji take two
+pub fn width(text: &str) -> usize {
+    text.width()
+}
+
+/// Display column of `cursor`
+pub fn column(text: &str, cursor: usize) -> usize {
+    width(&text[..cursor])
+}
+
+/// Cursor at display column `column`, or at the start of the character
+/// covering it
+pub fn cursor_at_column(text: &str, column: usize) -> usize {
+    let mut taken = 0;
+    for (index, grapheme) in text.grapheme_indices(true) {
+        taken += grapheme.width();
+        if taken > column {
+            return index;
+        }
+    }
+    text.len()
+}
+
+/// Insert `insert` at the cursor and move the cursor past it
+pub fn insert(text: &mut String, cursor: &mut usize, insert: &str) {
+    text.insert_str(*cursor, insert);
+    let end = *cursor + insert.len();
+    // A combining mark typed before existing text joins the cluster after it
+    *cursor = if end == snap(text, end) {
+        end
+    } else {
+        next_boundary(text, end)
+    };
+}
+
+/// Delete the grapheme cluster before the cursor, returning whether there
+/// was one
+pub fn remove_previous(text: &mut String, cursor: &mut usize) -> bool {
+    if *cursor == 0 {
+        return false;
+    }
+    let start = previous_boundary(text, *cursor);
+    text.replace_range(start..*cursor, "");
+    *cursor = start;
+    true
+}
+
+/// Delete the grapheme cluster after the cursor, returning whether there
+/// was one
+pub fn remove_next(text: &mut String, cursor: usize) -> bool {
+    let end = next_boundary(text, cursor);
+    text.replace_range(cursor..end, "");
+    end > cursor
+}
+
+fn is_word(grapheme: &str) -> bool {
+    grapheme
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Start of the word before `cursor`, skipping the spaces and punctuation
+/// in between
+pub fn previous_word(text: &str, cursor: usize) -> usize {
+    let mut start = cursor;
+    let mut in_word = false;
+    for (index, grapheme) in text[..cursor].grapheme_indices(true).rev() {
+        if is_word(grapheme) {
+            in_word = true;
+        } else if in_word {
+            break;
+        }
+        start = index;
+    }
+    start
+}
+
+/// End of the word after `cursor`, skipping the spaces and punctuation in
+/// between
+pub fn next_word(text: &str, cursor: usize) -> usize {
+    let mut end = cursor;
+    let mut in_word = false;
+    for (index, grapheme) in text[cursor..].grapheme_indices(true) {
+        if is_word(grapheme) {
+            in_word = true;
+        } else if in_word {
+            break;
+        }
+        end = cursor + index + grapheme.len();
+    }
+    end
+}
+
+/// First byte to show so the cursor fits in a field `columns` wide, keeping
+/// `start` when the cursor is still visible from there
+pub fn scroll_start(text: &str, cursor: usize, start: usize, columns: usize) -> usize {
+    let columns = columns.max(1);
+    let start = snap(text, start.min(cursor));
+    // One column is left for the cursor block drawn after the text
+    if width(&text[start..cursor]) < columns {
+        return start;
+    }
+    let mut start = cursor;
+    while start > 0 {
+        let previous = previous_boundary(text, start);
+        if width(&text[previous..cursor]) >= columns {
+            break;
+        }
+        start = previous;
+    }
+    start
+}
+
+#[cfg(test)]
+mod tests;
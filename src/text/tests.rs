@@ -0,0 +1,91 @@
+use super::*;
+
+#[test]
+fn test_boundaries_skip_whole_clusters() {
+    // "e" + combining acute, a family emoji joined with ZWJs, then "x"
+    let text = "e\u{301}👨‍👩‍👧x";
+    let family = 3;
+    let x = text.len() - 1;
+
+    assert_eq!(next_boundary(text, 0), family);
+    assert_eq!(next_boundary(text, family), x);
+    assert_eq!(next_boundary(text, text.len()), text.len());
+    assert_eq!(previous_boundary(text, x), family);
+    assert_eq!(previous_boundary(text, family), 0);
+    assert_eq!(previous_boundary(text, 0), 0);
+    assert_eq!(snap(text, family + 2), family);
+    assert_eq!(snap(text, 99), text.len());
+}
+
+#[test]
+fn test_columns_count_wide_characters() {
+    let text = "a日本b";
+    assert_eq!(width(text), 6);
+    assert_eq!(column(text, "a日".len()), 3);
+
+    assert_eq!(cursor_at_column(text, 0), 0);
+    assert_eq!(cursor_at_column(text, 1), 1);
+    // Column 2 is the right half of 日
+    assert_eq!(cursor_at_column(text, 2), 1);
+    assert_eq!(cursor_at_column(text, 3), "a日".len());
+    assert_eq!(cursor_at_column(text, 40), text.len());
+}
+
+#[test]
+fn test_insert_and_remove() {
+    let mut text = String::from("ab");
+    let mut cursor = 1;
+
+    insert(&mut text, &mut cursor, "é");
+    assert_eq!((text.as_str(), cursor), ("aéb", 3));
+    insert(&mut text, &mut cursor, "🎉");
+    assert_eq!((text.as_str(), cursor), ("aé🎉b", 7));
+
+    assert!(remove_previous(&mut text, &mut cursor));
+    assert_eq!((text.as_str(), cursor), ("aéb", 3));
+    assert!(remove_next(&mut text, cursor));
+    assert_eq!(text, "aé");
+    assert!(!remove_next(&mut text, cursor));
+
+    let mut cursor = 0;
+    assert!(!remove_previous(&mut text, &mut cursor));
+}
+
+#[test]
+fn test_insert_combining_mark_joins_cluster() {
+    let mut text = String::from("\u{301}");
+    let mut cursor = 0;
+
+    insert(&mut text, &mut cursor, "e");
+
+    assert_eq!(cursor, text.len());
+}
+
+#[test]
+fn test_scroll_start_keeps_cursor_visible() {
+    let text = "0123456789";
+    assert_eq!(scroll_start(text, 3, 0, 5), 0);
+    assert_eq!(scroll_start(text, 9, 0, 5), 5);
+    // Moving back left of the window scrolls to the cursor
+    assert_eq!(scroll_start(text, 2, 5, 5), 2);
+    // Wide characters take two columns each
+    let wide = "日本語テキスト";
+    let start = scroll_start(wide, wide.len(), 0, 5);
+    assert_eq!(&wide[start..], "スト");
+}
+
+#[test]
+fn test_word_motion() {
+    let text = "Content-Type: application/json";
+    assert_eq!(next_word(text, 0), "Content".len());
+    assert_eq!(next_word(text, "Content".len()), "Content-Type".len());
+    assert_eq!(previous_word(text, text.len()), text.len() - "json".len());
+    assert_eq!(
+        previous_word(text, "Content-Type: ".len()),
+        "Content-".len()
+    );
+    assert_eq!(previous_word(text, 3), 0);
+    assert_eq!(next_word(text, text.len()), text.len());
+    // Words may contain any letters
+    assert_eq!(next_word("  naïve café", 0), "  naïve".len());
+}
@@ -0,0 +1,439 @@
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context};
+use reqwest::blocking::ClientBuilder;
+use reqwest::tls::{Certificate, Identity, Version};
+use sha2::{Digest, Sha256};
+use x509_parser::extensions::GeneralName;
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MinTlsVersion {
+    Default,
+    Tls1_0,
+    Tls1_1,
+    Tls1_2,
+}
+
+impl MinTlsVersion {
+    pub const ALL: &'static [MinTlsVersion] = &[
+        MinTlsVersion::Default,
+        MinTlsVersion::Tls1_0,
+        MinTlsVersion::Tls1_1,
+        MinTlsVersion::Tls1_2,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MinTlsVersion::Default => "default",
+            MinTlsVersion::Tls1_0 => "1.0",
+            MinTlsVersion::Tls1_1 => "1.1",
+            MinTlsVersion::Tls1_2 => "1.2",
+        }
+    }
+
+    pub fn next(&self) -> MinTlsVersion {
+        let index = Self::ALL.iter().position(|v| v == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn parse(value: &str) -> Result<MinTlsVersion, String> {
+        let value = value.trim().trim_start_matches("TLS").trim();
+        if value == "1.3" {
+            // native TLS cannot require 1.3 everywhere, and quietly allowing
+            // 1.2 would weaken what the settings ask for
+            return Err("A 1.3 minimum is not supported by native TLS, use 1.2".to_string());
+        }
+        Self::ALL
+            .iter()
+            .find(|v| v.label().eq_ignore_ascii_case(value))
+            .copied()
+            .ok_or_else(|| format!("Expected default, 1.0, 1.1 or 1.2, got '{}'", value))
+    }
+
+    fn version(&self) -> Option<Version> {
+        match self {
+            MinTlsVersion::Default => None,
+            MinTlsVersion::Tls1_0 => Some(Version::TLS_1_0),
+            MinTlsVersion::Tls1_1 => Some(Version::TLS_1_1),
+            MinTlsVersion::Tls1_2 => Some(Version::TLS_1_2),
+        }
+    }
+}
+
+/// TLS options applied when building the HTTP client.
+///
+/// Paths are empty when unset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlsSettings {
+    /// PEM file with one or more extra root certificates to trust
+    pub ca_bundle: String,
+    /// Client certificate, either PEM (with `client_key`) or PKCS#12 (.p12/.pfx)
+    pub client_cert: String,
+    /// PKCS#8 PEM private key for a PEM client certificate
+    pub client_key: String,
+    /// Password for a PKCS#12 client certificate
+    pub client_cert_password: String,
+    pub min_version: MinTlsVersion,
+    /// Skip certificate and hostname verification entirely
+    pub insecure: bool,
+}
+
+impl Default for TlsSettings {
+    fn default() -> Self {
+        Self {
+            ca_bundle: String::new(),
+            client_cert: String::new(),
+            client_key: String::new(),
+            client_cert_password: String::new(),
+            min_version: MinTlsVersion::Default,
+            insecure: false,
+        }
+    }
+}
+
+/// Apply the TLS settings to a client builder, loading certificates from disk
+pub fn configure(
+    mut builder: ClientBuilder,
+    settings: &TlsSettings,
+) -> anyhow::Result<ClientBuilder> {
+    builder = builder.tls_info(true);
+
+    if !settings.ca_bundle.is_empty() {
+        let pem = read_file(&settings.ca_bundle, "CA bundle")?;
+        let certs = Certificate::from_pem_bundle(&pem)
+            .with_context(|| format!("Invalid PEM in CA bundle {}", settings.ca_bundle))?;
+        if certs.is_empty() {
+            return Err(anyhow!(
+                "No certificates found in CA bundle {}",
+                settings.ca_bundle
+            ));
+        }
+        for cert in certs {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+
+    if !settings.client_cert.is_empty() {
+        builder = builder.identity(load_identity(settings)?);
+    }
+
+    if let Some(version) = settings.min_version.version() {
+        builder = builder.min_tls_version(version);
+    }
+
+    if settings.insecure {
+        builder = builder
+            .danger_accept_invalid_certs(true)
+            .danger_accept_invalid_hostnames(true);
+    }
+
+    Ok(builder)
+}
+
+/// TLS connector with the same settings, for `wss://` WebSocket connections
+pub fn connector(settings: &TlsSettings) -> anyhow::Result<native_tls::TlsConnector> {
+    connector_with_alpn(settings, &[])
+}
+
+/// TLS connector offering the given ALPN protocols, such as `h2` for gRPC
+pub fn connector_with_alpn(
+    settings: &TlsSettings,
+    protocols: &[&str],
+) -> anyhow::Result<native_tls::TlsConnector> {
+    let mut builder = native_tls::TlsConnector::builder();
+    if !protocols.is_empty() {
+        builder.request_alpns(protocols);
+    }
+
+    if !settings.ca_bundle.is_empty() {
+        let pem = read_file(&settings.ca_bundle, "CA bundle")?;
+        let pem = String::from_utf8_lossy(&pem);
+        let blocks: Vec<String> = pem
+            .split_inclusive("-----END CERTIFICATE-----")
+            .filter(|block| block.contains("-----BEGIN CERTIFICATE-----"))
+            .map(str::to_string)
+            .collect();
+        if blocks.is_empty() {
+            return Err(anyhow!(
+                "No certificates found in CA bundle {}",
+                settings.ca_bundle
+            ));
+        }
+        for block in blocks {
+            let cert = native_tls::Certificate::from_pem(block.as_bytes())
+                .with_context(|| format!("Invalid PEM in CA bundle {}", settings.ca_bundle))?;
+            builder.add_root_certificate(cert);
+        }
+    }
+
+    if !settings.client_cert.is_empty() {
+        builder.identity(load_native_identity(settings)?);
+    }
+
+    builder.min_protocol_version(match settings.min_version {
+        MinTlsVersion::Default => None,
+        MinTlsVersion::Tls1_0 => Some(native_tls::Protocol::Tlsv10),
+        MinTlsVersion::Tls1_1 => Some(native_tls::Protocol::Tlsv11),
+        MinTlsVersion::Tls1_2 => Some(native_tls::Protocol::Tlsv12),
+    });
+
+    if settings.insecure {
+        builder
+            .danger_accept_invalid_certs(true)
+            .danger_accept_invalid_hostnames(true);
+    }
+
+    builder.build().context("Failed to set up TLS")
+}
+
+fn load_native_identity(settings: &TlsSettings) -> anyhow::Result<native_tls::Identity> {
+    let cert = read_file(&settings.client_cert, "client certificate")?;
+    let lower = settings.client_cert.to_ascii_lowercase();
+
+    if lower.ends_with(".p12") || lower.ends_with(".pfx") {
+        return native_tls::Identity::from_pkcs12(&cert, &settings.client_cert_password)
+            .with_context(|| format!("Invalid PKCS#12 file {}", settings.client_cert));
+    }
+
+    if settings.client_key.is_empty() {
+        return Err(anyhow!("A PEM client certificate also needs a client key"));
+    }
+    let key = read_file(&settings.client_key, "client key")?;
+    native_tls::Identity::from_pkcs8(&cert, &key)
+        .context("Invalid PEM client certificate or PKCS#8 key")
+}
+
+fn load_identity(settings: &TlsSettings) -> anyhow::Result<Identity> {
+    let cert = read_file(&settings.client_cert, "client certificate")?;
+    let lower = settings.client_cert.to_ascii_lowercase();
+
+    if lower.ends_with(".p12") || lower.ends_with(".pfx") {
+        return Identity::from_pkcs12_der(&cert, &settings.client_cert_password)
+            .with_context(|| format!("Invalid PKCS#12 file {}", settings.client_cert));
+    }
+
+    if settings.client_key.is_empty() {
+        return Err(anyhow!("A PEM client certificate also needs a client key"));
+    }
+    let key = read_file(&settings.client_key, "client key")?;
+    Identity::from_pkcs8_pem(&cert, &key).context("Invalid PEM client certificate or PKCS#8 key")
+}
+
+fn read_file(path: &str, what: &str) -> anyhow::Result<Vec<u8>> {
+    std::fs::read(path).with_context(|| format!("Failed to read {} {}", what, path))
+}
+
+/// How long fetching the certificate chain may take to connect or wait for the server
+pub const CHAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Certificates the server presents on `stream`, leaf first.
+///
+/// native-tls only hands out the leaf of the connection a request used, so
+/// the chain comes from a handshake of its own, made with OpenSSL and the
+/// same CA bundle, client certificate, minimum version and verification as
+/// requests.
+#[cfg(not(any(target_os = "windows", target_vendor = "apple")))]
+pub fn fetch_chain(
+    stream: TcpStream,
+    host: &str,
+    settings: &TlsSettings,
+) -> anyhow::Result<Vec<CertificateInfo>> {
+    use openssl::pkcs12::Pkcs12;
+    use openssl::pkey::PKey;
+    use openssl::ssl::{SslConnector, SslMethod, SslVerifyMode, SslVersion};
+    use openssl::x509::X509;
+
+    let mut builder = SslConnector::builder(SslMethod::tls_client())?;
+
+    if !settings.ca_bundle.is_empty() {
+        let pem = read_file(&settings.ca_bundle, "CA bundle")?;
+        let certs = X509::stack_from_pem(&pem)
+            .with_context(|| format!("Invalid PEM in CA bundle {}", settings.ca_bundle))?;
+        for cert in certs {
+            builder.cert_store_mut().add_cert(cert)?;
+        }
+    }
+
+    if !settings.client_cert.is_empty() {
+        let cert = read_file(&settings.client_cert, "client certificate")?;
+        let lower = settings.client_cert.to_ascii_lowercase();
+        let (cert, key, extra) = if lower.ends_with(".p12") || lower.ends_with(".pfx") {
+            let parsed = Pkcs12::from_der(&cert)
+                .and_then(|p12| p12.parse2(&settings.client_cert_password))
+                .with_context(|| format!("Invalid PKCS#12 file {}", settings.client_cert))?;
+            let extra = parsed
+                .ca
+                .map(|ca| ca.into_iter().collect())
+                .unwrap_or_default();
+            (parsed.cert, parsed.pkey, extra)
+        } else {
+            let key = read_file(&settings.client_key, "client key")?;
+            let mut certs = X509::stack_from_pem(&cert)
+                .context("Invalid PEM client certificate")?
+                .into_iter();
+            let key = PKey::private_key_from_pem(&key).context("Invalid PKCS#8 key")?;
+            (certs.next(), Some(key), certs.collect::<Vec<_>>())
+        };
+        let (Some(cert), Some(key)) = (cert, key) else {
+            return Err(anyhow!("The client certificate has no certificate or key"));
+        };
+        builder.set_certificate(&cert)?;
+        builder.set_private_key(&key)?;
+        for cert in extra {
+            builder.add_extra_chain_cert(cert)?;
+        }
+    }
+
+    builder.set_min_proto_version(match settings.min_version {
+        MinTlsVersion::Default => None,
+        MinTlsVersion::Tls1_0 => Some(SslVersion::TLS1),
+        MinTlsVersion::Tls1_1 => Some(SslVersion::TLS1_1),
+        MinTlsVersion::Tls1_2 => Some(SslVersion::TLS1_2),
+    })?;
+
+    if settings.insecure {
+        builder.set_verify(SslVerifyMode::NONE);
+    }
+
+    stream.set_read_timeout(Some(CHAIN_TIMEOUT))?;
+    stream.set_write_timeout(Some(CHAIN_TIMEOUT))?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let tls = builder
+        .build()
+        .configure()?
+        .verify_hostname(!settings.insecure)
+        .connect(host, stream)
+        .map_err(|e| anyhow!("TLS handshake with {} failed: {}", host, e))?;
+    let chain = tls
+        .ssl()
+        .peer_cert_chain()
+        .ok_or_else(|| anyhow!("{} sent no certificates", host))?;
+    chain
+        .iter()
+        .map(|cert| {
+            let der = cert.to_der()?;
+            CertificateInfo::from_der(&der).map_err(|e| anyhow!(e))
+        })
+        .collect()
+}
+
+/// Without OpenSSL there is no way to see more than the leaf
+#[cfg(any(target_os = "windows", target_vendor = "apple"))]
+pub fn fetch_chain(
+    _stream: TcpStream,
+    _host: &str,
+    _settings: &TlsSettings,
+) -> anyhow::Result<Vec<CertificateInfo>> {
+    Err(anyhow!(
+        "the rest of the chain is only available on systems using OpenSSL"
+    ))
+}
+
+/// Open a TCP connection, trying every address `host` resolves to
+pub fn connect(host: &str, port: u16) -> anyhow::Result<TcpStream> {
+    let mut last_error = None;
+    for addr in (host, port)
+        .to_socket_addrs()
+        .with_context(|| format!("Failed to resolve {}", host))?
+    {
+        match TcpStream::connect_timeout(&addr, CHAIN_TIMEOUT) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(match last_error {
+        Some(e) => anyhow!("Failed to connect to {}:{}: {}", host, port, e),
+        None => anyhow!("No addresses found for {}", host),
+    })
+}
+
+/// Human-readable summary of a peer certificate
+#[derive(Debug, Clone, PartialEq)]
+pub struct CertificateInfo {
+    pub subject: String,
+    pub issuer: String,
+    pub serial: String,
+    pub not_before: String,
+    pub not_after: String,
+    pub alt_names: Vec<String>,
+    pub is_ca: bool,
+    pub sha256: String,
+}
+
+impl CertificateInfo {
+    pub fn from_der(der: &[u8]) -> Result<CertificateInfo, String> {
+        let (_, cert) =
+            X509Certificate::from_der(der).map_err(|e| format!("Invalid certificate: {}", e))?;
+
+        let alt_names = cert
+            .subject_alternative_name()
+            .ok()
+            .flatten()
+            .map(|ext| {
+                ext.value
+                    .general_names
+                    .iter()
+                    .filter_map(|name| match name {
+                        GeneralName::DNSName(dns) => Some(dns.to_string()),
+                        GeneralName::IPAddress(ip) => format_ip(ip),
+                        GeneralName::URI(uri) => Some(uri.to_string()),
+                        GeneralName::RFC822Name(email) => Some(email.to_string()),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let sha256 = Sha256::digest(der)
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect::<Vec<_>>()
+            .join(":");
+
+        Ok(CertificateInfo {
+            subject: cert.subject().to_string(),
+            issuer: cert.issuer().to_string(),
+            serial: cert.raw_serial_as_string(),
+            not_before: cert.validity().not_before.to_string(),
+            not_after: cert.validity().not_after.to_string(),
+            alt_names,
+            is_ca: cert.is_ca(),
+            sha256,
+        })
+    }
+
+    pub fn lines(&self) -> Vec<(&'static str, String)> {
+        let mut lines = vec![
+            ("Subject", self.subject.clone()),
+            ("Issuer", self.issuer.clone()),
+            ("Serial", self.serial.clone()),
+            ("Valid from", self.not_before.clone()),
+            ("Valid until", self.not_after.clone()),
+        ];
+        if !self.alt_names.is_empty() {
+            lines.push(("Alt names", self.alt_names.join(", ")));
+        }
+        lines.push(("CA", if self.is_ca { "yes" } else { "no" }.to_string()));
+        lines.push(("SHA-256", self.sha256.clone()));
+        lines
+    }
+}
+
+fn format_ip(bytes: &[u8]) -> Option<String> {
+    match bytes.len() {
+        4 => {
+            let octets: [u8; 4] = bytes.try_into().ok()?;
+            Some(std::net::Ipv4Addr::from(octets).to_string())
+        }
+        16 => {
+            let octets: [u8; 16] = bytes.try_into().ok()?;
+            Some(std::net::Ipv6Addr::from(octets).to_string())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests;
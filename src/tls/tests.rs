@@ -0,0 +1,213 @@
+use super::*;
+
+const CERT_PEM: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/tls/testdata/cert.pem");
+const KEY_PEM: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/tls/testdata/key.pem");
+const IDENTITY_P12: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/tls/testdata/identity.p12");
+
+fn cert_der() -> Vec<u8> {
+    let pem = std::fs::read_to_string(CERT_PEM).unwrap();
+    let (_, pem) = x509_parser::pem::parse_x509_pem(pem.as_bytes()).unwrap();
+    pem.contents
+}
+
+fn builder() -> ClientBuilder {
+    reqwest::blocking::Client::builder()
+}
+
+#[test]
+fn test_min_tls_version_cycles() {
+    let mut version = MinTlsVersion::Default;
+    for _ in 0..MinTlsVersion::ALL.len() {
+        version = version.next();
+    }
+
+    assert_eq!(version, MinTlsVersion::Default);
+    assert_eq!(MinTlsVersion::Default.next(), MinTlsVersion::Tls1_0);
+}
+
+#[test]
+fn test_min_tls_version_parse() {
+    assert_eq!(MinTlsVersion::parse("1.2"), Ok(MinTlsVersion::Tls1_2));
+    assert_eq!(MinTlsVersion::parse("TLS1.2"), Ok(MinTlsVersion::Tls1_2));
+    assert!(MinTlsVersion::parse("1.3")
+        .unwrap_err()
+        .contains("not supported"));
+    assert_eq!(MinTlsVersion::parse("Default"), Ok(MinTlsVersion::Default));
+    assert!(MinTlsVersion::parse("2.0").is_err());
+}
+
+#[test]
+fn test_configure_with_defaults() {
+    let builder = configure(builder(), &TlsSettings::default()).unwrap();
+
+    assert!(builder.build().is_ok());
+}
+
+#[test]
+fn test_configure_with_ca_bundle() {
+    let settings = TlsSettings {
+        ca_bundle: CERT_PEM.to_string(),
+        ..TlsSettings::default()
+    };
+
+    assert!(configure(builder(), &settings).is_ok());
+}
+
+#[test]
+fn test_configure_missing_ca_bundle() {
+    let settings = TlsSettings {
+        ca_bundle: "/nonexistent/ca.pem".to_string(),
+        ..TlsSettings::default()
+    };
+
+    let error = configure(builder(), &settings).unwrap_err();
+
+    assert!(error.to_string().contains("Failed to read CA bundle"));
+}
+
+#[test]
+fn test_configure_ca_bundle_without_certificates() {
+    let settings = TlsSettings {
+        ca_bundle: KEY_PEM.to_string(),
+        ..TlsSettings::default()
+    };
+
+    assert!(configure(builder(), &settings).is_err());
+}
+
+#[test]
+fn test_configure_with_pem_identity() {
+    let settings = TlsSettings {
+        client_cert: CERT_PEM.to_string(),
+        client_key: KEY_PEM.to_string(),
+        ..TlsSettings::default()
+    };
+
+    assert!(configure(builder(), &settings).is_ok());
+}
+
+#[test]
+fn test_configure_pem_identity_requires_key() {
+    let settings = TlsSettings {
+        client_cert: CERT_PEM.to_string(),
+        ..TlsSettings::default()
+    };
+
+    let error = configure(builder(), &settings).unwrap_err();
+
+    assert!(error.to_string().contains("client key"));
+}
+
+#[test]
+fn test_configure_with_pkcs12_identity() {
+    let settings = TlsSettings {
+        client_cert: IDENTITY_P12.to_string(),
+        client_cert_password: "secret".to_string(),
+        ..TlsSettings::default()
+    };
+
+    assert!(configure(builder(), &settings).is_ok());
+}
+
+#[test]
+fn test_configure_pkcs12_wrong_password() {
+    let settings = TlsSettings {
+        client_cert: IDENTITY_P12.to_string(),
+        client_cert_password: "wrong".to_string(),
+        ..TlsSettings::default()
+    };
+
+    assert!(configure(builder(), &settings).is_err());
+}
+
+#[test]
+fn test_configure_insecure_and_min_version() {
+    let settings = TlsSettings {
+        min_version: MinTlsVersion::Tls1_2,
+        insecure: true,
+        ..TlsSettings::default()
+    };
+
+    assert!(configure(builder(), &settings).unwrap().build().is_ok());
+}
+
+#[test]
+fn test_certificate_info_from_der() {
+    let info = CertificateInfo::from_der(&cert_der()).unwrap();
+
+    assert!(info.subject.contains("CN=jorna.test"));
+    // Self-signed, so issuer and subject match
+    assert_eq!(info.subject, info.issuer);
+    assert_eq!(info.alt_names, vec!["jorna.test", "localhost", "127.0.0.1"]);
+    assert_eq!(info.sha256.split(':').count(), 32);
+}
+
+#[test]
+fn test_certificate_info_invalid_der() {
+    assert!(CertificateInfo::from_der(b"not a certificate").is_err());
+}
+
+#[test]
+fn test_certificate_info_lines() {
+    let info = CertificateInfo::from_der(&cert_der()).unwrap();
+
+    let labels: Vec<_> = info.lines().iter().map(|(label, _)| *label).collect();
+
+    assert_eq!(labels[0], "Subject");
+    assert!(labels.contains(&"Alt names"));
+    assert_eq!(labels.last(), Some(&"SHA-256"));
+}
+
+fn chain_settings(ca_bundle: &str) -> TlsSettings {
+    TlsSettings {
+        ca_bundle: ca_bundle.to_string(),
+        ..TlsSettings::default()
+    }
+}
+
+fn chain_server() -> TcpStream {
+    let url = crate::http::test_server::serve_tls_with(include_bytes!("testdata/chain.p12"), b"");
+    let port = url.rsplit(':').next().unwrap().parse().unwrap();
+    connect("localhost", port).unwrap()
+}
+
+#[test]
+#[cfg(not(any(target_os = "windows", target_vendor = "apple")))]
+fn test_fetch_chain_returns_leaf_and_intermediates() {
+    let ca = concat!(env!("CARGO_MANIFEST_DIR"), "/src/tls/testdata/ca.pem");
+
+    let chain = fetch_chain(chain_server(), "chain.jorna.test", &chain_settings(ca)).unwrap();
+
+    assert_eq!(chain.len(), 2);
+    assert!(chain[0].subject.contains("chain.jorna.test"));
+    assert_eq!(chain[0].issuer, chain[1].subject);
+}
+
+#[test]
+#[cfg(not(any(target_os = "windows", target_vendor = "apple")))]
+fn test_fetch_chain_verifies_like_requests() {
+    let untrusted = fetch_chain(chain_server(), "chain.jorna.test", &TlsSettings::default());
+    let insecure = TlsSettings {
+        insecure: true,
+        ..TlsSettings::default()
+    };
+
+    assert!(untrusted.is_err());
+    assert_eq!(
+        fetch_chain(chain_server(), "chain.jorna.test", &insecure)
+            .unwrap()
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn test_connect_refused() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+
+    assert!(connect("127.0.0.1", port).is_err());
+}
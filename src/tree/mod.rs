@@ -0,0 +1,192 @@
+use std::collections::HashSet;
+
+use serde_json::Value;
+
+/// One visible line of the tree
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeRow<'a> {
+    /// JSONPath of the node, e.g. `$.results[0].name`
+    pub path: String,
+    pub depth: usize,
+    /// Object key or array index, `None` for the root
+    pub label: Option<String>,
+    pub value: &'a Value,
+    pub expanded: bool,
+}
+
+impl TreeRow<'_> {
+    pub fn is_container(&self) -> bool {
+        matches!(self.value, Value::Object(_) | Value::Array(_))
+    }
+
+    /// Short description of the node value; containers show their child count
+    /// when collapsed
+    pub fn summary(&self) -> String {
+        match self.value {
+            Value::Object(_) if self.expanded => "{".to_string(),
+            Value::Array(_) if self.expanded => "[".to_string(),
+            Value::Object(map) => format!("{{…}} {}", plural(map.len(), "key", "keys")),
+            Value::Array(items) => format!("[…] {}", plural(items.len(), "item", "items")),
+            scalar => scalar.to_string(),
+        }
+    }
+}
+
+fn plural(count: usize, one: &str, many: &str) -> String {
+    format!("{} {}", count, if count == 1 { one } else { many })
+}
+
+/// Collapsible view of a parsed JSON response
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonTree {
+    root: Value,
+    expanded: HashSet<String>,
+    pub selected: usize,
+    pub scroll: u16,
+}
+
+impl JsonTree {
+    /// Build a tree with the root and its direct children expanded
+    pub fn new(root: Value) -> Self {
+        let mut expanded = HashSet::from([ROOT.to_string()]);
+        for (path, _, child) in children(ROOT, &root) {
+            if matches!(child, Value::Object(_) | Value::Array(_)) {
+                expanded.insert(path);
+            }
+        }
+        Self {
+            root,
+            expanded,
+            selected: 0,
+            scroll: 0,
+        }
+    }
+
+    pub fn root(&self) -> &Value {
+        &self.root
+    }
+
+    /// Visible rows in display order, skipping the children of collapsed nodes
+    pub fn rows(&self) -> Vec<TreeRow<'_>> {
+        let mut rows = Vec::new();
+        self.collect_rows(ROOT.to_string(), None, &self.root, 0, &mut rows);
+        rows
+    }
+
+    fn collect_rows<'a>(
+        &'a self,
+        path: String,
+        label: Option<String>,
+        value: &'a Value,
+        depth: usize,
+        rows: &mut Vec<TreeRow<'a>>,
+    ) {
+        let expanded = self.expanded.contains(&path);
+        rows.push(TreeRow {
+            path: path.clone(),
+            depth,
+            label,
+            value,
+            expanded,
+        });
+        if !expanded {
+            return;
+        }
+        for (child_path, label, child) in children(&path, value) {
+            self.collect_rows(child_path, Some(label), child, depth + 1, rows);
+        }
+    }
+
+    pub fn selected_row(&self) -> Option<TreeRow<'_>> {
+        self.rows().into_iter().nth(self.selected)
+    }
+
+    pub fn select_up(&mut self, amount: usize) {
+        self.selected = self.selected.saturating_sub(amount);
+    }
+
+    pub fn select_down(&mut self, amount: usize) {
+        let last = self.rows().len().saturating_sub(1);
+        self.selected = (self.selected + amount).min(last);
+    }
+
+    pub fn select_first(&mut self) {
+        self.selected = 0;
+    }
+
+    /// Expand or collapse the selected node
+    pub fn toggle_selected(&mut self) {
+        let Some(row) = self.selected_row() else {
+            return;
+        };
+        if !row.is_container() {
+            return;
+        }
+        let path = row.path;
+        if !self.expanded.remove(&path) {
+            self.expanded.insert(path);
+        }
+    }
+
+    /// JSONPath of the selected node
+    pub fn selected_path(&self) -> Option<String> {
+        self.selected_row().map(|row| row.path)
+    }
+
+    /// Pretty-printed JSON of the selected node
+    pub fn selected_value(&self) -> Option<String> {
+        self.selected_row()
+            .map(|row| serde_json::to_string_pretty(row.value).unwrap_or_default())
+    }
+
+    /// Keep the selection inside a viewport of `height` rows
+    pub fn ensure_selected_visible(&mut self, height: u16) {
+        let selected = self.selected as u16;
+        if selected < self.scroll {
+            self.scroll = selected;
+        } else if height > 0 && selected >= self.scroll + height {
+            self.scroll = selected + 1 - height;
+        }
+    }
+}
+
+const ROOT: &str = "$";
+
+/// Direct children of a container with their JSONPaths and display labels
+fn children<'a>(path: &str, value: &'a Value) -> Vec<(String, String, &'a Value)> {
+    match value {
+        Value::Object(map) => map
+            .iter()
+            .map(|(key, child)| {
+                (
+                    child_path(path, key),
+                    Value::from(key.as_str()).to_string(),
+                    child,
+                )
+            })
+            .collect(),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(i, child)| (format!("{}[{}]", path, i), format!("[{}]", i), child))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// `.key` for identifier-like keys, `["key"]` for anything else
+pub fn child_path(path: &str, key: &str) -> String {
+    let mut chars = key.chars();
+    let is_identifier = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if is_identifier {
+        format!("{}.{}", path, key)
+    } else {
+        format!("{}[{}]", path, Value::from(key))
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,110 @@
+use serde_json::json;
+
+use super::*;
+
+fn sample() -> JsonTree {
+    JsonTree::new(json!({
+        "name": "snorlax",
+        "types": [{"slot": 1, "type": {"name": "normal"}}],
+        "stats": {"hp": 160},
+        "content-type": "x"
+    }))
+}
+
+fn paths(tree: &JsonTree) -> Vec<String> {
+    tree.rows().into_iter().map(|row| row.path).collect()
+}
+
+#[test]
+fn test_root_children_start_expanded() {
+    let tree = sample();
+
+    assert_eq!(
+        paths(&tree),
+        vec![
+            "$",
+            "$[\"content-type\"]",
+            "$.name",
+            "$.stats",
+            "$.stats.hp",
+            "$.types",
+            "$.types[0]",
+        ]
+    );
+}
+
+#[test]
+fn test_collapsed_summary_shows_child_count() {
+    let tree = sample();
+    let rows = tree.rows();
+    let item = rows.iter().find(|row| row.path == "$.types[0]").unwrap();
+
+    assert!(!item.expanded);
+    assert_eq!(item.summary(), "{…} 2 keys");
+    assert_eq!(item.label.as_deref(), Some("[0]"));
+    assert_eq!(rows[0].summary(), "{");
+    assert_eq!(rows[2].summary(), "\"snorlax\"");
+    assert_eq!(rows[2].label.as_deref(), Some("\"name\""));
+}
+
+#[test]
+fn test_toggle_selected() {
+    let mut tree = sample();
+    tree.selected = 6;
+
+    tree.toggle_selected();
+    assert!(!paths(&tree).contains(&"$.types[0].type.name".to_string()));
+    assert!(paths(&tree).contains(&"$.types[0].slot".to_string()));
+
+    tree.toggle_selected();
+    assert!(!paths(&tree).contains(&"$.types[0].slot".to_string()));
+}
+
+#[test]
+fn test_toggle_scalar_is_ignored() {
+    let mut tree = sample();
+    tree.selected = 2;
+
+    tree.toggle_selected();
+
+    assert_eq!(tree.rows().len(), 7);
+}
+
+#[test]
+fn test_selection_is_clamped() {
+    let mut tree = sample();
+
+    tree.select_up(3);
+    assert_eq!(tree.selected, 0);
+
+    tree.select_down(100);
+    assert_eq!(tree.selected, 6);
+
+    tree.select_first();
+    assert_eq!(tree.selected, 0);
+}
+
+#[test]
+fn test_copy_path_and_value() {
+    let mut tree = sample();
+    tree.selected = 3;
+
+    assert_eq!(tree.selected_path().as_deref(), Some("$.stats"));
+    assert_eq!(
+        tree.selected_value().as_deref(),
+        Some("{\n  \"hp\": 160\n}")
+    );
+}
+
+#[test]
+fn test_ensure_selected_visible() {
+    let mut tree = sample();
+    tree.selected = 6;
+
+    tree.ensure_selected_visible(3);
+    assert_eq!(tree.scroll, 4);
+
+    tree.selected = 1;
+    tree.ensure_selected_visible(3);
+    assert_eq!(tree.scroll, 1);
+}
@@ -1,9 +1,23 @@
-use crate::app::{App, AppFocus};
+use crate::app::{App, AppFocus, ResponseTab};
+use crate::body::{BodyType, JsonError};
+use crate::diff::{self, DiffKind};
+use crate::editor::Editor;
+use crate::headers;
+use crate::highlight::{Highlighter, Language, Palette};
+use crate::http::{RequestTimings, SettingsField};
+use crate::params::{ParamColumn, ParamTable};
+use crate::search;
+use crate::text;
+use crate::tls::CertificateInfo;
+use crate::tree::JsonTree;
+use crate::ws::FrameType;
+use std::time::Duration;
+
 use ratatui::{
-    layout::{Constraint, Direction, Layout},
-    style::{Color, Style},
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
     Frame,
 };
 
@@ -13,6 +27,7 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
         .margin(1)
         .constraints([
             Constraint::Length(3), // Input area (method + URL)
+            Constraint::Length(5), // Query params
             Constraint::Length(5), // Headers input
             Constraint::Length(8), // Body input
             Constraint::Length(1), // Status line
@@ -21,17 +36,25 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
         ])
         .split(frame.area());
 
+    // Method selector, widened for long custom methods
+    let method_text = match &app.method_edit {
+        Some(edit) => Line::from(vec![
+            Span::raw(format!(" {}", edit)),
+            Span::styled("█", Style::default().fg(Color::Cyan)),
+        ]),
+        None => Line::from(format!(" {} ", app.http_method)),
+    };
+    let method_width = (method_text.width() as u16 + 2).max(12);
+
     // Split input area horizontally for method selector and URL input
     let input_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([
-            Constraint::Length(12), // Method selector
-            Constraint::Min(20),    // URL input
+            Constraint::Length(method_width), // Method selector
+            Constraint::Min(20),              // URL input
         ])
         .split(chunks[0]);
 
-    // Method selector
-    let method_text = format!(" {} ", app.http_method);
     let method_widget = Paragraph::new(method_text)
         .style(Style::default().fg(Color::DarkGray))
         .block(
@@ -46,11 +69,14 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
         );
     frame.render_widget(method_widget, input_chunks[0]);
 
-    // URL Input with cursor
+    // URL Input with cursor, scrolled sideways to keep the cursor in view
+    let url_columns = input_chunks[1].width.saturating_sub(2) as usize;
     let input_text = if app.focus == AppFocus::UrlInput {
         // Show cursor when focused
-        let before_cursor = &app.url_input[..app.cursor_position];
-        let after_cursor = &app.url_input[app.cursor_position..];
+        let cursor = text::snap(&app.url_input, app.cursor_position);
+        app.url_scroll = text::scroll_start(&app.url_input, cursor, app.url_scroll, url_columns);
+        let before_cursor = &app.url_input[app.url_scroll..cursor];
+        let after_cursor = &app.url_input[cursor..];
 
         Line::from(vec![
             Span::raw(before_cursor),
@@ -58,6 +84,7 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
             Span::raw(after_cursor),
         ])
     } else {
+        app.url_scroll = 0;
         Line::from(app.url_input.as_str())
     };
 
@@ -75,150 +102,1030 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
         );
     frame.render_widget(input_widget, input_chunks[1]);
 
-    // Headers input
-    let headers_text = if app.focus == AppFocus::HeadersInput {
-        // Show cursor when focused
-        let mut lines_with_cursor = Vec::new();
-        for (i, line) in app.headers_input.iter().enumerate() {
-            if i == app.headers_cursor_line {
-                let before = &line[..app.headers_cursor_col.min(line.len())];
-                let after = &line[app.headers_cursor_col.min(line.len())..];
-                lines_with_cursor.push(Line::from(vec![
-                    Span::raw(before),
-                    Span::styled("█", Style::default().fg(Color::Cyan)),
-                    Span::raw(after),
-                ]));
-            } else {
-                lines_with_cursor.push(Line::from(line.as_str()));
-            }
-        }
-        lines_with_cursor
-    } else {
-        app.headers_input
-            .iter()
-            .map(|line| Line::from(line.as_str()))
-            .collect()
-    };
+    // Query params table
+    let params_text = table_lines(
+        &app.params,
+        app.focus == AppFocus::ParamsInput,
+        "No query params, type to add one",
+    );
 
-    let headers_widget = Paragraph::new(headers_text)
+    let params_widget = Paragraph::new(params_text)
         .style(Style::default().fg(Color::DarkGray))
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Headers (Key: Value per line)")
-                .border_style(if app.focus == AppFocus::HeadersInput {
+                .title("Params (key = value)")
+                .border_style(if app.focus == AppFocus::ParamsInput {
                     Style::default().fg(Color::Cyan)
                 } else {
                     Style::default()
                 }),
         )
-        .scroll((app.headers_scroll, 0));
-    frame.render_widget(headers_widget, chunks[1]);
+        .scroll((app.params.scroll, 0));
+    frame.render_widget(params_widget, chunks[1]);
 
-    // Body input
-    let body_text = if app.focus == AppFocus::BodyInput {
-        // Show cursor when focused
-        let mut lines_with_cursor = Vec::new();
-        for (i, line) in app.body_input.iter().enumerate() {
-            if i == app.body_cursor_line {
-                let before = &line[..app.body_cursor_col.min(line.len())];
-                let after = &line[app.body_cursor_col.min(line.len())..];
-                lines_with_cursor.push(Line::from(vec![
-                    Span::raw(before),
-                    Span::styled("█", Style::default().fg(Color::Cyan)),
-                    Span::raw(after),
-                ]));
+    // Headers input
+    let headers_focused = app.focus == AppFocus::HeadersInput;
+    let header_errors = headers::errors(&app.headers.lines);
+    let headers_text: Vec<Line> = app
+        .headers
+        .lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let style = if header_errors.iter().any(|(index, _)| *index == i) {
+                Style::default()
+                    .fg(Color::Red)
+                    .add_modifier(Modifier::UNDERLINED)
+            } else if line.trim_start().starts_with('#') {
+                Style::default().add_modifier(Modifier::DIM)
             } else {
-                lines_with_cursor.push(Line::from(line.as_str()));
+                Style::default()
+            };
+            let mut line = editor_line(
+                vec![Span::styled(line.clone(), style)],
+                i,
+                &app.headers,
+                headers_focused,
+            );
+            if headers_focused && i == app.headers.cursor_line {
+                if let Some(suggestion) = app.header_suggestion() {
+                    line.push_span(Span::styled(
+                        suggestion,
+                        Style::default().add_modifier(Modifier::DIM),
+                    ));
+                }
             }
-        }
-        lines_with_cursor
-    } else {
-        app.body_input
-            .iter()
-            .map(|line| Line::from(line.as_str()))
-            .collect()
+            line
+        })
+        .collect();
+    let headers_title = match header_errors.first() {
+        Some((index, error)) => format!("Headers (line {}: {})", index + 1, error),
+        None => "Headers (Key: Value per line)".to_string(),
     };
 
-    let body_widget = Paragraph::new(body_text)
+    let headers_widget = Paragraph::new(headers_text)
         .style(Style::default().fg(Color::DarkGray))
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Body (JSON)")
-                .border_style(if app.focus == AppFocus::BodyInput {
+                .title(headers_title)
+                .border_style(if !header_errors.is_empty() {
+                    Style::default().fg(Color::Red)
+                } else if app.focus == AppFocus::HeadersInput {
                     Style::default().fg(Color::Cyan)
                 } else {
                     Style::default()
                 }),
         )
-        .scroll((app.body_scroll, 0));
-    frame.render_widget(body_widget, chunks[2]);
+        .scroll((app.headers.scroll, 0));
+    frame.render_widget(headers_widget, chunks[2]);
+
+    // Body input
+    let is_ws = app.http_method == "WS";
+    let is_grpc = app.http_method.starts_with("GRPC");
+    let body_focused = app.focus == AppFocus::BodyInput;
+    if app.body_type == BodyType::GraphQl && !is_ws && !is_grpc {
+        render_graphql_editors(frame, app, chunks[3]);
+    } else if app.form_body_active() {
+        let form_widget = Paragraph::new(table_lines(
+            &app.form,
+            body_focused,
+            "No form fields, type to add one",
+        ))
+        .style(Style::default().fg(Color::DarkGray))
+        .block(editor_block(
+            format!("Body (Form, {})", app.body_type.hint()),
+            body_focused,
+        ))
+        .scroll((app.form.scroll, 0));
+        frame.render_widget(form_widget, chunks[3]);
+    } else {
+        let body_language = match app.body_type {
+            _ if is_ws && app.ws_frame_type == FrameType::Json => Language::Json,
+            _ if is_ws => Language::Plain,
+            _ if is_grpc => Language::Json,
+            BodyType::Json => Language::Json,
+            BodyType::Xml => Language::Xml,
+            _ => Language::Plain,
+        };
+        let body_error = app.body_json_error();
+        let body_text = editor_lines(
+            &app.body,
+            body_language,
+            app,
+            body_focused,
+            body_error.as_ref(),
+        );
+
+        let file_size = match app.body_type {
+            BodyType::File => app.body_file_size(),
+            _ => None,
+        };
+        let mut body_title = match (file_size, app.body_type.hint()) {
+            _ if is_ws && app.ws_frame_type == FrameType::Binary => {
+                "Message (Binary, hex bytes)".to_string()
+            }
+            _ if is_ws => format!("Message ({})", app.ws_frame_type.label()),
+            _ if is_grpc => "Request message (JSON, Ctrl+B: pick method)".to_string(),
+            (Some(size), _) => format!("Body ({}, {})", app.body_type.label(), format_size(size)),
+            (None, "") => format!("Body ({})", app.body_type.label()),
+            (None, hint) => format!("Body ({}, {})", app.body_type.label(), hint),
+        };
+        if let Some(error) = &body_error {
+            let name = if is_ws {
+                "Message"
+            } else if is_grpc {
+                "Request message"
+            } else {
+                "Body"
+            };
+            body_title = json_error_title(name, error, &app.body);
+        }
+        let body_widget = Paragraph::new(body_text)
+            .style(Style::default().fg(Color::DarkGray))
+            .block(editor_block(body_title, body_focused).border_style(
+                match (&body_error, body_focused) {
+                    (Some(_), _) => Style::default().fg(Color::Red),
+                    (None, true) => Style::default().fg(Color::Cyan),
+                    (None, false) => Style::default(),
+                },
+            ))
+            .scroll((app.body.scroll, 0));
+        frame.render_widget(body_widget, chunks[3]);
+    }
 
     // Status line
-    let status_text = if app.loading {
-        "Loading...".to_string()
-    } else if let Some(status_code) = app.status_code {
-        let mut parts = vec![format!("Status: {}", status_code)];
+    let status_text = if app.loading && app.is_streaming() {
+        let mut parts = vec!["Streaming...".to_string()];
+        if let Some(status_code) = app.status_code {
+            parts.push(format!("Status: {}", status_code));
+        }
+        parts.push(format!(
+            "Received: {}",
+            format_size(app.response_bytes.len() as u64)
+        ));
+        parts.join(" │ ")
+    } else if app.loading {
+        match (app.upload.get(), app.download.get()) {
+            (Some((sent, total)), _) if sent < total => format_progress("Uploading", sent, total),
+            (_, Some((received, total))) => format_progress("Downloading", received, total),
+            _ => "Loading...".to_string(),
+        }
+    } else {
+        let mut parts = Vec::new();
+        if let Some(status_code) = app.status_code {
+            parts.push(format!("Status: {}", status_code));
+        }
         if let Some(duration) = app.response_time {
-            let ms = duration.as_millis();
-            if ms >= 1000 {
-                parts.push(format!("Time: {:.2}s", duration.as_secs_f64()));
-            } else {
-                parts.push(format!("Time: {}ms", ms));
-            }
+            parts.push(format!("Time: {}", format_duration(duration)));
         }
         if let Some(size) = app.response_size {
-            if size >= 1024 * 1024 {
-                parts.push(format!("Size: {:.2}MB", size as f64 / (1024.0 * 1024.0)));
-            } else if size >= 1024 {
-                parts.push(format!("Size: {:.2}KB", size as f64 / 1024.0));
-            } else {
-                parts.push(format!("Size: {}B", size));
-            }
+            parts.push(format!("Size: {}", format_size(size as u64)));
+        }
+        if let Some(reused) = app.connection_reused {
+            parts.push(format!("Conn: {}", if reused { "reused" } else { "new" }));
+        }
+        if let Some(proxy) = app.http.settings().proxy.active_proxy(&app.url_input) {
+            parts.push(format!("Proxy: {}", proxy));
+        }
+        if app.ws.is_some() {
+            parts.push("WebSocket: open".to_string());
+        }
+        if let Some(message) = &app.status_message {
+            parts.push(message.clone());
         }
         parts.join(" │ ")
-    } else {
-        String::new()
     };
-    let status_widget = Paragraph::new(status_text).style(Style::default().fg(Color::DarkGray));
-    frame.render_widget(status_widget, chunks[3]);
+    let mut status_spans = Vec::new();
+    if app.http.settings().tls.insecure {
+        status_spans.push(Span::styled(
+            "⚠ TLS VERIFY OFF",
+            Style::default().fg(Color::Red),
+        ));
+        if !status_text.is_empty() {
+            status_spans.push(Span::raw(" │ "));
+        }
+    }
+    if app.download_mode {
+        status_spans.push(Span::styled(
+            "⬇ DOWNLOAD MODE",
+            Style::default().fg(Color::Yellow),
+        ));
+        if !status_text.is_empty() {
+            status_spans.push(Span::raw(" │ "));
+        }
+    }
+    if app.stream_mode && !app.download_mode {
+        status_spans.push(Span::styled(
+            "⇶ STREAM MODE",
+            Style::default().fg(Color::Yellow),
+        ));
+        if !status_text.is_empty() {
+            status_spans.push(Span::raw(" │ "));
+        }
+    }
+    status_spans.push(Span::raw(status_text));
+    let status_widget =
+        Paragraph::new(Line::from(status_spans)).style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(status_widget, chunks[4]);
 
     // Response
+    let mut title = vec![Span::raw("Response ")];
+    for tab in ResponseTab::ALL {
+        let label = if *tab == ResponseTab::Body && app.response_raw {
+            format!("{} (raw)", tab.title())
+        } else {
+            tab.title().to_string()
+        };
+        if *tab == app.response_tab {
+            title.push(Span::styled(
+                format!("[{}]", label),
+                Style::default().fg(Color::Cyan),
+            ));
+        } else {
+            title.push(Span::raw(format!(" {} ", label)));
+        }
+    }
     let response_block = Block::default()
         .borders(Borders::ALL)
-        .title("Response")
+        .title(Line::from(title))
         .border_style(if app.focus == AppFocus::Response {
             Style::default().fg(Color::Cyan)
         } else {
             Style::default()
         });
 
-    let response_widget = Paragraph::new(app.response.as_str())
-        .block(response_block)
-        .wrap(Wrap { trim: false })
-        .scroll((app.response_scroll, 0))
-        .style(Style::default().fg(Color::DarkGray));
+    // Filter and search bars above the response body
+    let mut bars = Vec::new();
+    if app.response_tab == ResponseTab::Body {
+        if app.filter_editing || !app.filter_input.is_empty() {
+            bars.push(filter_line(app));
+        }
+        if app.search_editing || !app.search_input.is_empty() {
+            bars.push(search_line(app));
+        }
+    }
+    let response_area = if bars.is_empty() {
+        chunks[5]
+    } else {
+        let areas = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(bars.len() as u16), Constraint::Min(0)])
+            .split(chunks[5]);
+        frame.render_widget(Paragraph::new(bars), areas[0]);
+        areas[1]
+    };
+    app.response_width = response_area.width.saturating_sub(2);
+    app.response_height = response_area.height.saturating_sub(2);
 
-    frame.render_widget(response_widget, chunks[4]);
+    let response_widget = match app.response_tab {
+        ResponseTab::Body => {
+            // Filter output is always JSON
+            let (text, content_type) = match &app.filter_output {
+                Some(Ok(filtered)) => (filtered.as_str(), Some("application/json")),
+                _ => (app.response.as_str(), app.response_content_type.as_deref()),
+            };
+            // Only the lines in view are copied and drawn, each taking at
+            // least one row, scrolled by the rows of the first one above the top
+            let width = app.response_width;
+            let scroll = app.response_scroll as usize;
+            let first = search::line_at_row(text, scroll, width);
+            let hidden = scroll.saturating_sub(search::row_of_line(text, first, width));
+            let mut lines: Vec<_> = app
+                .response_lines
+                .lines(
+                    app.response_generation,
+                    text,
+                    content_type,
+                    app.theme.palette(),
+                )
+                .iter()
+                .skip(first)
+                .take(app.response_height as usize)
+                .cloned()
+                .collect();
+            mark_matches(&mut lines, first, app);
+            search::wrapped(lines).scroll((hidden as u16, 0))
+        }
+        ResponseTab::Tree => {
+            let height = response_area.height.saturating_sub(2);
+            let lines = match app.json_tree.as_mut() {
+                Some(tree) => {
+                    tree.ensure_selected_visible(height);
+                    tree_lines(tree, &app.theme.palette(), height)
+                }
+                None => vec![Line::from("No JSON response to show as a tree")],
+            };
+            Paragraph::new(lines)
+        }
+        ResponseTab::Timing => {
+            let lines = match &app.timings {
+                Some(timings) => timing_lines(timings, response_area.width.saturating_sub(2)),
+                None => vec![Line::from("No timings yet, send a request first")],
+            };
+            Paragraph::new(lines)
+        }
+        ResponseTab::Diff => Paragraph::new(diff_lines(app)).scroll((app.response_scroll, 0)),
+        ResponseTab::Certificate => {
+            let lines = if app.certificates.is_empty() {
+                vec![Line::from(
+                    "No server certificate, send an https request first",
+                )]
+            } else {
+                let mut lines = certificate_lines(&app.certificates);
+                if let Some(note) = &app.chain_note {
+                    lines.push(Line::from(""));
+                    lines.push(Line::from(Span::styled(
+                        note.clone(),
+                        Style::default().fg(Color::Yellow),
+                    )));
+                }
+                lines
+            };
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .scroll((app.response_scroll, 0))
+        }
+    }
+    .block(response_block)
+    .style(Style::default().fg(Color::DarkGray));
+
+    frame.render_widget(response_widget, response_area);
 
     // Instructions
-    let instructions = if app.loading {
+    let instructions = if app.loading && app.is_streaming() {
+        "Ctrl+X: Stop Stream | ↑↓: Scroll | End: Follow | Tab/Shift+Tab: Switch Focus | Esc: Quit"
+    } else if app.loading {
         "Loading..."
+    } else if app.search_editing {
+        "Type a regex | Enter: Done | Tab: Toggle Case | Esc: Clear"
+    } else if app.filter_editing {
+        "Type a jq (.key) or JSONPath ($.key) filter | Enter: Apply | Esc: Clear"
+    } else if app.settings_open {
+        if app.settings_edit.is_some() {
+            "Enter: Apply | Esc: Cancel Edit"
+        } else {
+            "↑↓: Select | Enter: Edit/Toggle | Esc/F2: Close"
+        }
     } else {
         match app.focus {
+            AppFocus::MethodSelector if app.method_edit.is_some() => {
+                "Enter: Use Method | Backspace: Delete | Esc: Cancel"
+            }
             AppFocus::MethodSelector => {
-                "↑↓: Change Method | Enter: Send | Tab/Shift+Tab: Switch Focus | Esc: Quit"
+                "↑↓: Change Method | Type: Custom Method | Enter: Send | Tab/Shift+Tab: Switch Focus | F2: Settings | F3: Theme | F4: Copy as curl | Esc: Quit"
             }
-            AppFocus::UrlInput => "Enter: Send | Tab/Shift+Tab: Switch Focus | ←→: Move Cursor | Esc: Quit",
-            AppFocus::HeadersInput => "Ctrl+T: Indent | Ctrl+S: Send | Tab/Shift+Tab: Switch Focus | Esc: Quit",
-            AppFocus::BodyInput => "Ctrl+T: Indent | Ctrl+F: Format | Ctrl+S: Send | Tab/Shift+Tab: Switch Focus | Esc: Quit",
-            AppFocus::Response => "↑↓: Scroll | Tab/Shift+Tab: Switch Focus | Esc: Quit",
+            AppFocus::UrlInput => "Enter: Send | Tab/Shift+Tab: Switch Focus | ←→: Move Cursor | Ctrl+C: Copy URL | F4: Copy as curl | Esc: Quit",
+            AppFocus::ParamsInput => "Enter: Next Field/Row | Ctrl+E: Enable/Disable | Ctrl+D: Delete Row | Ctrl+S: Send | Esc: Quit",
+            AppFocus::HeadersInput => "Ctrl+Space: Complete | Ctrl+Z/Y: Undo/Redo | Shift+Arrows: Select | Ctrl+C/X/V: Copy/Cut/Paste | Ctrl+E: $EDITOR | Ctrl+S: Send | Esc: Quit",
+            AppFocus::BodyInput if app.http_method == "WS" => "Ctrl+T: Indent | Ctrl+B: Frame Type | Ctrl+S: Connect/Send | Tab/Shift+Tab: Switch Focus | Esc: Quit",
+            AppFocus::BodyInput if app.http_method.starts_with("GRPC") => "Ctrl+B: Pick Method | Ctrl+F: Format | Ctrl+T: Indent | Ctrl+S: Send | Tab/Shift+Tab: Switch Focus | Esc: Quit",
+            AppFocus::BodyInput if app.body_type == BodyType::GraphQl => "Ctrl+G: Query/Variables | Ctrl+R: Fetch Schema | Ctrl+Space: Complete | Ctrl+B: Body Type | Ctrl+S: Send | Tab/Shift+Tab: Switch Focus | Esc: Quit",
+            AppFocus::BodyInput => "Ctrl+Z/Y: Undo/Redo | Ctrl+C/X/V: Copy/Cut/Paste | Ctrl+E: $EDITOR | Ctrl+F: Format | Ctrl+B: Body Type | Ctrl+S: Send | Esc: Quit",
+            AppFocus::Response => if app.response_tab == ResponseTab::Tree {
+                "↑↓: Select | Enter/Space: Expand/Collapse | c: Copy Value | p: Copy Path | ←→: Switch Tab | Esc: Quit"
+            } else if app.response_tab == ResponseTab::Diff {
+                "↑↓: Scroll | ←→: Switch Tab | Ctrl+P: Pin Response | m: Text/JSON Diff | u: Unpin | Esc: Quit"
+            } else {
+                "↑↓: Scroll | ←→: Switch Tab | /: Search | n/N: Next/Prev | f: Filter | r: Raw/Pretty | y: Copy Body | Ctrl+P: Pin | Ctrl+S: Save | Ctrl+D: Download Mode | Ctrl+E: Stream Mode | Ctrl+X: Stop/Close | Tab/Shift+Tab: Switch Focus | F2: Settings | Esc: Quit"
+            },
         }
     };
     let instructions_widget =
         Paragraph::new(instructions).style(Style::default().fg(Color::DarkGray));
-    frame.render_widget(instructions_widget, chunks[5]);
+    frame.render_widget(instructions_widget, chunks[6]);
+
+    if app.settings_open {
+        render_settings(frame, app);
+    }
+}
+
+/// Filter bar with the expression, a cursor while editing and any error
+fn filter_line(app: &App) -> Line<'static> {
+    let label = Span::styled("Filter: ", Style::default().fg(Color::Cyan));
+    let input = vec![Span::raw(app.filter_input.clone())];
+    let mut line = if app.filter_editing {
+        with_cursor(input, app.filter_cursor)
+    } else {
+        Line::from(input)
+    };
+    line.spans.insert(0, label);
+    if let Some(Err(error)) = &app.filter_output {
+        line.spans.push(Span::styled(
+            format!("  ✗ {}", error),
+            Style::default().fg(Color::Red),
+        ));
+    }
+    line
+}
+
+/// Search bar with the pattern, match counter, case mode and any error
+fn search_line(app: &App) -> Line<'static> {
+    let label = Span::styled("Search: ", Style::default().fg(Color::Cyan));
+    let input = vec![Span::raw(app.search_input.clone())];
+    let mut line = if app.search_editing {
+        with_cursor(input, app.search_cursor)
+    } else {
+        Line::from(input)
+    };
+    line.spans.insert(0, label);
+
+    let counter = match (&app.search_error, app.search_matches.len()) {
+        (Some(error), _) => Span::styled(format!("  ✗ {}", error), Style::default().fg(Color::Red)),
+        (None, 0) if !app.search_input.is_empty() => {
+            Span::styled("  No matches", Style::default().fg(Color::Red))
+        }
+        (None, 0) => Span::raw(""),
+        (None, count) => Span::raw(format!("  {}/{}", app.search_index + 1, count)),
+    };
+    line.spans.push(counter);
+    line.spans.push(Span::styled(
+        if app.search_case_sensitive {
+            "  [Aa]"
+        } else {
+            "  [aa]"
+        },
+        Style::default().fg(Color::DarkGray),
+    ));
+    line
+}
+
+/// Highlight search matches in the response lines starting at line `first`,
+/// the current one brighter
+fn mark_matches(lines: &mut [Line<'static>], first: usize, app: &App) {
+    let other = Style::default().bg(Color::DarkGray).fg(Color::White);
+    let current = Style::default().bg(Color::Yellow).fg(Color::Black);
+
+    for (index, found) in app.search_matches.iter().enumerate() {
+        let Some(line) = found
+            .line
+            .checked_sub(first)
+            .and_then(|index| lines.get_mut(index))
+        else {
+            continue;
+        };
+        let style = if index == app.search_index {
+            current
+        } else {
+            other
+        };
+        *line = restyle_range(std::mem::take(line), found.start, found.end, style);
+    }
+}
+
+/// Patch `style` onto bytes `start..end` of a line, splitting spans as needed
+fn restyle_range(line: Line<'static>, start: usize, end: usize, style: Style) -> Line<'static> {
+    let mut spans = Vec::with_capacity(line.spans.len() + 2);
+    let mut offset = 0;
+
+    for span in line.spans {
+        let len = span.content.len();
+        let (span_start, span_end) = (offset, offset + len);
+        offset = span_end;
+        if span_end <= start || span_start >= end {
+            spans.push(span);
+            continue;
+        }
+        let from = start.saturating_sub(span_start);
+        let to = (end - span_start).min(len);
+        let content = span.content.as_ref();
+        for (text, piece_style) in [
+            (&content[..from], span.style),
+            (&content[from..to], span.style.patch(style)),
+            (&content[to..], span.style),
+        ] {
+            if !text.is_empty() {
+                spans.push(Span::styled(text.to_string(), piece_style));
+            }
+        }
+    }
+    Line::from(spans).style(line.style)
+}
+
+/// Visible rows of the JSON tree, with the selected row reversed
+fn tree_lines(tree: &JsonTree, palette: &Palette, height: u16) -> Vec<Line<'static>> {
+    tree.rows()
+        .into_iter()
+        .enumerate()
+        .skip(tree.scroll as usize)
+        .take(height as usize)
+        .map(|(i, row)| {
+            let marker = match (row.is_container(), row.expanded) {
+                (true, true) => "▾ ",
+                (true, false) => "▸ ",
+                (false, _) => "  ",
+            };
+            let mut spans = vec![Span::styled(
+                format!("{}{}", "  ".repeat(row.depth), marker),
+                palette.punctuation,
+            )];
+            if let Some(label) = &row.label {
+                spans.push(Span::styled(label.clone(), palette.key));
+                spans.push(Span::styled(": ", palette.punctuation));
+            }
+            let value_style = match row.value {
+                serde_json::Value::String(_) => palette.string,
+                serde_json::Value::Number(_) => palette.number,
+                serde_json::Value::Bool(_) => palette.boolean,
+                serde_json::Value::Null => palette.null,
+                _ => palette.punctuation,
+            };
+            spans.push(Span::styled(row.summary(), value_style));
+
+            let line = Line::from(spans);
+            if i == tree.selected {
+                line.style(Style::default().add_modifier(Modifier::REVERSED))
+            } else {
+                line
+            }
+        })
+        .collect()
+}
+
+/// Pinned and current response side by side, one diff row per line
+fn diff_lines(app: &App) -> Vec<Line<'static>> {
+    let Some(pinned) = &app.pinned else {
+        return vec![Line::from(
+            "No pinned response, press Ctrl+P to pin the current one",
+        )];
+    };
+    let half = (app.response_width as usize).saturating_sub(3) / 2;
+    let column = |text: &str| {
+        let text: String = text.chars().take(half).collect();
+        let padding = half.saturating_sub(text.chars().count());
+        format!("{}{}", text, " ".repeat(padding))
+    };
+    let separator = || Span::styled(" │ ", Style::default().fg(Color::DarkGray));
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(column(&format!("Pinned: {}", pinned.label)), bold),
+            separator(),
+            Span::styled(column(&format!("Current: {}", app.response_label)), bold),
+        ]),
+        Line::from(Span::styled(
+            format!(
+                "{} diff: {}",
+                app.effective_diff_mode().label(),
+                diff::summary(&app.diff_rows)
+            ),
+            Style::default().fg(Color::Cyan),
+        )),
+    ];
+    let removed = Style::default().fg(Color::Red);
+    let added = Style::default().fg(Color::Green);
+    for row in &app.diff_rows {
+        let (marker, left_style, right_style) = match row.kind {
+            DiffKind::Same => (' ', Style::default(), Style::default()),
+            DiffKind::Removed => ('-', removed, added),
+            DiffKind::Added => ('+', removed, added),
+            DiffKind::Changed => ('~', removed, added),
+        };
+        let side = |text: &Option<String>| match text {
+            Some(text) => column(&format!("{} {}", marker, text)),
+            None => column(""),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(side(&row.left), left_style),
+            separator(),
+            Span::styled(side(&row.right), right_style),
+        ]));
+    }
+    lines
+}
+
+/// Query and variables panes of the GraphQL body, side by side. The active
+/// pane is the one in the body editor; the other one is parked.
+fn render_graphql_editors(frame: &mut Frame, app: &mut App, area: Rect) {
+    // Only the active variables pane is validated as you type
+    let variables_error = app.body_json_error();
+    let panes = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(area);
+    let focused = app.focus == AppFocus::BodyInput;
+    let (query, variables) = if app.graphql_variables_active {
+        (&app.graphql_parked, &app.body)
+    } else {
+        (&app.body, &app.graphql_parked)
+    };
+
+    let schema = match &app.graphql_schema {
+        Some(_) => "Ctrl+Space: complete",
+        None => "Ctrl+R: fetch schema",
+    };
+    let query_focused = focused && !app.graphql_variables_active;
+    let variables_focused = focused && app.graphql_variables_active;
+    let variables_title = match &variables_error {
+        Some(error) => json_error_title("Variables", error, variables),
+        None => "Variables (JSON)".to_string(),
+    };
+    let editors = [
+        (
+            query,
+            Language::Plain,
+            format!("GraphQL Query ({})", schema),
+            query_focused,
+            None,
+        ),
+        (
+            variables,
+            Language::Json,
+            variables_title,
+            variables_focused,
+            variables_error.as_ref(),
+        ),
+    ];
+    for ((editor, language, title, focused, error), pane) in editors.into_iter().zip(panes.iter()) {
+        let mut block = editor_block(title, focused);
+        if error.is_some() {
+            block = block.border_style(Style::default().fg(Color::Red));
+        }
+        let widget = Paragraph::new(editor_lines(editor, language, app, focused, error))
+            .style(Style::default().fg(Color::DarkGray))
+            .block(block)
+            .scroll((editor.scroll, 0));
+        frame.render_widget(widget, *pane);
+    }
+}
+
+/// Highlighted editor lines with the cursor drawn at `cursor` and the line
+/// of a JSON `error` underlined
+fn editor_lines(
+    editor: &Editor,
+    language: Language,
+    app: &App,
+    focused: bool,
+    error: Option<&JsonError>,
+) -> Vec<Line<'static>> {
+    let mut highlighter = Highlighter::new(language, app.theme.palette());
+    editor
+        .lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let mut spans = highlighter.line(line);
+            if let Some(error) = error.filter(|error| error.line == i) {
+                spans = with_error(spans, line, error.column);
+            }
+            editor_line(spans, i, editor, focused)
+        })
+        .collect()
+}
+
+/// Underline a line and mark the character at byte `column` in red
+fn with_error(spans: Vec<Span<'static>>, line: &str, column: usize) -> Vec<Span<'static>> {
+    let spans = restyle(
+        spans,
+        0,
+        line.len(),
+        Style::default().add_modifier(Modifier::UNDERLINED),
+    );
+    let end = text::next_boundary(line, column);
+    restyle(
+        spans,
+        column,
+        end,
+        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+    )
+}
+
+/// Title of a JSON editor with an error, with the column counted in cells
+fn json_error_title(name: &str, error: &JsonError, editor: &Editor) -> String {
+    let line = editor
+        .lines
+        .get(error.line)
+        .map(String::as_str)
+        .unwrap_or_default();
+    format!(
+        "{} (line {}, column {}: {} | Ctrl+L: go to error)",
+        name,
+        error.line + 1,
+        text::column(line, error.column.min(line.len())) + 1,
+        error.message
+    )
+}
+
+/// Line `index` of an editor with its selection and, when focused, cursor
+fn editor_line(
+    spans: Vec<Span<'static>>,
+    index: usize,
+    editor: &Editor,
+    focused: bool,
+) -> Line<'static> {
+    if !focused {
+        return Line::from(spans);
+    }
+    let line = &editor.lines[index];
+    let spans = match editor.selection() {
+        Some((start, end)) if (start.0..=end.0).contains(&index) => {
+            let from = if index == start.0 { start.1 } else { 0 };
+            let to = if index == end.0 { end.1 } else { line.len() };
+            restyle(
+                spans,
+                from,
+                to,
+                Style::default().add_modifier(Modifier::REVERSED),
+            )
+        }
+        _ => spans,
+    };
+    if index == editor.cursor_line {
+        with_cursor(spans, text::snap(line, editor.cursor_col))
+    } else {
+        Line::from(spans)
+    }
+}
+
+/// Patch the style of bytes `from..to` of a line
+fn restyle(spans: Vec<Span<'static>>, from: usize, to: usize, patch: Style) -> Vec<Span<'static>> {
+    let mut line = Vec::with_capacity(spans.len() + 2);
+    let mut offset = 0;
+    for span in spans {
+        let len = span.content.len();
+        let start = from.clamp(offset, offset + len) - offset;
+        let end = to.clamp(offset, offset + len) - offset;
+        offset += len;
+        if start == end {
+            line.push(span);
+            continue;
+        }
+        let content = span.content.as_ref();
+        for (part, selected) in [
+            (&content[..start], false),
+            (&content[start..end], true),
+            (&content[end..], false),
+        ] {
+            if part.is_empty() {
+                continue;
+            }
+            let style = if selected {
+                span.style.patch(patch)
+            } else {
+                span.style
+            };
+            line.push(Span::styled(part.to_string(), style));
+        }
+    }
+    line
+}
+
+fn editor_block(title: String, focused: bool) -> Block<'static> {
+    Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(if focused {
+            Style::default().fg(Color::Cyan)
+        } else {
+            Style::default()
+        })
+}
+
+/// Insert a block cursor at byte offset `col`, splitting the span it falls in
+fn with_cursor(spans: Vec<Span<'static>>, col: usize) -> Line<'static> {
+    let cursor = Span::styled("█", Style::default().fg(Color::Cyan));
+    let mut line = Vec::with_capacity(spans.len() + 2);
+    let mut offset = 0;
+    let mut placed = false;
+
+    for span in spans {
+        let len = span.content.len();
+        if !placed && col < offset + len {
+            let split = col - offset;
+            let (before, after) = span.content.split_at(split);
+            if !before.is_empty() {
+                line.push(Span::styled(before.to_string(), span.style));
+            }
+            line.push(cursor.clone());
+            line.push(Span::styled(after.to_string(), span.style));
+            placed = true;
+        } else {
+            line.push(span);
+        }
+        offset += len;
+    }
+    if !placed {
+        line.push(cursor);
+    }
+    Line::from(line)
+}
+
+fn format_size(size: u64) -> String {
+    if size >= 1024 * 1024 {
+        format!("{:.2}MB", size as f64 / (1024.0 * 1024.0))
+    } else if size >= 1024 {
+        format!("{:.2}KB", size as f64 / 1024.0)
+    } else {
+        format!("{}B", size)
+    }
+}
+
+fn format_progress(label: &str, done: u64, total: u64) -> String {
+    format!(
+        "{} {} / {} ({}%)",
+        label,
+        format_size(done),
+        format_size(total),
+        (done * 100 / total).min(100)
+    )
+}
+
+fn format_duration(duration: Duration) -> String {
+    let ms = duration.as_millis();
+    if ms >= 1000 {
+        format!("{:.2}s", duration.as_secs_f64())
+    } else {
+        format!("{}ms", ms)
+    }
+}
+
+/// Rows of a key/value table with checkboxes, showing the cursor in the
+/// cell being edited
+fn table_lines(table: &ParamTable, focused: bool, empty: &'static str) -> Vec<Line<'static>> {
+    if table.rows.is_empty() {
+        return vec![Line::from(empty)];
+    }
+    table
+        .rows
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let checkbox = if row.enabled { "[x] " } else { "[ ] " };
+            let style = if row.enabled {
+                Style::default()
+            } else {
+                Style::default().fg(Color::Gray)
+            };
+            let editing = focused && i == table.index;
+            let mut spans = vec![Span::raw(checkbox)];
+            for (column, cell) in [
+                (ParamColumn::Key, row.key.as_str()),
+                (ParamColumn::Value, row.value.as_str()),
+            ] {
+                if column == ParamColumn::Value {
+                    spans.push(Span::raw(" = "));
+                }
+                if editing && column == table.column {
+                    let cursor = text::snap(cell, table.cursor);
+                    spans.push(Span::styled(cell[..cursor].to_string(), style));
+                    spans.push(Span::styled("█", Style::default().fg(Color::Cyan)));
+                    spans.push(Span::styled(cell[cursor..].to_string(), style));
+                } else {
+                    spans.push(Span::styled(cell.to_string(), style));
+                }
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
+
+/// Every certificate of a chain under a heading, leaf first
+fn certificate_lines(chain: &[CertificateInfo]) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    for (i, cert) in chain.iter().enumerate() {
+        if i > 0 {
+            lines.push(Line::from(""));
+        }
+        let role = match i {
+            0 => "leaf",
+            _ if i + 1 == chain.len() && cert.subject == cert.issuer => "root",
+            _ => "intermediate",
+        };
+        lines.push(Line::from(Span::styled(
+            format!("Certificate {} of {} ({})", i + 1, chain.len(), role),
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+        lines.extend(cert.lines().into_iter().map(|(label, value)| {
+            Line::from(vec![
+                Span::styled(format!("{:<13}", label), Style::default().fg(Color::Cyan)),
+                Span::raw(value),
+            ])
+        }));
+    }
+    lines
+}
+
+const TIMING_LABEL_WIDTH: usize = 10;
+const TIMING_VALUE_WIDTH: usize = 9;
+
+/// Waterfall of request phases, each bar starting where the previous ended
+fn timing_lines(timings: &RequestTimings, width: u16) -> Vec<Line<'static>> {
+    let colors = [
+        Color::Magenta,
+        Color::Blue,
+        Color::Yellow,
+        Color::Cyan,
+        Color::Green,
+    ];
+    let bar_width = (width as usize).saturating_sub(TIMING_LABEL_WIDTH + TIMING_VALUE_WIDTH + 1);
+    let total = timings.total().as_secs_f64();
+    let scale = |d: Duration| {
+        if total > 0.0 {
+            ((d.as_secs_f64() / total) * bar_width as f64).round() as usize
+        } else {
+            0
+        }
+    };
+
+    let mut lines = Vec::new();
+    let mut elapsed = Duration::ZERO;
+    for (i, (label, duration)) in timings.phases().into_iter().enumerate() {
+        let offset = scale(elapsed).min(bar_width);
+        let length = scale(duration).clamp(1, bar_width.saturating_sub(offset).max(1));
+        elapsed += duration;
+
+        lines.push(Line::from(vec![
+            Span::raw(format!("{:<w$}", label, w = TIMING_LABEL_WIDTH)),
+            Span::raw(" ".repeat(offset)),
+            Span::styled(
+                "█".repeat(length),
+                Style::default().fg(colors[i % colors.len()]),
+            ),
+            Span::raw(" ".repeat(bar_width.saturating_sub(offset + length) + 1)),
+            Span::raw(format!(
+                "{:>w$}",
+                format_duration(duration),
+                w = TIMING_VALUE_WIDTH
+            )),
+        ]));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(format!(
+        "{:<w$}{}",
+        "Total",
+        format_duration(timings.total()),
+        w = TIMING_LABEL_WIDTH
+    )));
+    if timings.connect.is_none() {
+        lines.push(Line::from("Connection reused, no DNS or connect phase"));
+    }
+    lines
+}
+
+fn render_settings(frame: &mut Frame, app: &App) {
+    let settings = app.http.settings();
+    let mut lines: Vec<Line> = SettingsField::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let selected = i == app.settings_index;
+            let value = match (&app.settings_edit, selected) {
+                (Some(edit), true) if field.is_secret() => {
+                    format!("{}█", "*".repeat(edit.chars().count()))
+                }
+                (Some(edit), true) => format!("{}█", edit),
+                _ => field.display_value(settings),
+            };
+            let style = if selected {
+                Style::default().fg(Color::Cyan)
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!("{} {}: ", if selected { ">" } else { " " }, field.label()),
+                    style,
+                ),
+                Span::styled(value, style),
+            ])
+        })
+        .collect();
+
+    if let Some(error) = &app.settings_error {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            error.as_str(),
+            Style::default().fg(Color::Red),
+        )));
+    }
+
+    let area = centered_rect(60, lines.len() as u16 + 2, frame.area());
+    // Keep the selected row visible when the terminal is too short for the list
+    let visible = area.height.saturating_sub(2) as usize;
+    let scroll = (app.settings_index + 1).saturating_sub(visible) as u16;
+    let widget = Paragraph::new(lines).scroll((scroll, 0)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Client Settings")
+            .border_style(Style::default().fg(Color::Cyan)),
+    );
+    frame.render_widget(Clear, area);
+    frame.render_widget(widget, area);
+}
+
+/// Rectangle of the given width percentage and height, centered in `area`
+fn centered_rect(percent_x: u16, height: u16, area: Rect) -> Rect {
+    let width = area.width * percent_x / 100;
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
 }
 
 #[cfg(test)]
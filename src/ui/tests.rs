@@ -223,7 +223,7 @@ fn test_ui_renders_with_headers_content() {
     let backend = TestBackend::new(80, 24);
     let mut terminal = Terminal::new(backend).unwrap();
     let mut app = App::new();
-    app.headers_input = vec![
+    app.headers.lines = vec![
         "Content-Type: application/json".to_string(),
         "Authorization: Bearer token123".to_string(),
     ];
@@ -238,7 +238,7 @@ fn test_ui_renders_with_body_content() {
     let backend = TestBackend::new(80, 24);
     let mut terminal = Terminal::new(backend).unwrap();
     let mut app = App::new();
-    app.body_input = vec![
+    app.body.lines = vec![
         "{".to_string(),
         "  \"name\": \"test\",".to_string(),
         "  \"value\": 123".to_string(),
@@ -256,9 +256,9 @@ fn test_ui_renders_headers_with_cursor() {
     let mut terminal = Terminal::new(backend).unwrap();
     let mut app = App::new();
     app.focus = AppFocus::HeadersInput;
-    app.headers_input = vec!["Content".to_string()];
-    app.headers_cursor_line = 0;
-    app.headers_cursor_col = 3;
+    app.headers.lines = vec!["Content".to_string()];
+    app.headers.cursor_line = 0;
+    app.headers.cursor_col = 3;
 
     terminal
         .draw(|f| ui(f, &mut app))
@@ -271,9 +271,9 @@ fn test_ui_renders_body_with_cursor() {
     let mut terminal = Terminal::new(backend).unwrap();
     let mut app = App::new();
     app.focus = AppFocus::BodyInput;
-    app.body_input = vec!["{}".to_string()];
-    app.body_cursor_line = 0;
-    app.body_cursor_col = 1;
+    app.body.lines = vec!["{}".to_string()];
+    app.body.cursor_line = 0;
+    app.body.cursor_col = 1;
 
     terminal
         .draw(|f| ui(f, &mut app))
@@ -285,7 +285,7 @@ fn test_ui_renders_with_multiline_headers() {
     let backend = TestBackend::new(80, 24);
     let mut terminal = Terminal::new(backend).unwrap();
     let mut app = App::new();
-    app.headers_input = vec![
+    app.headers.lines = vec![
         "Header1: value1".to_string(),
         "Header2: value2".to_string(),
         "Header3: value3".to_string(),
@@ -302,7 +302,7 @@ fn test_ui_renders_with_multiline_body() {
     let backend = TestBackend::new(80, 24);
     let mut terminal = Terminal::new(backend).unwrap();
     let mut app = App::new();
-    app.body_input = vec![
+    app.body.lines = vec![
         "{".to_string(),
         "  \"field1\": \"value1\",".to_string(),
         "  \"field2\": \"value2\",".to_string(),
@@ -322,7 +322,7 @@ fn test_ui_renders_with_empty_headers() {
     let backend = TestBackend::new(80, 24);
     let mut terminal = Terminal::new(backend).unwrap();
     let mut app = App::new();
-    app.headers_input = vec!["".to_string()];
+    app.headers.lines = vec!["".to_string()];
 
     terminal
         .draw(|f| ui(f, &mut app))
@@ -334,7 +334,7 @@ fn test_ui_renders_with_empty_body() {
     let backend = TestBackend::new(80, 24);
     let mut terminal = Terminal::new(backend).unwrap();
     let mut app = App::new();
-    app.body_input = vec!["".to_string()];
+    app.body.lines = vec!["".to_string()];
 
     terminal
         .draw(|f| ui(f, &mut app))
@@ -417,6 +417,16 @@ fn test_ui_renders_status_line_with_large_response_time() {
         .expect("UI should render with large response time (seconds format)");
 }
 
+#[test]
+fn test_format_progress_is_clamped() {
+    assert_eq!(
+        format_progress("Uploading", 50, 100),
+        "Uploading 50B / 100B (50%)"
+    );
+    // The body grew after its size was taken
+    assert!(format_progress("Uploading", 300, 100).ends_with("(100%)"));
+}
+
 #[test]
 fn test_ui_renders_status_line_with_large_response_size() {
     let backend = TestBackend::new(80, 30);
@@ -456,3 +466,489 @@ fn test_ui_renders_status_line_with_only_status_code() {
         .draw(|f| ui(f, &mut app))
         .expect("UI should render with only status code");
 }
+
+#[test]
+fn test_ui_renders_status_line_with_connection_reuse() {
+    let backend = TestBackend::new(80, 24);
+    let mut terminal = Terminal::new(backend).unwrap();
+    let mut app = App::new();
+    app.status_code = Some(200);
+    app.connection_reused = Some(true);
+
+    terminal
+        .draw(|f| ui(f, &mut app))
+        .expect("UI should render connection reuse in status line");
+}
+
+#[test]
+fn test_ui_renders_settings_overlay() {
+    let backend = TestBackend::new(80, 24);
+    let mut terminal = Terminal::new(backend).unwrap();
+    let mut app = App::new();
+    app.settings_open = true;
+    app.settings_edit = Some("15".to_string());
+    app.settings_error = Some("Expected a number of seconds".to_string());
+
+    terminal
+        .draw(|f| ui(f, &mut app))
+        .expect("UI should render settings overlay");
+}
+
+#[test]
+fn test_ui_renders_settings_overlay_in_small_terminal() {
+    let backend = TestBackend::new(20, 5);
+    let mut terminal = Terminal::new(backend).unwrap();
+    let mut app = App::new();
+    app.settings_open = true;
+
+    terminal
+        .draw(|f| ui(f, &mut app))
+        .expect("UI should render settings overlay in small terminal");
+}
+
+#[test]
+fn test_ui_renders_timing_tab_without_timings() {
+    let backend = TestBackend::new(80, 24);
+    let mut terminal = Terminal::new(backend).unwrap();
+    let mut app = App::new();
+    app.response_tab = ResponseTab::Timing;
+
+    terminal
+        .draw(|f| ui(f, &mut app))
+        .expect("UI should render empty timing tab");
+}
+
+#[test]
+fn test_ui_renders_timing_tab_with_timings() {
+    let backend = TestBackend::new(80, 24);
+    let mut terminal = Terminal::new(backend).unwrap();
+    let mut app = App::new();
+    app.response_tab = ResponseTab::Timing;
+    app.timings = Some(RequestTimings {
+        dns: Some(Duration::from_millis(12)),
+        connect: Some(Duration::from_millis(40)),
+        ttfb: Duration::from_millis(120),
+        download: Duration::from_millis(1500),
+    });
+
+    terminal
+        .draw(|f| ui(f, &mut app))
+        .expect("UI should render timing waterfall");
+}
+
+#[test]
+fn test_timing_lines_waterfall_layout() {
+    let timings = RequestTimings {
+        dns: None,
+        connect: None,
+        ttfb: Duration::from_millis(50),
+        download: Duration::from_millis(50),
+    };
+
+    let lines = timing_lines(&timings, 60);
+    let text: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+
+    assert!(text[0].starts_with("TTFB"));
+    assert!(text[1].starts_with("Download"));
+    // Download starts where TTFB ends
+    let ttfb_bar = text[0].find('█').unwrap();
+    let download_bar = text[1].find('█').unwrap();
+    assert!(download_bar > ttfb_bar);
+    assert!(text.iter().any(|line| line.starts_with("Total")));
+    assert!(text.iter().any(|line| line.contains("Connection reused")));
+}
+
+#[test]
+fn test_timing_lines_zero_width() {
+    let timings = RequestTimings::default();
+
+    let lines = timing_lines(&timings, 0);
+
+    assert!(!lines.is_empty());
+}
+
+#[test]
+fn test_ui_renders_certificate_tab_without_certificate() {
+    let backend = TestBackend::new(80, 24);
+    let mut terminal = Terminal::new(backend).unwrap();
+    let mut app = App::new();
+    app.response_tab = ResponseTab::Certificate;
+
+    terminal
+        .draw(|f| ui(f, &mut app))
+        .expect("UI should render empty certificate tab");
+}
+
+#[test]
+fn test_ui_renders_certificate_tab() {
+    let backend = TestBackend::new(100, 60);
+    let mut terminal = Terminal::new(backend).unwrap();
+    let mut app = App::new();
+    app.response_tab = ResponseTab::Certificate;
+    let certificate = |subject: &str, issuer: &str, is_ca| crate::tls::CertificateInfo {
+        subject: subject.to_string(),
+        issuer: issuer.to_string(),
+        serial: "01:02".to_string(),
+        not_before: "Jan  1 00:00:00 2025 +00:00".to_string(),
+        not_after: "Jan  1 00:00:00 2026 +00:00".to_string(),
+        alt_names: vec!["example.com".to_string()],
+        is_ca,
+        sha256: "AA:BB".to_string(),
+    };
+    app.certificates = vec![
+        certificate("CN=example.com", "CN=Example CA", false),
+        certificate("CN=Example CA", "CN=Example CA", true),
+    ];
+
+    terminal
+        .draw(|f| ui(f, &mut app))
+        .expect("UI should render certificate details");
+
+    let screen: String = terminal
+        .backend()
+        .buffer()
+        .content()
+        .iter()
+        .map(|cell| cell.symbol())
+        .collect();
+    assert!(screen.contains("Certificate 1 of 2 (leaf)"));
+    assert!(screen.contains("Certificate 2 of 2 (root)"));
+}
+
+#[test]
+fn test_ui_flags_insecure_tls_in_status_line() {
+    let backend = TestBackend::new(80, 24);
+    let mut terminal = Terminal::new(backend).unwrap();
+    let mut app = App::new();
+    let mut settings = app.http.settings().clone();
+    settings.tls.insecure = true;
+    app.apply_client_settings(settings);
+
+    terminal.draw(|f| ui(f, &mut app)).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let screen: String = buffer
+        .content()
+        .iter()
+        .map(|cell| cell.symbol().to_string())
+        .collect();
+    assert!(screen.contains("TLS VERIFY OFF"));
+}
+
+#[test]
+fn test_ui_shows_active_proxy_in_status_line() {
+    let backend = TestBackend::new(100, 24);
+    let mut terminal = Terminal::new(backend).unwrap();
+    let mut app = App::new();
+    let mut settings = app.http.settings().clone();
+    settings.proxy.mode = crate::proxy::ProxyMode::Manual;
+    settings.proxy.https = "http://user:pw@proxy.local:8080".to_string();
+    app.apply_client_settings(settings);
+
+    terminal.draw(|f| ui(f, &mut app)).unwrap();
+
+    let screen: String = terminal
+        .backend()
+        .buffer()
+        .content()
+        .iter()
+        .map(|cell| cell.symbol().to_string())
+        .collect();
+    assert!(screen.contains("Proxy: http://proxy.local:8080"));
+    assert!(!screen.contains("pw@"));
+}
+
+#[test]
+fn test_ui_settings_overlay_scrolls_to_selection() {
+    let backend = TestBackend::new(80, 10);
+    let mut terminal = Terminal::new(backend).unwrap();
+    let mut app = App::new();
+    app.settings_open = true;
+    app.settings_index = crate::http::SettingsField::ALL.len() - 1;
+
+    terminal.draw(|f| ui(f, &mut app)).unwrap();
+
+    let screen: String = terminal
+        .backend()
+        .buffer()
+        .content()
+        .iter()
+        .map(|cell| cell.symbol().to_string())
+        .collect();
+    assert!(screen.contains("No proxy for"));
+}
+
+#[test]
+fn test_ui_renders_params_table() {
+    let backend = TestBackend::new(80, 30);
+    let mut terminal = Terminal::new(backend).unwrap();
+    let mut app = App::new();
+    app.url_input = "http://x/?q=caf%C3%A9&debug=1".to_string();
+    app.sync_params_from_url();
+    app.params.rows[1].enabled = false;
+    app.focus = AppFocus::ParamsInput;
+    app.params.column = crate::params::ParamColumn::Value;
+    app.params.cursor = "caf".len();
+
+    terminal.draw(|f| ui(f, &mut app)).unwrap();
+
+    let screen: String = terminal
+        .backend()
+        .buffer()
+        .content()
+        .iter()
+        .map(|cell| cell.symbol().to_string())
+        .collect();
+    assert!(screen.contains("[x] q = caf█é"));
+    assert!(screen.contains("[ ] debug = 1"));
+}
+
+#[test]
+fn test_ui_renders_empty_params() {
+    let backend = TestBackend::new(80, 30);
+    let mut terminal = Terminal::new(backend).unwrap();
+    let mut app = App::new();
+    app.focus = AppFocus::ParamsInput;
+
+    terminal
+        .draw(|f| ui(f, &mut app))
+        .expect("UI should render empty params table");
+}
+
+#[test]
+fn test_ui_body_title_shows_body_type() {
+    let backend = TestBackend::new(100, 30);
+    let mut terminal = Terminal::new(backend).unwrap();
+    let mut app = App::new();
+    app.body_type = crate::body::BodyType::FormUrlEncoded;
+
+    terminal.draw(|f| ui(f, &mut app)).unwrap();
+
+    let screen: String = terminal
+        .backend()
+        .buffer()
+        .content()
+        .iter()
+        .map(|cell| cell.symbol().to_string())
+        .collect();
+    assert!(screen.contains("Body (Form, key = value)"));
+    assert!(screen.contains("No form fields, type to add one"));
+}
+
+#[test]
+fn test_with_cursor_splits_highlighted_span() {
+    let spans = vec![
+        Span::styled("\"key\"", Style::default().fg(Color::Cyan)),
+        Span::raw(": 1"),
+    ];
+
+    let line = with_cursor(spans, 2);
+
+    assert_eq!(line.to_string(), "\"k█ey\": 1");
+    assert_eq!(line.spans[0].style, Style::default().fg(Color::Cyan));
+    assert_eq!(line.spans[2].style, Style::default().fg(Color::Cyan));
+    assert_eq!(with_cursor(vec![Span::raw("ab")], 2).to_string(), "ab█");
+}
+
+#[test]
+fn test_ui_highlights_json_response() {
+    let backend = TestBackend::new(80, 30);
+    let mut terminal = Terminal::new(backend).unwrap();
+    let mut app = App::new();
+    app.response = "{\n  \"name\": \"snorlax\"\n}".to_string();
+
+    terminal.draw(|f| ui(f, &mut app)).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let palette = app.theme.palette();
+    let key_cell = buffer
+        .content()
+        .iter()
+        .zip(buffer.content().iter().skip(1))
+        .find(|(cell, next)| cell.symbol() == "\"" && next.symbol() == "n")
+        .map(|(cell, _)| cell)
+        .expect("key should be rendered");
+    assert_eq!(key_cell.fg, palette.key.fg.unwrap());
+}
+
+#[test]
+fn test_ui_renders_json_tree() {
+    let backend = TestBackend::new(80, 40);
+    let mut terminal = Terminal::new(backend).unwrap();
+    let mut app = App::new();
+    app.response_tab = ResponseTab::Tree;
+    app.json_tree = Some(crate::tree::JsonTree::new(serde_json::json!({
+        "items": [{"id": 1}, {"id": 2}],
+        "next": null
+    })));
+
+    terminal.draw(|f| ui(f, &mut app)).unwrap();
+
+    let screen: String = terminal
+        .backend()
+        .buffer()
+        .content()
+        .iter()
+        .map(|cell| cell.symbol().to_string())
+        .collect();
+    assert!(screen.contains("▾ \"items\": ["));
+    assert!(screen.contains("▸ [0]: {…} 1 key"));
+    assert!(screen.contains("\"next\": null"));
+}
+
+#[test]
+fn test_ui_renders_filter_error_inline() {
+    let backend = TestBackend::new(100, 40);
+    let mut terminal = Terminal::new(backend).unwrap();
+    let mut app = App::new();
+    app.filter_input = ".a[".to_string();
+    app.filter_output = Some(Err("Missing ']'".to_string()));
+
+    terminal.draw(|f| ui(f, &mut app)).unwrap();
+
+    let screen: String = terminal
+        .backend()
+        .buffer()
+        .content()
+        .iter()
+        .map(|cell| cell.symbol().to_string())
+        .collect();
+    assert!(screen.contains("Filter: .a[  ✗ Missing ']'"));
+}
+
+#[test]
+fn test_restyle_range_across_spans() {
+    let red = Style::default().fg(Color::Red);
+    let mark = Style::default().bg(Color::Yellow);
+    let line = Line::from(vec![Span::styled("\"name\"", red), Span::raw(": 1")]);
+
+    let line = restyle_range(line, 3, 8, mark);
+
+    let pieces: Vec<(String, Style)> = line
+        .spans
+        .iter()
+        .map(|span| (span.content.to_string(), span.style))
+        .collect();
+    assert_eq!(
+        pieces,
+        vec![
+            ("\"na".to_string(), red),
+            ("me\"".to_string(), red.patch(mark)),
+            (": ".to_string(), mark),
+            ("1".to_string(), Style::default()),
+        ]
+    );
+}
+
+#[test]
+fn test_ui_renders_search_counter() {
+    let backend = TestBackend::new(100, 40);
+    let mut terminal = Terminal::new(backend).unwrap();
+    let mut app = App::new();
+    app.response = "foo bar foo".to_string();
+    app.search_input = "foo".to_string();
+    app.update_search();
+    app.search_next();
+
+    terminal.draw(|f| ui(f, &mut app)).unwrap();
+
+    let screen: String = terminal
+        .backend()
+        .buffer()
+        .content()
+        .iter()
+        .map(|cell| cell.symbol().to_string())
+        .collect();
+    assert!(screen.contains("Search: foo  2/2  [aa]"));
+}
+
+#[test]
+fn test_ui_draws_scrolled_response_from_the_first_visible_line() {
+    let backend = TestBackend::new(100, 40);
+    let mut terminal = Terminal::new(backend).unwrap();
+    let mut app = App::new();
+    app.response = (0..100)
+        .map(|i| format!("row {:02}", i))
+        .collect::<Vec<_>>()
+        .join("\n");
+    app.search_input = "row 51".to_string();
+    app.update_search();
+    app.response_scroll = 50;
+
+    terminal.draw(|f| ui(f, &mut app)).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let screen: Vec<String> = buffer
+        .content()
+        .iter()
+        .map(|cell| cell.symbol().to_string())
+        .collect();
+    let joined = screen.concat();
+    assert!(joined.contains("row 50"));
+    assert!(!joined.contains("row 49"));
+    let start = screen
+        .windows(6)
+        .rposition(|cells| cells.concat() == "row 51")
+        .unwrap();
+    assert_eq!(buffer.content()[start].bg, Color::Yellow);
+}
+
+#[test]
+fn test_ui_renders_wide_characters_at_cursor() {
+    let backend = TestBackend::new(40, 24);
+    let mut terminal = Terminal::new(backend).unwrap();
+    let mut app = App::new();
+    app.focus = AppFocus::UrlInput;
+    app.url_input = format!("https://example.com/{}", "日本語".repeat(10));
+    // A byte offset inside a character is drawn at the character before it
+    app.cursor_position = app.url_input.len() - 1;
+    app.headers.lines = vec!["X-Name: é🎉".to_string()];
+    app.headers.cursor_col = "X-Name: é".len() + 1;
+
+    terminal
+        .draw(|f| ui(f, &mut app))
+        .expect("UI should render multibyte text around the cursor");
+
+    // The URL scrolled so the cursor stays inside the 38 column field
+    assert!(app.url_scroll > 0);
+    assert!(text::width(&app.url_input[app.url_scroll..]) < 38);
+}
+
+#[test]
+fn test_ui_marks_malformed_headers() {
+    let backend = TestBackend::new(100, 24);
+    let mut terminal = Terminal::new(backend).unwrap();
+    let mut app = App::new();
+    app.headers.set_text("Accept: */*\nnot a header");
+
+    terminal.draw(|f| ui(f, &mut app)).unwrap();
+
+    let screen: String = terminal
+        .backend()
+        .buffer()
+        .content()
+        .iter()
+        .map(|cell| cell.symbol().to_string())
+        .collect();
+    assert!(screen.contains("Headers (line 2: Missing ':' between name and value)"));
+}
+
+#[test]
+fn test_ui_shows_body_json_error_in_title() {
+    let backend = TestBackend::new(120, 24);
+    let mut terminal = Terminal::new(backend).unwrap();
+    let mut app = App::new();
+    app.focus = AppFocus::BodyInput;
+    app.body.set_text("{\n  \"a\" 1\n}");
+
+    terminal.draw(|f| ui(f, &mut app)).unwrap();
+
+    let screen: String = terminal
+        .backend()
+        .buffer()
+        .content()
+        .iter()
+        .map(|cell| cell.symbol().to_string())
+        .collect();
+    assert!(screen.contains("Body (line 2, column 7: expected `:` | Ctrl+L: go to error)"));
+}
@@ -0,0 +1,409 @@
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context};
+use chrono::{DateTime, Local};
+use tungstenite::client::IntoClientRequest;
+use tungstenite::http::{HeaderName, HeaderValue};
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Connector, Message, WebSocket};
+
+use crate::download;
+use crate::proxy::{self, ProxySettings};
+use crate::tls::{self, TlsSettings};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a read waits before the connection thread checks for frames to send
+const READ_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// Payload bytes shown for binary frames in the log
+const BINARY_PREVIEW: usize = 32;
+
+/// Kind of frame the composer sends
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrameType {
+    Text,
+    Json,
+    Binary,
+}
+
+impl FrameType {
+    pub const ALL: &'static [FrameType] = &[FrameType::Text, FrameType::Json, FrameType::Binary];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FrameType::Text => "Text",
+            FrameType::Json => "JSON",
+            FrameType::Binary => "Binary",
+        }
+    }
+
+    pub fn next(&self) -> FrameType {
+        let index = Self::ALL.iter().position(|t| t == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Frame for the composer text. JSON is validated and sent compact;
+    /// binary frames are written as hex bytes, e.g. `48 65 6c 6c 6f`.
+    pub fn encode(&self, text: &str) -> Result<Message, String> {
+        match self {
+            FrameType::Text => Ok(Message::text(text)),
+            FrameType::Json => {
+                let value: serde_json::Value =
+                    serde_json::from_str(text).map_err(|e| format!("Invalid JSON: {}", e))?;
+                Ok(Message::text(value.to_string()))
+            }
+            FrameType::Binary => parse_hex(text).map(Message::binary),
+        }
+    }
+}
+
+fn parse_hex(text: &str) -> Result<Vec<u8>, String> {
+    let digits: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let digits: Vec<char> = digits
+        .strip_prefix("0x")
+        .unwrap_or(&digits)
+        .chars()
+        .collect();
+    if !digits.len().is_multiple_of(2) {
+        return Err("Binary frames need an even number of hex digits".to_string());
+    }
+    digits
+        .chunks(2)
+        .map(|pair| match (pair[0].to_digit(16), pair[1].to_digit(16)) {
+            (Some(high), Some(low)) => Ok((high * 16 + low) as u8),
+            _ => Err(format!("Invalid hex byte '{}{}'", pair[0], pair[1])),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Sent,
+    Received,
+    /// Connection events and errors
+    Info,
+}
+
+impl Direction {
+    pub fn arrow(&self) -> &'static str {
+        match self {
+            Direction::Sent => "→",
+            Direction::Received => "←",
+            Direction::Info => "•",
+        }
+    }
+}
+
+/// One line of the message log
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    pub time: DateTime<Local>,
+    pub direction: Direction,
+    pub text: String,
+}
+
+impl LogEntry {
+    pub fn new(direction: Direction, text: impl Into<String>) -> Self {
+        Self {
+            time: Local::now(),
+            direction,
+            text: text.into(),
+        }
+    }
+
+    pub fn line(&self) -> String {
+        format!(
+            "{} {} {}",
+            self.time.format("%H:%M:%S%.3f"),
+            self.direction.arrow(),
+            self.text
+        )
+    }
+}
+
+/// The message log as shown in the response pane
+pub fn format_log(entries: &[LogEntry]) -> String {
+    entries
+        .iter()
+        .map(LogEntry::line)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Log text for a frame
+pub fn describe(message: &Message) -> String {
+    match message {
+        Message::Text(text) => format!("text {}", text.as_str()),
+        Message::Binary(bytes) => {
+            let shown = &bytes[..bytes.len().min(BINARY_PREVIEW)];
+            let hex: Vec<String> = shown.iter().map(|byte| format!("{:02x}", byte)).collect();
+            let more = if bytes.len() > BINARY_PREVIEW {
+                " …"
+            } else {
+                ""
+            };
+            format!("binary {} bytes: {}{}", bytes.len(), hex.join(" "), more)
+        }
+        Message::Ping(payload) => control("ping", payload),
+        Message::Pong(payload) => control("pong", payload),
+        Message::Close(Some(frame)) if frame.reason.is_empty() => {
+            format!("close {} ({:?})", u16::from(frame.code), frame.code)
+        }
+        Message::Close(Some(frame)) => format!(
+            "close {} ({:?}) {}",
+            u16::from(frame.code),
+            frame.code,
+            frame.reason.as_str()
+        ),
+        Message::Close(None) => "close".to_string(),
+        Message::Frame(frame) => format!("raw frame {} bytes", frame.len()),
+    }
+}
+
+fn control(name: &str, payload: &[u8]) -> String {
+    if payload.is_empty() {
+        name.to_string()
+    } else if download::is_binary(payload) {
+        format!("{} {} bytes", name, payload.len())
+    } else {
+        format!("{} {}", name, String::from_utf8_lossy(payload))
+    }
+}
+
+enum WsEvent {
+    Log(LogEntry),
+    Closed,
+}
+
+/// WebSocket connection run on a background thread. Frames to send go in
+/// through a channel and log entries come back through another one.
+pub struct WsConnection {
+    /// URL the connection was opened for
+    url: String,
+    commands: Sender<Message>,
+    events: Receiver<WsEvent>,
+    open: bool,
+}
+
+impl WsConnection {
+    /// Start connecting to `url`, through the proxy when one applies; the
+    /// outcome shows up in the log
+    pub fn connect(
+        url: &str,
+        headers: Vec<(String, String)>,
+        tls: &TlsSettings,
+        proxy: &ProxySettings,
+    ) -> Self {
+        let (commands, command_receiver) = mpsc::channel();
+        let (event_sender, events) = mpsc::channel();
+        let url = url.to_string();
+        let tls = tls.clone();
+        let proxy = proxy.clone();
+        let target = url.clone();
+        std::thread::spawn(move || {
+            run(
+                &target,
+                &headers,
+                &tls,
+                &proxy,
+                &command_receiver,
+                &event_sender,
+            );
+            let _ = event_sender.send(WsEvent::Closed);
+        });
+        Self {
+            url,
+            commands,
+            events,
+            open: true,
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Whether the connection is still being set up or open
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn send(&self, message: Message) {
+        let _ = self.commands.send(message);
+    }
+
+    /// Start the closing handshake
+    pub fn close(&self) {
+        self.send(Message::Close(None));
+    }
+
+    /// Log entries that arrived since the last poll
+    pub fn poll(&mut self) -> Vec<LogEntry> {
+        let mut entries = Vec::new();
+        for event in self.events.try_iter() {
+            match event {
+                WsEvent::Log(entry) => entries.push(entry),
+                WsEvent::Closed => self.open = false,
+            }
+        }
+        entries
+    }
+}
+
+type Socket = WebSocket<MaybeTlsStream<TcpStream>>;
+
+fn run(
+    url: &str,
+    headers: &[(String, String)],
+    tls: &TlsSettings,
+    proxy: &ProxySettings,
+    commands: &Receiver<Message>,
+    events: &Sender<WsEvent>,
+) {
+    let log = |direction, text: String| {
+        let _ = events.send(WsEvent::Log(LogEntry::new(direction, text)));
+    };
+
+    let mut socket = match open(url, headers, tls, proxy) {
+        Ok((socket, status)) => {
+            log(
+                Direction::Info,
+                format!("connected to {} ({})", url, status),
+            );
+            socket
+        }
+        Err(e) => {
+            log(Direction::Info, format!("connection failed: {:#}", e));
+            return;
+        }
+    };
+
+    loop {
+        loop {
+            let message = match commands.try_recv() {
+                Ok(message) => message,
+                Err(TryRecvError::Empty) => break,
+                // The app is gone, nobody is reading the log any more
+                Err(TryRecvError::Disconnected) => {
+                    let _ = socket.close(None);
+                    let _ = socket.flush();
+                    return;
+                }
+            };
+            let text = describe(&message);
+            let result = match message {
+                Message::Close(frame) => socket.close(frame),
+                message => socket.send(message),
+            };
+            match result {
+                Ok(()) => log(Direction::Sent, text),
+                Err(e) => log(Direction::Info, format!("send failed: {}", e)),
+            }
+        }
+
+        match socket.read() {
+            Ok(message) => log(Direction::Received, describe(&message)),
+            Err(tungstenite::Error::Io(e))
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) =>
+            {
+                // Push out queued frames such as automatic pong replies
+                let _ = socket.flush();
+            }
+            Err(tungstenite::Error::ConnectionClosed) => {
+                log(Direction::Info, "connection closed".to_string());
+                return;
+            }
+            Err(e) => {
+                log(Direction::Info, format!("connection lost: {}", e));
+                return;
+            }
+        }
+    }
+}
+
+/// Connect and run the opening handshake, returning the socket and the HTTP
+/// status of the upgrade response
+fn open(
+    url: &str,
+    headers: &[(String, String)],
+    tls: &TlsSettings,
+    proxy: &ProxySettings,
+) -> anyhow::Result<(Socket, u16)> {
+    let mut request = url
+        .into_client_request()
+        .with_context(|| format!("Invalid WebSocket URL {}", url))?;
+    for (key, value) in headers {
+        let name = HeaderName::from_bytes(key.as_bytes())
+            .with_context(|| format!("Invalid header name '{}'", key))?;
+        let value = HeaderValue::from_str(value)
+            .with_context(|| format!("Invalid value for header '{}'", key))?;
+        request.headers_mut().insert(name, value);
+    }
+
+    let uri = request.uri();
+    let secure = match uri.scheme_str() {
+        Some("ws") => false,
+        Some("wss") => true,
+        _ => return Err(anyhow!("WebSocket URLs start with ws:// or wss://")),
+    };
+    let host = uri
+        .host()
+        .ok_or_else(|| anyhow!("Missing host in {}", url))?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string();
+    let port = uri.port_u16().unwrap_or(if secure { 443 } else { 80 });
+
+    let stream = match proxy.proxy_for(url) {
+        Some(through) => {
+            let (proxy_host, proxy_port) = proxy::http_proxy_address(&through)?;
+            let mut stream = connect_tcp(&proxy_host, proxy_port)?;
+            stream.set_read_timeout(Some(CONNECT_TIMEOUT))?;
+            proxy::tunnel(&mut stream, &through, proxy, &host, port)?;
+            stream
+        }
+        None => connect_tcp(&host, port)?,
+    };
+    let connector = if secure {
+        Connector::NativeTls(tls::connector(tls)?)
+    } else {
+        Connector::Plain
+    };
+    let (socket, response) =
+        tungstenite::client_tls_with_config(request, stream, None, Some(connector))
+            .map_err(|e| anyhow!("{}", e))?;
+
+    let tcp = match socket.get_ref() {
+        MaybeTlsStream::Plain(stream) => stream,
+        MaybeTlsStream::NativeTls(stream) => stream.get_ref(),
+        _ => return Err(anyhow!("Unsupported stream type")),
+    };
+    tcp.set_read_timeout(Some(READ_TIMEOUT))?;
+    Ok((socket, response.status().as_u16()))
+}
+
+fn connect_tcp(host: &str, port: u16) -> anyhow::Result<TcpStream> {
+    let mut last_error = None;
+    for addr in (host, port)
+        .to_socket_addrs()
+        .with_context(|| format!("Failed to resolve {}", host))?
+    {
+        match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(match last_error {
+        Some(e) => anyhow!("Failed to connect to {}:{}: {}", host, port, e),
+        None => anyhow!("No addresses found for {}", host),
+    })
+}
+
+#[cfg(test)]
+mod tests;
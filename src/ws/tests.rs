@@ -0,0 +1,241 @@
+use std::net::TcpListener;
+use std::time::Instant;
+
+use super::*;
+
+/// Accept one WebSocket connection and echo data frames back until the
+/// client closes. Returns a `ws://` URL.
+fn echo_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut socket = tungstenite::accept(stream).unwrap();
+        while let Ok(message) = socket.read() {
+            if message.is_text() || message.is_binary() {
+                socket.send(message).unwrap();
+            }
+        }
+    });
+    format!("ws://{}", addr)
+}
+
+/// Poll until `done` holds for the log, failing after a few seconds
+fn wait_for(
+    connection: &mut WsConnection,
+    log: &mut Vec<LogEntry>,
+    done: impl Fn(&[LogEntry]) -> bool,
+) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !done(log) {
+        assert!(Instant::now() < deadline, "timed out, log: {:?}", log);
+        log.extend(connection.poll());
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn test_frame_type_cycles() {
+    assert_eq!(FrameType::Text.next(), FrameType::Json);
+    assert_eq!(FrameType::Binary.next(), FrameType::Text);
+    assert_eq!(FrameType::Json.label(), "JSON");
+}
+
+#[test]
+fn test_encode_frames() {
+    assert_eq!(FrameType::Text.encode("hi").unwrap(), Message::text("hi"));
+    assert_eq!(
+        FrameType::Json.encode("{ \"a\": 1 }").unwrap(),
+        Message::text("{\"a\":1}")
+    );
+    assert!(FrameType::Json
+        .encode("{nope")
+        .unwrap_err()
+        .starts_with("Invalid JSON"));
+    assert_eq!(
+        FrameType::Binary.encode("48 65 6c\n6C 6f").unwrap(),
+        Message::binary(b"Hello".to_vec())
+    );
+    assert_eq!(
+        FrameType::Binary.encode("0x0aff").unwrap(),
+        Message::binary(vec![0x0a, 0xff])
+    );
+    assert!(FrameType::Binary.encode("abc").is_err());
+    assert_eq!(
+        FrameType::Binary.encode("zz").unwrap_err(),
+        "Invalid hex byte 'zz'"
+    );
+    assert_eq!(
+        FrameType::Binary.encode("aébb").unwrap_err(),
+        "Invalid hex byte 'aé'"
+    );
+    assert!(FrameType::Binary.encode("aéb").is_err());
+}
+
+#[test]
+fn test_describe_frames() {
+    assert_eq!(describe(&Message::text("hi")), "text hi");
+    assert_eq!(
+        describe(&Message::binary(vec![1, 2, 255])),
+        "binary 3 bytes: 01 02 ff"
+    );
+    assert_eq!(
+        describe(&Message::binary(vec![0; 40])),
+        format!("binary 40 bytes: {} …", vec!["00"; 32].join(" "))
+    );
+    assert_eq!(describe(&Message::Ping(Vec::new().into())), "ping");
+    assert_eq!(
+        describe(&Message::Pong(b"beat".to_vec().into())),
+        "pong beat"
+    );
+    assert_eq!(
+        describe(&Message::Close(Some(tungstenite::protocol::CloseFrame {
+            code: tungstenite::protocol::frame::coding::CloseCode::Normal,
+            reason: "bye".into(),
+        }))),
+        "close 1000 (Normal) bye"
+    );
+}
+
+#[test]
+fn test_log_entry_line() {
+    let entry = LogEntry::new(Direction::Received, "text hi");
+    let line = entry.line();
+    assert!(line.ends_with(" ← text hi"), "{}", line);
+    // HH:MM:SS.mmm
+    assert_eq!(line.find(' '), Some(12));
+}
+
+#[test]
+fn test_connect_send_and_close() {
+    let url = echo_server();
+    let mut connection = WsConnection::connect(
+        &url,
+        Vec::new(),
+        &TlsSettings::default(),
+        &ProxySettings::default(),
+    );
+    let mut log = Vec::new();
+    wait_for(&mut connection, &mut log, |log| !log.is_empty());
+    assert_eq!(log[0].direction, Direction::Info);
+    assert_eq!(log[0].text, format!("connected to {} (101)", url));
+
+    connection.send(Message::text("hello"));
+    wait_for(&mut connection, &mut log, |log| {
+        log.iter().any(|e| e.direction == Direction::Received)
+    });
+    assert_eq!(log[1].direction, Direction::Sent);
+    assert_eq!(log[1].text, "text hello");
+    assert_eq!(log[2].text, "text hello");
+
+    connection.close();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while connection.is_open() {
+        assert!(Instant::now() < deadline, "timed out, log: {:?}", log);
+        log.extend(connection.poll());
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(log
+        .iter()
+        .any(|e| e.direction == Direction::Sent && e.text == "close"));
+    assert_eq!(log.last().unwrap().text, "connection closed");
+}
+
+/// HTTP proxy that answers one `CONNECT` and relays bytes both ways.
+/// Returns its URL and the request line it received.
+fn connect_proxy() -> (String, Receiver<String>) {
+    use std::io::{BufRead, BufReader, Write};
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (request_line, received) = mpsc::channel();
+    std::thread::spawn(move || {
+        let (client, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(client.try_clone().unwrap());
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let target = line.split_whitespace().nth(1).unwrap().to_string();
+        request_line.send(line.trim_end().to_string()).unwrap();
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).unwrap();
+            if header == "\r\n" {
+                break;
+            }
+        }
+        let upstream = TcpStream::connect(target).unwrap();
+        (&client)
+            .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+            .unwrap();
+        let mut upstream_reader = upstream.try_clone().unwrap();
+        let mut client_writer = client.try_clone().unwrap();
+        std::thread::spawn(move || std::io::copy(&mut upstream_reader, &mut client_writer));
+        let _ = std::io::copy(&mut reader, &mut &upstream);
+    });
+    (format!("http://{}", addr), received)
+}
+
+#[test]
+fn test_connect_through_http_proxy() {
+    let url = echo_server();
+    let (proxy_url, request_line) = connect_proxy();
+    let proxy = ProxySettings {
+        mode: crate::proxy::ProxyMode::Manual,
+        http: proxy_url,
+        ..ProxySettings::default()
+    };
+
+    let mut connection = WsConnection::connect(&url, Vec::new(), &TlsSettings::default(), &proxy);
+    let mut log = Vec::new();
+    wait_for(&mut connection, &mut log, |log| !log.is_empty());
+    assert_eq!(log[0].text, format!("connected to {} (101)", url));
+    assert_eq!(
+        request_line.recv().unwrap(),
+        format!("CONNECT {} HTTP/1.1", url.trim_start_matches("ws://"))
+    );
+
+    connection.send(Message::text("via proxy"));
+    wait_for(&mut connection, &mut log, |log| {
+        log.iter().any(|e| e.direction == Direction::Received)
+    });
+    assert_eq!(log[2].text, "text via proxy");
+}
+
+#[test]
+fn test_socks_proxy_is_refused() {
+    let proxy = ProxySettings {
+        mode: crate::proxy::ProxyMode::Manual,
+        http: "socks5://127.0.0.1:1".to_string(),
+        ..ProxySettings::default()
+    };
+
+    let mut connection = WsConnection::connect(
+        "ws://127.0.0.1:1",
+        Vec::new(),
+        &TlsSettings::default(),
+        &proxy,
+    );
+    let mut log = Vec::new();
+    wait_for(&mut connection, &mut log, |log| !log.is_empty());
+    assert_eq!(
+        log[0].text,
+        "connection failed: Only http:// proxies can tunnel this connection, not socks5://127.0.0.1:1"
+    );
+}
+
+#[test]
+fn test_connection_failure_is_logged() {
+    let mut connection = WsConnection::connect(
+        "http://127.0.0.1:1",
+        Vec::new(),
+        &TlsSettings::default(),
+        &ProxySettings::default(),
+    );
+    let mut log = Vec::new();
+    wait_for(&mut connection, &mut log, |log| !log.is_empty());
+    assert!(
+        log[0].text.starts_with("connection failed"),
+        "{}",
+        log[0].text
+    );
+}